@@ -0,0 +1,288 @@
+use anyhow::{Context, Result};
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::str::FromStr;
+use uuid::Uuid;
+use ectd_service::EctdService;
+use crate::error::ApiError;
+use crate::AppState;
+
+/// Application-level roles, carried in the token's `roles` claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Author,
+    Reviewer,
+    Publisher,
+    Admin,
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "author" => Ok(Role::Author),
+            "reviewer" => Ok(Role::Reviewer),
+            "publisher" => Ok(Role::Publisher),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("Unknown role '{}'", other)),
+        }
+    }
+}
+
+/// What a handler is about to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Read units, contexts, keywords and their audit trail
+    Read,
+    /// Create and change units, upload documents, edit contexts and keywords
+    Edit,
+    /// Run the validation profile
+    Validate,
+    /// Produce transmission packages
+    Export,
+    /// Read and verify the whole audit trail, manage submission templates
+    Administer,
+}
+
+impl Role {
+    pub fn grants(self, permission: Permission) -> bool {
+        use Permission::*;
+        match self {
+            Role::Admin => true,
+            Role::Author => matches!(permission, Read | Edit | Validate),
+            Role::Reviewer => matches!(permission, Read | Validate),
+            Role::Publisher => matches!(permission, Read | Validate | Export),
+        }
+    }
+}
+
+/// Token claims. `applications` lists the application numbers the user may
+/// work on; `"*"` means all of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: u64,
+    /// Role names; names this API doesn't know (e.g. other IdP groups) are ignored.
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub applications: Vec<String>,
+}
+
+/// How bearer tokens are checked. Configured from the environment:
+///
+/// - `AUTH_JWT_SECRET`: shared secret, HS256
+/// - `AUTH_JWT_PUBLIC_KEY`: path to a PEM public key, for tokens issued by an
+///   identity provider (OIDC); `AUTH_JWT_ALGORITHM` picks RS256 (default), PS256 or ES256
+/// - `AUTH_ISSUER` / `AUTH_AUDIENCE`: required `iss` / `aud` values, when set
+/// - `AUTH_DISABLED=true`: no checks, every request acts as an admin (local development only)
+pub enum AuthConfig {
+    Jwt { key: DecodingKey, validation: Box<Validation> },
+    Disabled,
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Result<Self> {
+        if env::var("AUTH_DISABLED").is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes")) {
+            return Ok(AuthConfig::Disabled);
+        }
+
+        let (key, algorithm) = if let Ok(secret) = env::var("AUTH_JWT_SECRET") {
+            (DecodingKey::from_secret(secret.as_bytes()), Algorithm::HS256)
+        } else if let Ok(path) = env::var("AUTH_JWT_PUBLIC_KEY") {
+            let pem = std::fs::read(&path).context(format!("Failed to read AUTH_JWT_PUBLIC_KEY {}", path))?;
+            let algorithm = env::var("AUTH_JWT_ALGORITHM").unwrap_or_else(|_| "RS256".to_string());
+            match algorithm.to_uppercase().as_str() {
+                "RS256" => (DecodingKey::from_rsa_pem(&pem)?, Algorithm::RS256),
+                "PS256" => (DecodingKey::from_rsa_pem(&pem)?, Algorithm::PS256),
+                "ES256" => (DecodingKey::from_ec_pem(&pem)?, Algorithm::ES256),
+                other => anyhow::bail!("Unsupported AUTH_JWT_ALGORITHM '{}' (expected RS256, PS256 or ES256)", other),
+            }
+        } else {
+            anyhow::bail!("Set AUTH_JWT_SECRET or AUTH_JWT_PUBLIC_KEY (or AUTH_DISABLED=true for local development)");
+        };
+
+        Ok(Self::jwt(
+            key,
+            algorithm,
+            env::var("AUTH_ISSUER").ok().as_deref(),
+            env::var("AUTH_AUDIENCE").ok().as_deref(),
+        ))
+    }
+
+    pub fn jwt(key: DecodingKey, algorithm: Algorithm, issuer: Option<&str>, audience: Option<&str>) -> Self {
+        let mut validation = Validation::new(algorithm);
+        if let Some(issuer) = issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        AuthConfig::Jwt { key, validation: Box::new(validation) }
+    }
+
+    /// Checks an `Authorization` header value.
+    pub fn authenticate(&self, header: Option<&str>) -> Result<User, ApiError> {
+        let (key, validation) = match self {
+            AuthConfig::Disabled => return Ok(User::development()),
+            AuthConfig::Jwt { key, validation } => (key, validation),
+        };
+
+        let token = header
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .ok_or_else(|| unauthorized("Missing bearer token"))?;
+        let claims = decode::<Claims>(token.trim(), key, validation)
+            .map_err(|e| unauthorized(format!("Invalid token: {}", e)))?
+            .claims;
+
+        Ok(User {
+            subject: claims.sub,
+            roles: claims.roles.iter().filter_map(|r| r.parse().ok()).collect(),
+            applications: claims.applications,
+        })
+    }
+}
+
+/// The caller, as established by the bearer token.
+#[derive(Debug, Clone)]
+pub struct User {
+    pub subject: String,
+    pub roles: Vec<Role>,
+    pub applications: Vec<String>,
+}
+
+impl User {
+    fn development() -> Self {
+        User { subject: "api".to_string(), roles: vec![Role::Admin], applications: vec!["*".to_string()] }
+    }
+
+    pub fn has(&self, permission: Permission) -> bool {
+        self.roles.iter().any(|r| r.grants(permission))
+    }
+
+    pub fn can_access(&self, application_number: &str) -> bool {
+        self.roles.contains(&Role::Admin)
+            || self.applications.iter().any(|a| a == "*" || a == application_number)
+    }
+
+    /// The applications this user is limited to, or `None` for all of them.
+    pub fn application_scope(&self) -> Option<Vec<String>> {
+        if self.roles.contains(&Role::Admin) || self.applications.iter().any(|a| a == "*") {
+            None
+        } else {
+            Some(self.applications.clone())
+        }
+    }
+
+    pub fn require(&self, permission: Permission) -> Result<(), ApiError> {
+        if self.has(permission) {
+            Ok(())
+        } else {
+            Err(forbidden(format!("{} may not perform this action ({:?} permission required)", self.subject, permission)))
+        }
+    }
+
+    pub fn require_application(&self, application_number: &str) -> Result<(), ApiError> {
+        if self.can_access(application_number) {
+            Ok(())
+        } else {
+            Err(forbidden(format!("{} has no access to application {}", self.subject, application_number)))
+        }
+    }
+
+    /// The service acting on this user's behalf, so the audit trail names them.
+    pub fn service(&self, state: &AppState) -> EctdService {
+        state.service.clone().with_actor(self.subject.clone())
+    }
+
+    /// Checks the permission and access to the unit's application, then
+    /// returns the service acting as this user.
+    pub async fn authorize(&self, state: &AppState, unit_id: Uuid, permission: Permission) -> Result<EctdService, ApiError> {
+        self.require(permission)?;
+        let application = state.service.submission_application(unit_id).await?;
+        self.require_application(&application)?;
+        Ok(self.service(state))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for User {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let header = parts.headers.get(AUTHORIZATION).and_then(|v| v.to_str().ok());
+        state.auth.authenticate(header)
+    }
+}
+
+fn unauthorized(message: impl Into<String>) -> ApiError {
+    ApiError::new(StatusCode::UNAUTHORIZED, "unauthorized", message)
+}
+
+fn forbidden(message: impl Into<String>) -> ApiError {
+    ApiError::new(StatusCode::FORBIDDEN, "forbidden", message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn token(secret: &[u8], roles: &[&str], applications: &[&str], ttl_secs: i64) -> String {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        let claims = Claims {
+            sub: "alice".to_string(),
+            exp: (now + ttl_secs) as u64,
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            applications: applications.iter().map(|a| a.to_string()).collect(),
+        };
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    fn config() -> AuthConfig {
+        AuthConfig::jwt(DecodingKey::from_secret(b"s3cret"), Algorithm::HS256, None, None)
+    }
+
+    #[test]
+    fn test_valid_token_yields_user() {
+        let header = format!("Bearer {}", token(b"s3cret", &["author", "sso-users"], &["123456"], 300));
+        let user = config().authenticate(Some(&header)).unwrap();
+
+        assert_eq!(user.subject, "alice");
+        assert_eq!(user.roles, vec![Role::Author]);
+        assert!(user.has(Permission::Edit));
+        assert!(!user.has(Permission::Export));
+        assert!(user.can_access("123456"));
+        assert!(!user.can_access("654321"));
+    }
+
+    #[test]
+    fn test_bad_tokens_are_unauthorized() {
+        let cfg = config();
+        let expired = format!("Bearer {}", token(b"s3cret", &["admin"], &["*"], -3600));
+        let forged = format!("Bearer {}", token(b"other", &["admin"], &["*"], 300));
+
+        for header in [None, Some("Basic abc"), Some(expired.as_str()), Some(forged.as_str())] {
+            let err = cfg.authenticate(header).unwrap_err();
+            assert_eq!(err.status, StatusCode::UNAUTHORIZED, "{:?}", header);
+        }
+    }
+
+    #[test]
+    fn test_role_grants() {
+        assert!(Role::Publisher.grants(Permission::Export));
+        assert!(!Role::Reviewer.grants(Permission::Edit));
+        assert!(!Role::Author.grants(Permission::Administer));
+        assert!(Role::Admin.grants(Permission::Administer));
+    }
+}
@@ -0,0 +1,183 @@
+use axum::{
+    extract::{
+        multipart::MultipartError,
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        FromRequest, FromRequestParts,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ectd_service::ServiceError;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Every error response has the same body:
+/// `{"error": {"code": "not_found", "message": "Submission unit … not found"}}`
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into() }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_request", message)
+    }
+
+    fn internal() -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal", "Internal server error")
+    }
+
+    /// Extractor rejections keep axum's status and message.
+    fn rejected(status: StatusCode, message: String) -> Self {
+        let code = match status {
+            StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+            StatusCode::UNPROCESSABLE_ENTITY => "invalid_request",
+            _ => "bad_request",
+        };
+        Self::new(status, code, message)
+    }
+}
+
+/// Response body of every failed request.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: ErrorDetail,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorDetail {
+    /// Stable, machine-readable: not_found, invalid_request, conflict, bad_request, internal, …
+    pub code: String,
+    pub message: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: ErrorDetail { code: self.code.to_string(), message: self.message },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+/// Maps service failures onto HTTP statuses. Anything unrecognised is a 500;
+/// its details go to the log, not to the client.
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        for cause in err.chain() {
+            if let Some(e) = cause.downcast_ref::<ServiceError>() {
+                return match e {
+                    ServiceError::NotFound(_) => Self::new(StatusCode::NOT_FOUND, "not_found", e.to_string()),
+                    ServiceError::Invalid(_) => Self::invalid(e.to_string()),
+                    ServiceError::Conflict(_) => Self::new(StatusCode::CONFLICT, "conflict", e.to_string()),
+                };
+            }
+            if let Some(e) = cause.downcast_ref::<sqlx::Error>() {
+                match e {
+                    sqlx::Error::RowNotFound => {
+                        return Self::new(StatusCode::NOT_FOUND, "not_found", err.to_string());
+                    }
+                    sqlx::Error::Database(db) => match db.code().as_deref() {
+                        // unique / foreign key violation
+                        Some("23505") | Some("23503") => {
+                            return Self::new(StatusCode::CONFLICT, "conflict", db.message().to_string());
+                        }
+                        // check violation, value too long, malformed input
+                        Some("23514") | Some("22001") | Some("22P02") => {
+                            return Self::invalid(db.message().to_string());
+                        }
+                        _ => {}
+                    },
+                    _ => {}
+                }
+            }
+        }
+
+        tracing::error!("Request failed: {:?}", err);
+        Self::internal()
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::rejected(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        Self::rejected(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::rejected(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<MultipartError> for ApiError {
+    fn from(err: MultipartError) -> Self {
+        Self::rejected(err.status(), err.body_text())
+    }
+}
+
+/// `axum::Json`, rejecting malformed bodies with an `ApiError`.
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(ApiError))]
+pub struct ApiJson<T>(pub T);
+
+impl<T: serde::Serialize> IntoResponse for ApiJson<T> {
+    fn into_response(self) -> Response {
+        Json(self.0).into_response()
+    }
+}
+
+/// `axum::extract::Path`, rejecting malformed ids with an `ApiError`.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiError))]
+pub struct ApiPath<T>(pub T);
+
+/// `axum::extract::Query`, rejecting malformed parameters with an `ApiError`.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(ApiError))]
+pub struct ApiQuery<T>(pub T);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_errors_map_to_statuses() {
+        let id = uuid::Uuid::nil();
+        let not_found: ApiError = anyhow::Error::new(ServiceError::submission_not_found(id))
+            .context("while exporting")
+            .into();
+        assert_eq!(not_found.status, StatusCode::NOT_FOUND);
+        assert_eq!(not_found.code, "not_found");
+
+        let invalid: ApiError = anyhow::Error::new(ServiceError::Invalid("priority must be 1 or greater".into())).into();
+        assert_eq!(invalid.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(invalid.message, "priority must be 1 or greater");
+
+        let conflict: ApiError = anyhow::Error::new(ServiceError::Conflict("sequence 0002 exists".into())).into();
+        assert_eq!(conflict.status, StatusCode::CONFLICT);
+        assert_eq!(conflict.code, "conflict");
+
+        let other: ApiError = anyhow::anyhow!("bucket unreachable: secret-host:9000").into();
+        assert_eq!(other.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!other.message.contains("secret-host"));
+    }
+}
@@ -0,0 +1,71 @@
+use axum::{extract::State, http::StatusCode};
+use ectd_db::application::ApplicationRecord;
+use ectd_service::{ApplicationPatch, NewApplicationParams};
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ErrorBody};
+use crate::AppState;
+
+#[utoipa::path(
+    post,
+    path = "/applications",
+    tag = "applications",
+    request_body = NewApplicationParams,
+    responses(
+        (status = 201, description = "Application created", body = ApplicationRecord),
+        (status = 409, description = "An application with this number exists", body = ErrorBody),
+        (status = 422, description = "Invalid application", body = ErrorBody),
+    )
+)]
+pub async fn create_application(
+    user: User,
+    State(state): State<AppState>,
+    ApiJson(params): ApiJson<NewApplicationParams>,
+) -> Result<(StatusCode, ApiJson<ApplicationRecord>), ApiError> {
+    user.require(Permission::Edit)?;
+    user.require_application(&params.application_number)?;
+    let app = user.service(&state).create_application(params).await?;
+    Ok((StatusCode::CREATED, ApiJson(app)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/applications/{number}",
+    tag = "applications",
+    params(("number" = String, Path, description = "Application number")),
+    responses(
+        (status = 200, description = "The application and its next sequence number", body = ApplicationRecord),
+        (status = 404, description = "Unknown application", body = ErrorBody),
+    )
+)]
+pub async fn get_application(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(number): ApiPath<String>,
+) -> Result<ApiJson<ApplicationRecord>, ApiError> {
+    user.require(Permission::Read)?;
+    user.require_application(&number)?;
+    Ok(ApiJson(state.service.get_application(&number).await?))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/applications/{number}",
+    tag = "applications",
+    params(("number" = String, Path, description = "Application number")),
+    request_body = ApplicationPatch,
+    responses(
+        (status = 200, description = "The updated application; its units carry the change", body = ApplicationRecord),
+        (status = 404, description = "Unknown application", body = ErrorBody),
+        (status = 422, description = "Invalid change", body = ErrorBody),
+    )
+)]
+pub async fn update_application(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(number): ApiPath<String>,
+    ApiJson(patch): ApiJson<ApplicationPatch>,
+) -> Result<ApiJson<ApplicationRecord>, ApiError> {
+    user.require(Permission::Edit)?;
+    user.require_application(&number)?;
+    Ok(ApiJson(user.service(&state).update_application(&number, patch).await?))
+}
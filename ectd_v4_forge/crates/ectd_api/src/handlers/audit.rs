@@ -0,0 +1,76 @@
+use axum::extract::State;
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+use ectd_db::audit::{AuditQuery, AuditRecord, ChainVerification};
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ApiQuery};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AuditParams {
+    pub entity_id: Option<Uuid>,
+    /// Records about the unit and about its documents, contexts of use and placeholders
+    pub submission_unit_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/audit",
+    tag = "audit",
+    params(AuditParams),
+    responses((status = 200, description = "Matching audit records, in chain order", body = Vec<AuditRecord>))
+)]
+pub async fn list_audit(
+    user: User,
+    State(state): State<AppState>,
+    ApiQuery(params): ApiQuery<AuditParams>,
+) -> Result<ApiJson<Vec<AuditRecord>>, ApiError> {
+    user.require(Permission::Administer)?;
+    let query = AuditQuery {
+        entity_id: params.entity_id,
+        submission_unit_id: params.submission_unit_id,
+        actor: params.actor,
+        action: params.action,
+        limit: params.limit,
+    };
+    fetch(state, query).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}/audit",
+    tag = "audit",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses((status = 200, description = "Audit records of the unit and of its documents, contexts of use and placeholders, in chain order", body = Vec<AuditRecord>))
+)]
+pub async fn submission_audit(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<Vec<AuditRecord>>, ApiError> {
+    user.authorize(&state, id, Permission::Read).await?;
+    let query = AuditQuery { submission_unit_id: Some(id), ..Default::default() };
+    fetch(state, query).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/audit/verify",
+    tag = "audit",
+    responses((status = 200, description = "Result of re-walking the hash chain", body = ChainVerification))
+)]
+pub async fn verify_audit(
+    user: User,
+    State(state): State<AppState>,
+) -> Result<ApiJson<ChainVerification>, ApiError> {
+    user.require(Permission::Administer)?;
+    Ok(ApiJson(state.service.verify_audit_chain().await?))
+}
+
+async fn fetch(state: AppState, query: AuditQuery) -> Result<ApiJson<Vec<AuditRecord>>, ApiError> {
+    Ok(ApiJson(state.service.audit_trail(&query).await?))
+}
@@ -0,0 +1,263 @@
+use axum::{extract::State, http::StatusCode};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use ectd_core::models::context_of_use::ContextOfUse;
+use ectd_core::models::keyword_definition::{KeywordDefinition, KeywordType};
+use ectd_db::keyword::KeywordEntry;
+use ectd_db::priority::ViewContext;
+use ectd_service::{ContextPatch, MoveContextParams, ReorderContextsParams};
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ApiQuery, ErrorBody};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct KeywordRequest {
+    /// Defaults to "sponsor"
+    #[serde(rename = "type", default)]
+    pub keyword_type: KeywordType,
+    pub display_name: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct KeywordTypeParams {
+    /// Keyword type (default "sponsor")
+    #[serde(rename = "type", default)]
+    #[param(rename = "type", value_type = Option<KeywordType>)]
+    pub keyword_type: KeywordType,
+}
+
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}/contexts",
+    tag = "contexts",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses(
+        (status = 200, description = "Contexts of use of the unit", body = Vec<ContextOfUse>),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
+pub async fn list_contexts(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<Vec<ContextOfUse>>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Read).await?;
+    Ok(ApiJson(service.list_contexts(id).await?))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/submissions/{id}/contexts/{context_id}",
+    tag = "contexts",
+    params(
+        ("id" = Uuid, Path, description = "Submission unit id"),
+        ("context_id" = Uuid, Path, description = "Context of use id"),
+    ),
+    request_body = ContextPatch,
+    responses(
+        (status = 200, description = "The updated context of use", body = ContextOfUse),
+        (status = 404, description = "Unknown unit or context of use", body = ErrorBody),
+        (status = 422, description = "Invalid change", body = ErrorBody),
+    )
+)]
+pub async fn update_context(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((id, context_id)): ApiPath<(Uuid, Uuid)>,
+    ApiJson(patch): ApiJson<ContextPatch>,
+) -> Result<ApiJson<ContextOfUse>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+    Ok(ApiJson(service.update_context(id, context_id, patch).await?))
+}
+
+/// Contexts in force for the unit, by code and priority: its own and those of
+/// earlier sequences not replaced or suspended.
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}/current-view",
+    tag = "contexts",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses(
+        (status = 200, description = "The current view as of the unit", body = Vec<ViewContext>),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
+pub async fn current_view(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<Vec<ViewContext>>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Read).await?;
+    Ok(ApiJson(service.current_view(id).await?))
+}
+
+/// Swaps a context with its neighbour among the unit's contexts of its code
+/// and renumbers them after the earlier sequences' priorities.
+#[utoipa::path(
+    post,
+    path = "/submissions/{id}/contexts/{context_id}/move",
+    tag = "contexts",
+    params(
+        ("id" = Uuid, Path, description = "Submission unit id"),
+        ("context_id" = Uuid, Path, description = "Context of use id"),
+    ),
+    request_body = MoveContextParams,
+    responses(
+        (status = 200, description = "The code's contexts in the current view", body = Vec<ViewContext>),
+        (status = 404, description = "Unknown unit or context of use", body = ErrorBody),
+        (status = 422, description = "Already first or last, or suspended", body = ErrorBody),
+    )
+)]
+pub async fn move_context(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((id, context_id)): ApiPath<(Uuid, Uuid)>,
+    ApiJson(params): ApiJson<MoveContextParams>,
+) -> Result<ApiJson<Vec<ViewContext>>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+    Ok(ApiJson(service.move_context(id, context_id, params.direction).await?))
+}
+
+/// Renumbers all of the unit's active contexts of a code in the given order.
+#[utoipa::path(
+    post,
+    path = "/submissions/{id}/contexts/reorder",
+    tag = "contexts",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    request_body = ReorderContextsParams,
+    responses(
+        (status = 200, description = "The code's contexts in the current view", body = Vec<ViewContext>),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+        (status = 422, description = "The order doesn't list each of the code's contexts once", body = ErrorBody),
+    )
+)]
+pub async fn reorder_contexts(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiJson(params): ApiJson<ReorderContextsParams>,
+) -> Result<ApiJson<Vec<ViewContext>>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+    Ok(ApiJson(service.reorder_contexts(id, params).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}/keywords",
+    tag = "keywords",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses(
+        (status = 200, description = "Keyword definitions the unit submits", body = Vec<KeywordDefinition>),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
+pub async fn list_keywords(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<Vec<KeywordDefinition>>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Read).await?;
+    Ok(ApiJson(service.list_keywords(id).await?))
+}
+
+/// Keywords the unit's contexts may use: those defined by this or an earlier
+/// sequence of the application, each as its latest sequence left it.
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}/vocabulary",
+    tag = "keywords",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses(
+        (status = 200, description = "Keywords in force for the unit", body = Vec<KeywordEntry>),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
+pub async fn keyword_vocabulary(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<Vec<KeywordEntry>>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Read).await?;
+    Ok(ApiJson(service.keyword_vocabulary(id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/submissions/{id}/keywords/{code}",
+    tag = "keywords",
+    params(
+        ("id" = Uuid, Path, description = "Submission unit id"),
+        ("code" = String, Path, description = "Keyword code"),
+    ),
+    request_body = KeywordRequest,
+    responses(
+        (status = 200, description = "The keyword definition, created or renamed", body = KeywordDefinition),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+        (status = 422, description = "Invalid keyword", body = ErrorBody),
+    )
+)]
+pub async fn put_keyword(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((id, code)): ApiPath<(Uuid, String)>,
+    ApiJson(req): ApiJson<KeywordRequest>,
+) -> Result<ApiJson<KeywordDefinition>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+    Ok(ApiJson(service.put_keyword(id, req.keyword_type, &code, &req.display_name).await?))
+}
+
+/// Retires a keyword from this sequence on; later sequences can't use it.
+#[utoipa::path(
+    post,
+    path = "/submissions/{id}/keywords/{code}/retire",
+    tag = "keywords",
+    params(
+        ("id" = Uuid, Path, description = "Submission unit id"),
+        ("code" = String, Path, description = "Keyword code"),
+        KeywordTypeParams,
+    ),
+    responses(
+        (status = 200, description = "The retiring definition", body = KeywordDefinition),
+        (status = 404, description = "Unknown unit or keyword", body = ErrorBody),
+        (status = 409, description = "Already retired, or still tagging contexts of use", body = ErrorBody),
+    )
+)]
+pub async fn retire_keyword(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((id, code)): ApiPath<(Uuid, String)>,
+    ApiQuery(params): ApiQuery<KeywordTypeParams>,
+) -> Result<ApiJson<KeywordDefinition>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+    Ok(ApiJson(service.retire_keyword(id, params.keyword_type, &code).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/submissions/{id}/keywords/{code}",
+    tag = "keywords",
+    params(
+        ("id" = Uuid, Path, description = "Submission unit id"),
+        ("code" = String, Path, description = "Keyword code"),
+        KeywordTypeParams,
+    ),
+    responses(
+        (status = 204, description = "Keyword definition removed"),
+        (status = 404, description = "Unknown unit or keyword", body = ErrorBody),
+        (status = 409, description = "Still tagging contexts of use, with no earlier definition", body = ErrorBody),
+    )
+)]
+pub async fn delete_keyword(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((id, code)): ApiPath<(Uuid, String)>,
+    ApiQuery(params): ApiQuery<KeywordTypeParams>,
+) -> Result<StatusCode, ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+    service.delete_keyword(id, params.keyword_type, &code).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,220 @@
+use axum::{
+    extract::{multipart::Field, Multipart, State},
+    http::StatusCode,
+};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use ectd_db::listing::DocumentSummary;
+use ectd_service::{AddDocumentParams, AttachedDocument, DocumentReuse, EctdService, KeywordRef};
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ApiQuery, ErrorBody};
+use crate::AppState;
+
+/// The form accepted by [`attach_document`]; only used to document it.
+#[allow(dead_code)]
+#[derive(ToSchema)]
+#[schema(rename_all = "camelCase")]
+pub struct AttachDocumentForm {
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+    /// e.g. "cover-letter"
+    context_code: String,
+    /// Defaults to the file name
+    title: Option<String>,
+    /// Defaults to the placeholder's priority, else 1
+    priority: Option<u32>,
+    /// The placeholder the document delivers; defaults to the open placeholder
+    /// of the same context code that is due first
+    #[schema(value_type = Option<String>, format = Uuid)]
+    placeholder_id: Option<Uuid>,
+    /// Repeatable; "type:code" (e.g. "study-id:ABC-101"), or a bare sponsor keyword code
+    keyword: Vec<String>,
+    /// Package folder, e.g. "m5/53-clin-stud-rep/535-rep-effic-safety-stud/study-101";
+    /// defaults to the placeholder's, else the one for the context code
+    folder: Option<String>,
+    /// If the file was already submitted: "ask" (default; 409 naming the
+    /// earlier document), "reference" it instead of storing a copy, or "copy"
+    #[schema(value_type = Option<DocumentReuse>)]
+    reuse: Option<String>,
+}
+
+/// `multipart/form-data` with a `file` part plus `contextCode`, and optionally
+/// `title` (defaults to the file name), `priority` (defaults to the
+/// placeholder's, else 1), `placeholderId`, `folder`, `reuse` and any number
+/// of `keyword` parts.
+#[utoipa::path(
+    post,
+    path = "/submissions/{id}/documents",
+    tag = "documents",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    request_body(content = AttachDocumentForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Document stored and attached, or an identical submitted one referenced", body = AttachedDocument),
+        (status = 404, description = "Unknown submission unit or placeholder", body = ErrorBody),
+        (status = 409, description = "The placeholder is already fulfilled, or the file was already submitted (reuse \"ask\")", body = ErrorBody),
+        (status = 413, description = "Upload exceeds MAX_UPLOAD_MB", body = ErrorBody),
+        (status = 422, description = "Missing fields, unknown keywords or failed PDF checks", body = ErrorBody),
+    )
+)]
+pub async fn attach_document(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+    multipart: Multipart,
+) -> Result<(StatusCode, ApiJson<AttachedDocument>), ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+
+    // The service takes a file path, so the upload is spooled to a private folder first
+    let staging = std::env::temp_dir().join(format!("ectd-upload-{}", Uuid::new_v4()));
+    tokio::fs::create_dir_all(&staging).await
+        .map_err(|e| anyhow::anyhow!("Failed to create upload staging dir {:?}: {}", staging, e))?;
+
+    let result = receive_and_attach(&service, id, multipart, &staging).await;
+    let _ = tokio::fs::remove_dir_all(&staging).await;
+
+    Ok((StatusCode::CREATED, ApiJson(result?)))
+}
+
+async fn receive_and_attach(
+    service: &EctdService,
+    id: Uuid,
+    mut multipart: Multipart,
+    staging: &Path,
+) -> Result<AttachedDocument, ApiError> {
+    let mut file_path: Option<PathBuf> = None;
+    let mut context_code = None;
+    let mut title = None;
+    let mut priority = None;
+    let mut placeholder_id = None;
+    let mut keywords = Vec::new();
+    let mut folder = None;
+    let mut reuse = DocumentReuse::default();
+
+    while let Some(mut field) = multipart.next_field().await? {
+        match field.name().unwrap_or_default() {
+            "file" => file_path = Some(stage_upload(&mut field, staging).await?),
+            "contextCode" => context_code = Some(field.text().await?),
+            "title" => title = Some(field.text().await?),
+            "priority" => {
+                let text = field.text().await?;
+                priority = Some(text.trim().parse::<u32>().ok().filter(|p| *p > 0)
+                    .ok_or_else(|| ApiError::invalid(format!("priority must be a positive integer, got '{}'", text)))?);
+            }
+            "placeholderId" => {
+                let text = field.text().await?;
+                placeholder_id = Some(Uuid::parse_str(text.trim())
+                    .map_err(|_| ApiError::invalid(format!("placeholderId must be a UUID, got '{}'", text)))?);
+            }
+            "keyword" => {
+                let text = field.text().await?;
+                keywords.push(text.parse::<KeywordRef>().map_err(ApiError::invalid)?);
+            }
+            "folder" => folder = Some(field.text().await?).filter(|f| !f.trim().is_empty()),
+            "reuse" => reuse = field.text().await?.parse().map_err(ApiError::invalid)?,
+            other => return Err(ApiError::invalid(format!("Unexpected form field '{}'", other))),
+        }
+    }
+
+    let file_path = file_path.ok_or_else(|| ApiError::invalid("Missing form field 'file'"))?;
+    let context_code = context_code
+        .filter(|c| !c.trim().is_empty())
+        .ok_or_else(|| ApiError::invalid("Missing form field 'contextCode'"))?;
+    let title = title.unwrap_or_else(|| {
+        file_path.file_name().unwrap_or_default().to_string_lossy().to_string()
+    });
+
+    let attached = service.attach_document(AddDocumentParams {
+        submission_id: id,
+        file_path,
+        context_code,
+        title,
+        priority,
+        placeholder_id,
+        keywords,
+        folder,
+        reuse,
+    }).await?;
+
+    Ok(attached)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct IdenticalParams {
+    /// SHA-256 of the file, hex
+    pub checksum: String,
+}
+
+/// Lets a client check a file before uploading it: documents with this
+/// checksum that the unit may reference instead (those of earlier sequences
+/// of its application).
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}/identical-documents",
+    tag = "documents",
+    params(("id" = Uuid, Path, description = "Submission unit id"), IdenticalParams),
+    responses(
+        (status = 200, description = "Identical submitted documents, latest first", body = Vec<DocumentSummary>),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+        (status = 422, description = "The checksum is not a SHA-256", body = ErrorBody),
+    )
+)]
+pub async fn identical_documents(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiQuery(params): ApiQuery<IdenticalParams>,
+) -> Result<ApiJson<Vec<DocumentSummary>>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Read).await?;
+    let checksum = params.checksum.trim();
+    if checksum.len() != 64 || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ApiError::invalid("checksum must be 64 hex digits (SHA-256)"));
+    }
+    Ok(ApiJson(service.identical_documents(id, checksum).await?))
+}
+
+/// Writes a file part into `staging` under its sanitized file name.
+pub(crate) async fn stage_upload(field: &mut Field<'_>, staging: &Path) -> Result<PathBuf, ApiError> {
+    let name = field.file_name()
+        .map(sanitize_file_name)
+        .filter(|n| !n.is_empty())
+        .ok_or_else(|| ApiError::invalid(format!("The {} part needs a filename", field.name().unwrap_or_default())))?;
+    let path = staging.join(name);
+    let mut out = tokio::fs::File::create(&path).await
+        .map_err(|e| anyhow::anyhow!("Failed to stage upload {:?}: {}", path, e))?;
+    while let Some(chunk) = field.chunk().await? {
+        out.write_all(&chunk).await
+            .map_err(|e| anyhow::anyhow!("Failed to stage upload {:?}: {}", path, e))?;
+    }
+    out.flush().await
+        .map_err(|e| anyhow::anyhow!("Failed to stage upload {:?}: {}", path, e))?;
+    Ok(path)
+}
+
+/// The file name becomes part of the package path, so keep only its last
+/// component and characters that are safe in eCTD file names.
+fn sanitize_file_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    base.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '-' })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("cover.pdf"), "cover.pdf");
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_file_name("C:\\docs\\Cover Letter.pdf"), "Cover-Letter.pdf");
+        assert_eq!(sanitize_file_name(".."), "");
+    }
+}
@@ -0,0 +1,62 @@
+use axum::{
+    extract::State,
+    http::{header::LOCATION, HeaderName, StatusCode},
+};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use ectd_db::job::{JobKind, JobRecord};
+use ectd_service::{ArchiveFormat, ExportOptions};
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ErrorBody};
+use crate::AppState;
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExportRequest {
+    /// Lay the sequence out as <application number>/<sequence>/
+    #[serde(default)]
+    pub transmission: bool,
+    pub archive: Option<ArchiveFormat>,
+}
+
+/// Queues the export as a background job and returns it straight away.
+/// The package is written below the server's export root, in a folder named
+/// after the job; follow it at `/jobs/{id}` or `/jobs/{id}/events`.
+#[utoipa::path(
+    post,
+    path = "/submissions/{id}/exports",
+    tag = "exports",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    request_body = ExportRequest,
+    responses(
+        (status = 202, description = "Export job queued", body = JobRecord,
+         headers(("Location" = String, description = "The job resource"))),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+        (status = 409, description = "Planned documents are still missing", body = ErrorBody),
+    )
+)]
+pub async fn start_export(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiJson(req): ApiJson<ExportRequest>,
+) -> Result<(StatusCode, [(HeaderName, String); 1], ApiJson<JobRecord>), ApiError> {
+    let service = user.authorize(&state, id, Permission::Export).await?;
+    // The job would fail on it too, but the caller learns it straight away
+    service.ensure_ready(id).await?;
+
+    let parameters = json!({ "transmission": req.transmission, "archive": req.archive });
+    let job = service.create_job(JobKind::Export, id, parameters).await?;
+
+    let options = ExportOptions {
+        transmission_layout: req.transmission,
+        util_dir: state.util_dir.clone(),
+        archive: req.archive,
+    };
+    let output_dir = state.export_root.join(job.id.to_string());
+    state.jobs.spawn_export(service, &job, output_dir, options);
+
+    Ok((StatusCode::ACCEPTED, [(LOCATION, format!("/jobs/{}", job.id))], ApiJson(job)))
+}
@@ -0,0 +1,146 @@
+use axum::{
+    extract::State,
+    http::{header::LOCATION, HeaderName, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::Stream;
+use serde_json::json;
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+use ectd_db::job::{JobKind, JobRecord};
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ErrorBody};
+use crate::jobs::JobEvent;
+use crate::AppState;
+
+/// Queues the standard validation profile as a background job; the findings
+/// end up in the job's `result`. `POST /submissions/{id}/validate` is the
+/// synchronous variant for small units.
+#[utoipa::path(
+    post,
+    path = "/submissions/{id}/validations",
+    tag = "jobs",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses(
+        (status = 202, description = "Validation job queued", body = JobRecord,
+         headers(("Location" = String, description = "The job resource"))),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
+pub async fn start_validation(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<(StatusCode, [(HeaderName, String); 1], ApiJson<JobRecord>), ApiError> {
+    let service = user.authorize(&state, id, Permission::Validate).await?;
+
+    let job = service.create_job(JobKind::Validation, id, json!({})).await?;
+    state.jobs.spawn_validation(service, &job);
+
+    Ok((StatusCode::ACCEPTED, [(LOCATION, format!("/jobs/{}", job.id))], ApiJson(job)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}/jobs",
+    tag = "jobs",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses(
+        (status = 200, description = "Export and validation jobs of the unit, newest first", body = Vec<JobRecord>),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
+pub async fn list_jobs(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<Vec<JobRecord>>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Read).await?;
+    Ok(ApiJson(service.list_jobs(id).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    tag = "jobs",
+    params(("id" = Uuid, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Status, latest progress and outcome of the job", body = JobRecord),
+        (status = 404, description = "Unknown job", body = ErrorBody),
+    )
+)]
+pub async fn get_job(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<JobRecord>, ApiError> {
+    Ok(ApiJson(authorized_job(&user, &state, id).await?))
+}
+
+/// Server-sent events for one job: a `job` event with the current record,
+/// then `export-progress` events (the desktop app's payload) and a `job`
+/// event on every status change. The stream ends after the final `job`
+/// event; for a finished job that is the first one.
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}/events",
+    tag = "jobs",
+    params(("id" = Uuid, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "`text/event-stream` of `job` and `export-progress` events",
+         content_type = "text/event-stream", body = String),
+        (status = 404, description = "Unknown job", body = ErrorBody),
+    )
+)]
+pub async fn job_events(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    // Subscribe before reading the record, so no status change falls in between
+    let receiver = state.jobs.subscribe(id);
+    let job = authorized_job(&user, &state, id).await?;
+    let service = user.service(&state);
+
+    let stream = async_stream::stream! {
+        let finished = job.is_finished();
+        yield Ok(sse_event(&JobEvent::Job(Box::new(job))));
+
+        let Some(mut receiver) = receiver.filter(|_| !finished) else { return };
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let last = matches!(&event, JobEvent::Job(job) if job.is_finished());
+                    yield Ok(sse_event(&event));
+                    if last {
+                        return;
+                    }
+                }
+                // A slow client misses intermediate progress, not the outcome
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+
+        // The runner went away without a final event; report what was recorded
+        if let Ok(job) = service.get_job(id).await {
+            yield Ok(sse_event(&JobEvent::Job(Box::new(job))));
+        }
+    };
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+async fn authorized_job(user: &User, state: &AppState, id: Uuid) -> Result<JobRecord, ApiError> {
+    let job = state.service.get_job(id).await?;
+    user.authorize(state, job.submission_unit_id, Permission::Read).await?;
+    Ok(job)
+}
+
+fn sse_event(event: &JobEvent) -> Event {
+    Event::default()
+        .event(event.name())
+        .json_data(event)
+        .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()))
+}
@@ -0,0 +1,127 @@
+use axum::extract::State;
+use serde::Deserialize;
+use time::OffsetDateTime;
+use utoipa::IntoParams;
+use uuid::Uuid;
+use ectd_db::listing::{
+    ApplicationSummary, DocumentSummary, Page, PageRequest, SubmissionFilter, SubmissionSort,
+};
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ApiQuery, ErrorBody};
+use crate::AppState;
+
+/// Filters and paging shared by the application, submission and document listings.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+    pub application_number: Option<String>,
+    /// Case-insensitive substring of the applicant name
+    pub applicant: Option<String>,
+    /// Lowest sequence number, inclusive
+    pub sequence_from: Option<i32>,
+    /// Highest sequence number, inclusive
+    pub sequence_to: Option<i32>,
+    /// "active" or "suspended"
+    pub status: Option<String>,
+    /// Only units (or documents) with a context of use of this code
+    pub context_code: Option<String>,
+    /// Units created at or after this instant (RFC 3339)
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub created_after: Option<OffsetDateTime>,
+    /// Units created before this instant (RFC 3339)
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub created_before: Option<OffsetDateTime>,
+    /// Submission listing only; defaults to "application"
+    #[param(inline)]
+    pub sort: Option<SubmissionSort>,
+    /// Page size, 1 to 500 (default 50)
+    pub limit: Option<i64>,
+    /// `nextCursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+impl ListParams {
+    /// The filter, narrowed to the applications the caller may see.
+    pub fn filter(&self, user: &User) -> SubmissionFilter {
+        SubmissionFilter {
+            application_number: self.application_number.clone(),
+            applicant: self.applicant.clone(),
+            sequence_from: self.sequence_from,
+            sequence_to: self.sequence_to,
+            status_code: self.status.clone(),
+            context_code: self.context_code.clone(),
+            created_after: self.created_after,
+            created_before: self.created_before,
+            applications: user.application_scope(),
+        }
+    }
+
+    pub fn page(&self) -> PageRequest {
+        PageRequest { cursor: self.cursor.clone(), limit: self.limit }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/applications",
+    tag = "applications",
+    params(ListParams),
+    responses(
+        (status = 200, description = "Applications the caller can access, by application number; \
+                                      only the applicant and application number filters apply",
+         body = Page<ApplicationSummary>),
+        (status = 422, description = "Invalid filter or cursor", body = ErrorBody),
+    )
+)]
+pub async fn list_applications(
+    user: User,
+    State(state): State<AppState>,
+    ApiQuery(params): ApiQuery<ListParams>,
+) -> Result<ApiJson<Page<ApplicationSummary>>, ApiError> {
+    user.require(Permission::Read)?;
+    Ok(ApiJson(state.service.list_applications(&params.filter(&user), &params.page()).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/documents",
+    tag = "documents",
+    params(ListParams),
+    responses(
+        (status = 200, description = "Documents of the applications the caller can access, \
+                                      by application, sequence and path",
+         body = Page<DocumentSummary>),
+        (status = 422, description = "Invalid filter or cursor", body = ErrorBody),
+    )
+)]
+pub async fn list_documents(
+    user: User,
+    State(state): State<AppState>,
+    ApiQuery(params): ApiQuery<ListParams>,
+) -> Result<ApiJson<Page<DocumentSummary>>, ApiError> {
+    user.require(Permission::Read)?;
+    Ok(ApiJson(state.service.list_documents(&params.filter(&user), None, &params.page()).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}/documents",
+    tag = "documents",
+    params(("id" = Uuid, Path, description = "Submission unit id"), ListParams),
+    responses(
+        (status = 200, description = "Documents of the unit, by path", body = Page<DocumentSummary>),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
+pub async fn list_unit_documents(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiQuery(params): ApiQuery<ListParams>,
+) -> Result<ApiJson<Page<DocumentSummary>>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Read).await?;
+    Ok(ApiJson(service.list_documents(&params.filter(&user), Some(id), &params.page()).await?))
+}
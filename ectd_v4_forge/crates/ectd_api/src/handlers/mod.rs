@@ -1,6 +1,23 @@
-
+pub mod application;
 pub mod submission;
+pub mod document;
+pub mod context;
+pub mod export;
+pub mod job;
+pub mod listing;
+pub mod planning;
+pub mod search;
+pub mod template;
+pub mod study;
+pub mod audit;
 
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "meta",
+    security(()),
+    responses((status = 200, description = "The API is up", body = String))
+)]
 pub async fn health_check() -> &'static str {
     "OK"
 }
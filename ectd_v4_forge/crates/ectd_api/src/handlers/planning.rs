@@ -0,0 +1,118 @@
+use axum::{extract::State, http::StatusCode};
+use uuid::Uuid;
+use ectd_db::planning::Placeholder;
+use ectd_service::{NewPlaceholderParams, PlaceholderPatch, ReadinessReport};
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ErrorBody};
+use crate::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}/placeholders",
+    tag = "planning",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses(
+        (status = 200, description = "Planned contexts of use, open ones first", body = Vec<Placeholder>),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
+pub async fn list_placeholders(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<Vec<Placeholder>>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Read).await?;
+    Ok(ApiJson(service.list_placeholders(id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/submissions/{id}/placeholders",
+    tag = "planning",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    request_body = NewPlaceholderParams,
+    responses(
+        (status = 201, description = "Placeholder added to the plan", body = Placeholder),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+        (status = 422, description = "Invalid placeholder", body = ErrorBody),
+    )
+)]
+pub async fn add_placeholder(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiJson(params): ApiJson<NewPlaceholderParams>,
+) -> Result<(StatusCode, ApiJson<Placeholder>), ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+    let placeholder = service.add_placeholder(id, params).await?;
+    Ok((StatusCode::CREATED, ApiJson(placeholder)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/submissions/{id}/placeholders/{placeholder_id}",
+    tag = "planning",
+    params(
+        ("id" = Uuid, Path, description = "Submission unit id"),
+        ("placeholder_id" = Uuid, Path, description = "Placeholder id"),
+    ),
+    request_body = PlaceholderPatch,
+    responses(
+        (status = 200, description = "The updated placeholder", body = Placeholder),
+        (status = 404, description = "Unknown unit or placeholder", body = ErrorBody),
+        (status = 409, description = "The placeholder is already fulfilled", body = ErrorBody),
+        (status = 422, description = "Invalid change", body = ErrorBody),
+    )
+)]
+pub async fn update_placeholder(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((id, placeholder_id)): ApiPath<(Uuid, Uuid)>,
+    ApiJson(patch): ApiJson<PlaceholderPatch>,
+) -> Result<ApiJson<Placeholder>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+    Ok(ApiJson(service.update_placeholder(id, placeholder_id, patch).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/submissions/{id}/placeholders/{placeholder_id}",
+    tag = "planning",
+    params(
+        ("id" = Uuid, Path, description = "Submission unit id"),
+        ("placeholder_id" = Uuid, Path, description = "Placeholder id"),
+    ),
+    responses(
+        (status = 204, description = "Placeholder removed from the plan"),
+        (status = 404, description = "Unknown unit or placeholder", body = ErrorBody),
+        (status = 409, description = "The placeholder is already fulfilled", body = ErrorBody),
+    )
+)]
+pub async fn delete_placeholder(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((id, placeholder_id)): ApiPath<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+    service.remove_placeholder(id, placeholder_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}/readiness",
+    tag = "planning",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses(
+        (status = 200, description = "Planned documents still missing; export is blocked until none are", body = ReadinessReport),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
+pub async fn readiness(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<ReadinessReport>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Read).await?;
+    Ok(ApiJson(service.readiness(id).await?))
+}
@@ -0,0 +1,64 @@
+use axum::extract::State;
+use serde::Deserialize;
+use utoipa::IntoParams;
+use ectd_db::listing::{Page, PageRequest, SubmissionFilter};
+use ectd_db::search::SearchHit;
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiQuery, ErrorBody};
+use crate::AppState;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+    /// Words to find in titles, keywords and PDF text; `"exact phrase"`,
+    /// `or` and `-excluded` are supported
+    pub q: String,
+    pub application_number: Option<String>,
+    /// Lowest sequence number, inclusive
+    pub sequence_from: Option<i32>,
+    /// Highest sequence number, inclusive
+    pub sequence_to: Option<i32>,
+    /// Only documents with a context of use of this code
+    pub context_code: Option<String>,
+    /// Page size, 1 to 500 (default 50)
+    pub limit: Option<i64>,
+    /// `nextCursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+impl SearchParams {
+    /// The filter, narrowed to the applications the caller may see.
+    fn filter(&self, user: &User) -> SubmissionFilter {
+        SubmissionFilter {
+            application_number: self.application_number.clone(),
+            sequence_from: self.sequence_from,
+            sequence_to: self.sequence_to,
+            context_code: self.context_code.clone(),
+            applications: user.application_scope(),
+            ..Default::default()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/search",
+    tag = "documents",
+    params(SearchParams),
+    responses(
+        (status = 200, description = "Matching documents of the applications the caller can access, \
+                                      best matches first, with their sequence and contexts of use",
+         body = Page<SearchHit>),
+        (status = 422, description = "Empty query or invalid cursor", body = ErrorBody),
+    )
+)]
+pub async fn search_documents(
+    user: User,
+    State(state): State<AppState>,
+    ApiQuery(params): ApiQuery<SearchParams>,
+) -> Result<ApiJson<Page<SearchHit>>, ApiError> {
+    user.require(Permission::Read)?;
+    let page = PageRequest { cursor: params.cursor.clone(), limit: params.limit };
+    Ok(ApiJson(state.service.search_documents(&params.q, &params.filter(&user), &page).await?))
+}
@@ -0,0 +1,243 @@
+use axum::{
+    extract::{Multipart, State},
+    http::StatusCode,
+};
+use serde::Serialize;
+use std::path::Path;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use ectd_db::study::{Study, StudyDocument};
+use ectd_service::{
+    AttachedDocument, DocumentReuse, EctdService, KeywordRef, NewStudyParams, StudyDocumentParams,
+    StudyDocumentRole, StudyPatch,
+};
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ErrorBody};
+use crate::handlers::document::stage_upload;
+use crate::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/applications/{number}/studies",
+    tag = "studies",
+    params(("number" = String, Path, description = "Application number")),
+    responses(
+        (status = 200, description = "The application's studies by study id", body = Vec<Study>),
+        (status = 404, description = "Unknown application", body = ErrorBody),
+    )
+)]
+pub async fn list_studies(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(number): ApiPath<String>,
+) -> Result<ApiJson<Vec<Study>>, ApiError> {
+    user.require(Permission::Read)?;
+    user.require_application(&number)?;
+    Ok(ApiJson(state.service.list_studies(&number).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/applications/{number}/studies",
+    tag = "studies",
+    params(("number" = String, Path, description = "Application number")),
+    request_body = NewStudyParams,
+    responses(
+        (status = 201, description = "Study registered", body = Study),
+        (status = 404, description = "Unknown application", body = ErrorBody),
+        (status = 409, description = "The application has a study with this id", body = ErrorBody),
+        (status = 422, description = "Invalid study", body = ErrorBody),
+    )
+)]
+pub async fn create_study(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(number): ApiPath<String>,
+    ApiJson(params): ApiJson<NewStudyParams>,
+) -> Result<(StatusCode, ApiJson<Study>), ApiError> {
+    user.require(Permission::Edit)?;
+    user.require_application(&number)?;
+    let study = user.service(&state).create_study(&number, params).await?;
+    Ok((StatusCode::CREATED, ApiJson(study)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/applications/{number}/studies/{study_id}",
+    tag = "studies",
+    params(
+        ("number" = String, Path, description = "Application number"),
+        ("study_id" = String, Path, description = "Study id, e.g. ABC-101"),
+    ),
+    responses(
+        (status = 200, description = "The study", body = Study),
+        (status = 404, description = "Unknown study", body = ErrorBody),
+    )
+)]
+pub async fn get_study(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((number, study_id)): ApiPath<(String, String)>,
+) -> Result<ApiJson<Study>, ApiError> {
+    user.require(Permission::Read)?;
+    user.require_application(&number)?;
+    Ok(ApiJson(state.service.get_study(&number, &study_id).await?))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/applications/{number}/studies/{study_id}",
+    tag = "studies",
+    params(
+        ("number" = String, Path, description = "Application number"),
+        ("study_id" = String, Path, description = "Study id, e.g. ABC-101"),
+    ),
+    request_body = StudyPatch,
+    responses(
+        (status = 200, description = "The updated study", body = Study),
+        (status = 404, description = "Unknown study", body = ErrorBody),
+        (status = 422, description = "Invalid change", body = ErrorBody),
+    )
+)]
+pub async fn update_study(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((number, study_id)): ApiPath<(String, String)>,
+    ApiJson(patch): ApiJson<StudyPatch>,
+) -> Result<ApiJson<Study>, ApiError> {
+    user.require(Permission::Edit)?;
+    user.require_application(&number)?;
+    Ok(ApiJson(user.service(&state).update_study(&number, &study_id, patch).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/applications/{number}/studies/{study_id}/documents",
+    tag = "studies",
+    params(
+        ("number" = String, Path, description = "Application number"),
+        ("study_id" = String, Path, description = "Study id, e.g. ABC-101"),
+    ),
+    responses(
+        (status = 200, description = "Contexts of use tagged with the study, in every sequence", body = Vec<StudyDocument>),
+        (status = 404, description = "Unknown study", body = ErrorBody),
+    )
+)]
+pub async fn list_study_documents(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((number, study_id)): ApiPath<(String, String)>,
+) -> Result<ApiJson<Vec<StudyDocument>>, ApiError> {
+    user.require(Permission::Read)?;
+    user.require_application(&number)?;
+    Ok(ApiJson(state.service.study_documents(&number, &study_id).await?))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachedStudyDocuments {
+    /// In attachment order: by role, then as uploaded
+    pub documents: Vec<AttachedDocument>,
+}
+
+/// The form accepted by [`attach_study_documents`]; only used to document it.
+#[allow(dead_code)]
+#[derive(ToSchema)]
+pub struct AttachStudyDocumentsForm {
+    #[schema(value_type = Vec<String>, format = Binary)]
+    protocol: Vec<Vec<u8>>,
+    #[schema(value_type = Vec<String>, format = Binary)]
+    report: Vec<Vec<u8>>,
+    #[schema(value_type = Vec<String>, format = Binary)]
+    appendix: Vec<Vec<u8>>,
+    #[schema(value_type = Vec<String>, format = Binary)]
+    crf: Vec<Vec<u8>>,
+    #[schema(value_type = Vec<String>, format = Binary)]
+    dataset: Vec<Vec<u8>>,
+    #[schema(value_type = Vec<String>, format = Binary)]
+    define: Vec<Vec<u8>>,
+    /// Repeatable; added to every document besides the study-id keyword
+    keyword: Vec<String>,
+    /// For documents already submitted: "ask" (default), "reference" or "copy"
+    #[schema(value_type = Option<DocumentReuse>)]
+    reuse: Option<String>,
+}
+
+/// `multipart/form-data` with one file part per document, named after its
+/// role (`protocol`, `report`, `appendix`, `crf`, `dataset`, `define`; each
+/// repeatable), any number of `keyword` parts and an optional `reuse`. All
+/// documents are attached or none.
+#[utoipa::path(
+    post,
+    path = "/submissions/{id}/studies/{study_id}/documents",
+    tag = "studies",
+    params(
+        ("id" = Uuid, Path, description = "Submission unit id"),
+        ("study_id" = String, Path, description = "Study id, e.g. ABC-101"),
+    ),
+    request_body(content = AttachStudyDocumentsForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Documents stored and attached", body = AttachedStudyDocuments),
+        (status = 404, description = "Unknown submission unit or study", body = ErrorBody),
+        (status = 409, description = "A file was already submitted (reuse \"ask\")", body = ErrorBody),
+        (status = 413, description = "Upload exceeds MAX_UPLOAD_MB", body = ErrorBody),
+        (status = 422, description = "No documents, unknown parts or keywords, or failed PDF checks", body = ErrorBody),
+    )
+)]
+pub async fn attach_study_documents(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath((id, study_id)): ApiPath<(Uuid, String)>,
+    multipart: Multipart,
+) -> Result<(StatusCode, ApiJson<AttachedStudyDocuments>), ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+
+    let staging = std::env::temp_dir().join(format!("ectd-upload-{}", Uuid::new_v4()));
+    tokio::fs::create_dir_all(&staging).await
+        .map_err(|e| anyhow::anyhow!("Failed to create upload staging dir {:?}: {}", staging, e))?;
+
+    let result = receive_and_attach(&service, id, &study_id, multipart, &staging).await;
+    let _ = tokio::fs::remove_dir_all(&staging).await;
+
+    let documents = result?;
+    Ok((StatusCode::CREATED, ApiJson(AttachedStudyDocuments { documents })))
+}
+
+async fn receive_and_attach(
+    service: &EctdService,
+    id: Uuid,
+    study_id: &str,
+    mut multipart: Multipart,
+    staging: &Path,
+) -> Result<Vec<AttachedDocument>, ApiError> {
+    let mut documents = Vec::new();
+    let mut keywords = Vec::new();
+    let mut reuse = DocumentReuse::default();
+
+    while let Some(mut field) = multipart.next_field().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "keyword" {
+            let text = field.text().await?;
+            keywords.push(text.parse::<KeywordRef>().map_err(ApiError::invalid)?);
+            continue;
+        }
+        if name == "reuse" {
+            reuse = field.text().await?.parse().map_err(ApiError::invalid)?;
+            continue;
+        }
+        let role = StudyDocumentRole::from_code(&name)
+            .ok_or_else(|| ApiError::invalid(format!("Unexpected form field '{}'", name)))?;
+        // Parts may share a file name, so each gets its own folder
+        let dir = staging.join(documents.len().to_string());
+        tokio::fs::create_dir(&dir).await
+            .map_err(|e| anyhow::anyhow!("Failed to create upload staging dir {:?}: {}", dir, e))?;
+        let file_path = stage_upload(&mut field, &dir).await?;
+        documents.push(StudyDocumentParams { role, file_path, title: None, keywords: Vec::new(), reuse: DocumentReuse::default() });
+    }
+
+    for document in &mut documents {
+        document.keywords = keywords.clone();
+        document.reuse = reuse;
+    }
+    Ok(service.attach_study_documents(id, study_id, documents).await?)
+}
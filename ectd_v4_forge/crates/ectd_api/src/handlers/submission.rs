@@ -1,33 +1,161 @@
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    Json,
-};
+use axum::{extract::State, http::StatusCode};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 use ectd_core::models::submission_unit::SubmissionUnit;
-use ectd_db::repository::SubmissionRepository;
+use ectd_core::validation::ValidationError;
+use ectd_db::listing::Page;
+use ectd_db::repository::SubmissionSummary;
+use ectd_service::{InitSubmissionParams, SubmissionPatch};
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ApiQuery, ErrorBody};
+use crate::handlers::listing::ListParams;
 use crate::AppState;
 
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CreateSubmissionRequest {
+    /// Created along with the unit when it doesn't exist yet
+    pub application_number: String,
+    /// Required for a new application; otherwise must match it
+    pub application_type: Option<String>,
+    /// Required for a new application; otherwise must match it
+    pub applicant_name: Option<String>,
+    /// Defaults to the application's next sequence number
+    pub sequence_number: Option<u32>,
+    /// Defaults to "seq-NNNN"
+    pub submission_code: Option<String>,
+    /// Template to plan the unit from (see `/templates`)
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ValidationReport {
+    /// False when any finding is a High Error
+    pub valid: bool,
+    pub findings: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    pub fn new(findings: Vec<ValidationError>) -> Self {
+        let valid = !findings.iter().any(|f| f.severity.contains("High Error"));
+        Self { valid, findings }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/submissions",
+    tag = "submissions",
+    request_body = CreateSubmissionRequest,
+    responses(
+        (status = 201, description = "Submission unit created", body = SubmissionUnit),
+        (status = 403, description = "No Edit permission or no access to the application", body = ErrorBody),
+        (status = 404, description = "Unknown template", body = ErrorBody),
+        (status = 409, description = "The application already has this sequence number", body = ErrorBody),
+        (status = 422, description = "Invalid request, or it contradicts the existing application", body = ErrorBody),
+    )
+)]
+pub async fn create_submission(
+    user: User,
+    State(state): State<AppState>,
+    ApiJson(req): ApiJson<CreateSubmissionRequest>,
+) -> Result<(StatusCode, ApiJson<SubmissionUnit>), ApiError> {
+    user.require(Permission::Edit)?;
+    user.require_application(&req.application_number)?;
+
+    let service = user.service(&state);
+    let id = service.create_submission(InitSubmissionParams {
+        app_number: req.application_number,
+        app_type: req.application_type,
+        applicant_name: req.applicant_name,
+        sequence_number: req.sequence_number,
+        submission_code: req.submission_code,
+        template: req.template,
+    }).await?;
+
+    let unit = service.get_submission(id).await?;
+    Ok((StatusCode::CREATED, ApiJson(unit)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/submissions",
+    tag = "submissions",
+    params(ListParams),
+    responses(
+        (status = 200, description = "Submission units of the applications the caller can access", body = Page<SubmissionSummary>),
+        (status = 422, description = "Invalid filter or cursor", body = ErrorBody),
+    )
+)]
+pub async fn list_submissions(
+    user: User,
+    State(state): State<AppState>,
+    ApiQuery(params): ApiQuery<ListParams>,
+) -> Result<ApiJson<Page<SubmissionSummary>>, ApiError> {
+    user.require(Permission::Read)?;
+    let filter = params.filter(&user);
+    let sort = params.sort.unwrap_or_default();
+    Ok(ApiJson(state.service.list_submissions(&filter, sort, &params.page()).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/submissions/{id}",
+    tag = "submissions",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses(
+        (status = 200, description = "The full submission unit", body = SubmissionUnit),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
 pub async fn get_submission(
+    user: User,
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-) -> Result<Json<SubmissionUnit>, (StatusCode, String)> {
-    let repo = SubmissionRepository::new(state.pool);
-
-    match repo.get_submission(id).await {
-        Ok(unit) => Ok(Json(unit)),
-        Err(sqlx::Error::RowNotFound) => {
-            Err((
-                StatusCode::NOT_FOUND,
-                format!("Submission Unit not found: {}", id),
-            ))
-        }
-        Err(e) => {
-            tracing::error!("Failed to fetch submission {}: {:?}", id, e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Internal server error"),
-            ))
-        }
-    }
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<SubmissionUnit>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Read).await?;
+    Ok(ApiJson(service.get_submission(id).await?))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/submissions/{id}",
+    tag = "submissions",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    request_body = SubmissionPatch,
+    responses(
+        (status = 200, description = "The updated submission unit", body = SubmissionUnit),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+        (status = 422, description = "Invalid change", body = ErrorBody),
+    )
+)]
+pub async fn update_submission(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+    ApiJson(patch): ApiJson<SubmissionPatch>,
+) -> Result<ApiJson<SubmissionUnit>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Edit).await?;
+    Ok(ApiJson(service.update_submission(id, patch).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/submissions/{id}/validate",
+    tag = "submissions",
+    params(("id" = Uuid, Path, description = "Submission unit id")),
+    responses(
+        (status = 200, description = "Findings of the standard validation profile", body = ValidationReport),
+        (status = 404, description = "Unknown submission unit", body = ErrorBody),
+    )
+)]
+pub async fn validate_submission(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(id): ApiPath<Uuid>,
+) -> Result<ApiJson<ValidationReport>, ApiError> {
+    let service = user.authorize(&state, id, Permission::Validate).await?;
+    let findings = service.validate_submission(id).await?;
+    Ok(ApiJson(ValidationReport::new(findings)))
 }
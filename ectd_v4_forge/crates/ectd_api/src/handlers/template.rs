@@ -0,0 +1,87 @@
+use axum::{extract::State, http::StatusCode};
+use ectd_db::template::SubmissionTemplate;
+use crate::auth::{Permission, User};
+use crate::error::{ApiError, ApiJson, ApiPath, ErrorBody};
+use crate::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/templates",
+    tag = "templates",
+    responses((status = 200, description = "Submission templates, by name", body = Vec<SubmissionTemplate>))
+)]
+pub async fn list_templates(
+    user: User,
+    State(state): State<AppState>,
+) -> Result<ApiJson<Vec<SubmissionTemplate>>, ApiError> {
+    user.require(Permission::Read)?;
+    Ok(ApiJson(state.service.list_templates().await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/templates/{name}",
+    tag = "templates",
+    params(("name" = String, Path, description = "Template name")),
+    responses(
+        (status = 200, description = "The template", body = SubmissionTemplate),
+        (status = 404, description = "Unknown template", body = ErrorBody),
+    )
+)]
+pub async fn get_template(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(name): ApiPath<String>,
+) -> Result<ApiJson<SubmissionTemplate>, ApiError> {
+    user.require(Permission::Read)?;
+    Ok(ApiJson(state.service.get_template(&name).await?))
+}
+
+/// Creates or replaces the template. Units already created from it keep
+/// what they were created with.
+#[utoipa::path(
+    put,
+    path = "/templates/{name}",
+    tag = "templates",
+    params(("name" = String, Path, description = "Template name")),
+    request_body = SubmissionTemplate,
+    responses(
+        (status = 200, description = "Template replaced", body = SubmissionTemplate),
+        (status = 201, description = "Template created", body = SubmissionTemplate),
+        (status = 422, description = "Invalid template, or its name differs from the path", body = ErrorBody),
+    )
+)]
+pub async fn put_template(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(name): ApiPath<String>,
+    ApiJson(template): ApiJson<SubmissionTemplate>,
+) -> Result<(StatusCode, ApiJson<SubmissionTemplate>), ApiError> {
+    user.require(Permission::Administer)?;
+    if template.name != name {
+        return Err(ApiError::invalid(format!("The template is named '{}', not '{}'", template.name, name)));
+    }
+    let (template, created) = user.service(&state).save_template(template).await?;
+    let status = if created { StatusCode::CREATED } else { StatusCode::OK };
+    Ok((status, ApiJson(template)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/templates/{name}",
+    tag = "templates",
+    params(("name" = String, Path, description = "Template name")),
+    responses(
+        (status = 204, description = "Template removed; units created from it are unaffected"),
+        (status = 404, description = "Unknown template", body = ErrorBody),
+    )
+)]
+pub async fn delete_template(
+    user: User,
+    State(state): State<AppState>,
+    ApiPath(name): ApiPath<String>,
+) -> Result<StatusCode, ApiError> {
+    user.require(Permission::Administer)?;
+    user.service(&state).delete_template(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,216 @@
+use futures::StreamExt;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Semaphore};
+use uuid::Uuid;
+use ectd_db::job::JobRecord;
+use ectd_service::{EctdService, ExportOptions, ExportProgress};
+use crate::handlers::submission::ValidationReport;
+
+/// How often running jobs write their latest progress to the database.
+/// Live subscribers get every event regardless.
+const PROGRESS_PERSIST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Events buffered per job for slow subscribers before they start skipping.
+const EVENT_BUFFER: usize = 256;
+
+/// What subscribers of a running job receive.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum JobEvent {
+    /// The job record, whenever its status changes
+    Job(Box<JobRecord>),
+    /// Same payload as the desktop app's `export-progress` event
+    ExportProgress(ExportProgress),
+}
+
+impl JobEvent {
+    /// Server-sent event name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobEvent::Job(_) => "job",
+            JobEvent::ExportProgress(_) => "export-progress",
+        }
+    }
+}
+
+/// Runs export and validation jobs in the background of the API process.
+/// Job state lives in Postgres; this only holds the live event channels and
+/// caps how many jobs run at once (the rest wait as `queued`).
+#[derive(Clone)]
+pub struct JobRunner {
+    slots: Arc<Semaphore>,
+    channels: Arc<Mutex<HashMap<Uuid, broadcast::Sender<JobEvent>>>>,
+}
+
+impl JobRunner {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            channels: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Live events of a job, or `None` once it has finished (or was never
+    /// started by this process); the job record then has the outcome.
+    pub fn subscribe(&self, job_id: Uuid) -> Option<broadcast::Receiver<JobEvent>> {
+        self.channels.lock().unwrap().get(&job_id).map(|tx| tx.subscribe())
+    }
+
+    /// Starts an export job created with `EctdService::create_job`.
+    /// `service` carries the requesting user, so the audit trail names them.
+    pub fn spawn_export(&self, service: EctdService, job: &JobRecord, output_dir: PathBuf, options: ExportOptions) {
+        let (job_id, unit_id) = (job.id, job.submission_unit_id);
+        self.spawn(service, job_id, move |service, tx| async move {
+            let mut stream = service.export_package_stream(unit_id, output_dir.clone(), options);
+            let mut last_persisted: Option<Instant> = None;
+            let mut latest = None;
+
+            while let Some(item) = stream.next().await {
+                let progress = item?;
+                if last_persisted.is_none_or(|t| t.elapsed() >= PROGRESS_PERSIST_INTERVAL) {
+                    persist_progress(&service, job_id, &progress).await;
+                    last_persisted = Some(Instant::now());
+                }
+                let _ = tx.send(JobEvent::ExportProgress(progress.clone()));
+                latest = Some(progress);
+            }
+            if let Some(progress) = latest {
+                persist_progress(&service, job_id, &progress).await;
+            }
+
+            Ok(json!({ "outputDir": output_dir }))
+        });
+    }
+
+    /// Starts a validation job created with `EctdService::create_job`.
+    pub fn spawn_validation(&self, service: EctdService, job: &JobRecord) {
+        let unit_id = job.submission_unit_id;
+        self.spawn(service, job.id, move |service, _tx| async move {
+            let findings = service.validate_submission(unit_id).await?;
+            Ok(serde_json::to_value(ValidationReport::new(findings))?)
+        });
+    }
+
+    fn spawn<F, Fut>(&self, service: EctdService, job_id: Uuid, work: F)
+    where
+        F: FnOnce(EctdService, broadcast::Sender<JobEvent>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = anyhow::Result<serde_json::Value>> + Send,
+    {
+        let (tx, _) = broadcast::channel(EVENT_BUFFER);
+        self.channels.lock().unwrap().insert(job_id, tx.clone());
+
+        let runner = self.clone();
+        tokio::spawn(async move {
+            let _slot = runner.slots.clone().acquire_owned().await;
+
+            match service.start_job(job_id).await {
+                Ok(job) => {
+                    let _ = tx.send(JobEvent::Job(Box::new(job)));
+                }
+                Err(e) => tracing::error!("job {}: {:#}", job_id, e),
+            }
+
+            let outcome = work(service.clone(), tx.clone()).await;
+            if let Err(e) = &outcome {
+                tracing::warn!("job {} failed: {:#}", job_id, e);
+            }
+
+            match service.finish_job(job_id, outcome).await {
+                Ok(job) => {
+                    tracing::info!("job {} ({} of {}) {}", job_id, job.kind, job.submission_unit_id, job.status);
+                    let _ = tx.send(JobEvent::Job(Box::new(job)));
+                }
+                Err(e) => tracing::error!("job {}: {:#}", job_id, e),
+            }
+
+            // Subscribers keep whatever is still buffered, then see the channel close
+            runner.channels.lock().unwrap().remove(&job_id);
+        });
+    }
+}
+
+async fn persist_progress(service: &EctdService, job_id: Uuid, progress: &ExportProgress) {
+    let value = match serde_json::to_value(progress) {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::warn!("job {}: could not serialize progress: {}", job_id, e);
+            return;
+        }
+    };
+    // Progress is advisory; a failed write must not fail the export
+    if let Err(e) = service.report_job_progress(job_id, &value).await {
+        tracing::warn!("job {}: {:#}", job_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ectd_db::job::JobKind;
+    use ectd_service::storage::MemoryStore;
+    use ectd_service::InitSubmissionParams;
+
+    async fn service_with_unit(pool: sqlx::PgPool) -> (EctdService, Uuid) {
+        ectd_db::schema::migrate(&pool).await.unwrap();
+        let service = EctdService::new(pool, Arc::new(MemoryStore::new())).with_actor("alice");
+        let unit = service.create_submission(InitSubmissionParams {
+            app_number: "100001".to_string(),
+            app_type: Some("nda".to_string()),
+            applicant_name: Some("Acme Pharma".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+        (service, unit)
+    }
+
+    /// The job records sent to subscribers, until the job's channel closes.
+    async fn job_updates(mut events: broadcast::Receiver<JobEvent>) -> Vec<JobRecord> {
+        let mut jobs = Vec::new();
+        loop {
+            match events.recv().await {
+                Ok(JobEvent::Job(job)) => jobs.push(*job),
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return jobs,
+            }
+        }
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_validation_job_succeeds_with_its_report(pool: sqlx::PgPool) {
+        let (service, unit) = service_with_unit(pool).await;
+        let runner = JobRunner::new(1);
+        let job = service.create_job(JobKind::Validation, unit, json!({})).await.unwrap();
+
+        runner.spawn_validation(service.clone(), &job);
+        let updates = job_updates(runner.subscribe(job.id).unwrap()).await;
+
+        let statuses: Vec<&str> = updates.iter().map(|j| j.status.as_str()).collect();
+        assert_eq!(statuses, ["running", "succeeded"]);
+        let stored = service.get_job(job.id).await.unwrap();
+        assert!(stored.result.unwrap()["valid"].is_boolean());
+        assert!(runner.subscribe(job.id).is_none());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_export_error_ends_up_in_the_job(pool: sqlx::PgPool) {
+        let (service, unit) = service_with_unit(pool).await;
+        let runner = JobRunner::new(1);
+        let job = service.create_job(JobKind::Export, unit, json!({})).await.unwrap();
+        // A file where the output directory should go
+        let output = std::env::temp_dir().join(format!("ectd-export-{}", Uuid::new_v4()));
+        std::fs::write(&output, "in the way").unwrap();
+
+        runner.spawn_export(service.clone(), &job, output, ExportOptions::default());
+        let updates = job_updates(runner.subscribe(job.id).unwrap()).await;
+
+        let statuses: Vec<&str> = updates.iter().map(|j| j.status.as_str()).collect();
+        assert_eq!(statuses, ["running", "failed"]);
+        let stored = service.get_job(job.id).await.unwrap();
+        assert_eq!(stored.status, "failed");
+        assert!(stored.error.unwrap().starts_with("Failed to create output dir"));
+    }
+}
@@ -1,5 +1,10 @@
+pub mod auth;
+pub mod jobs;
 pub mod routes;
 pub mod handlers;
 pub mod state;
+pub mod error;
+pub mod openapi;
 
 pub use state::AppState;
+pub use error::ApiError;
@@ -1,7 +1,10 @@
-use ectd_api::{routes::app_router, AppState};
+use ectd_api::{auth::AuthConfig, jobs::JobRunner, routes::app_router, AppState};
+use ectd_service::{EctdService, StorageBackend};
 use tokio::net::TcpListener;
 use sqlx::postgres::PgPoolOptions;
 use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() {
@@ -16,7 +19,59 @@ async fn main() {
         .await
         .expect("Failed to connect to Postgres");
 
-    let state = AppState { pool };
+    // Refuse to serve against a schema this build doesn't match.
+    // Migrations are applied out-of-band via `ectd_cli db migrate`.
+    ectd_db::schema::ensure_current(&pool)
+        .await
+        .expect("Refusing to start: database schema is not current");
+
+    // AUTH_JWT_SECRET or AUTH_JWT_PUBLIC_KEY; see AuthConfig
+    let auth = AuthConfig::from_env().expect("Invalid authentication configuration");
+    if matches!(auth, AuthConfig::Disabled) {
+        tracing::warn!("AUTH_DISABLED is set: every request is served as an admin");
+    }
+
+    // STORAGE_BACKEND=s3 (default, MinIO) | local | memory, as for the CLI
+    let store = StorageBackend::from_env()
+        .expect("Invalid storage configuration")
+        .build()
+        .await;
+
+    let require_approval = env::var("ECTD_REQUIRE_APPROVAL")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+
+    // Handlers act as the authenticated user; "api" is only the fallback actor
+    let service = EctdService::new(pool, store)
+        .with_actor("api")
+        .with_approval_required(require_approval);
+
+    // Jobs of a previous process can't be resumed; say so instead of leaving them "running"
+    let interrupted = service.fail_interrupted_jobs().await.expect("Failed to clean up interrupted jobs");
+    if interrupted > 0 {
+        tracing::warn!("marked {} interrupted job(s) as failed", interrupted);
+    }
+
+    let max_concurrent_jobs: usize = env::var("MAX_CONCURRENT_JOBS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(2);
+
+    let max_upload_mb: usize = env::var("MAX_UPLOAD_MB")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(512);
+
+    let state = AppState {
+        service,
+        auth: Arc::new(auth),
+        jobs: JobRunner::new(max_concurrent_jobs),
+        export_root: env::var("EXPORT_ROOT")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("./data/exports")),
+        util_dir: env::var("EXPORT_UTIL_DIR").ok().map(PathBuf::from),
+        max_upload_bytes: max_upload_mb * 1024 * 1024,
+    };
 
     let app = app_router(state);
 
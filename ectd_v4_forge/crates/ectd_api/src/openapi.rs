@@ -0,0 +1,130 @@
+use axum::{response::Html, Json};
+use utoipa::openapi::{
+    security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    ContentBuilder, Ref, ResponseBuilder,
+};
+use utoipa::{Modify, OpenApi};
+use utoipa_scalar::Scalar;
+use crate::handlers;
+
+/// The OpenAPI 3.1 document, generated from the handler annotations and the
+/// serde models; `tests/openapi_contract.rs` checks it against the router.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "eCTD v4.0 API",
+        description = "Submission units, documents, contexts of use, keywords, studies, validation and exports. \
+                       Every error response has the body `{\"error\": {\"code\", \"message\"}}`. \
+                       Requests need a bearer JWT whose `roles` (author, reviewer, publisher, admin) \
+                       and `applications` claims decide what the caller may do.",
+        license(name = "Apache-2.0", identifier = "Apache-2.0")
+    ),
+    paths(
+        handlers::health_check,
+        handlers::listing::list_applications,
+        handlers::application::create_application,
+        handlers::application::get_application,
+        handlers::application::update_application,
+        handlers::study::list_studies,
+        handlers::study::create_study,
+        handlers::study::get_study,
+        handlers::study::update_study,
+        handlers::study::list_study_documents,
+        handlers::study::attach_study_documents,
+        handlers::submission::list_submissions,
+        handlers::submission::create_submission,
+        handlers::submission::get_submission,
+        handlers::submission::update_submission,
+        handlers::submission::validate_submission,
+        handlers::listing::list_documents,
+        handlers::listing::list_unit_documents,
+        handlers::search::search_documents,
+        handlers::document::attach_document,
+        handlers::document::identical_documents,
+        handlers::context::list_contexts,
+        handlers::context::update_context,
+        handlers::context::move_context,
+        handlers::context::reorder_contexts,
+        handlers::context::current_view,
+        handlers::context::list_keywords,
+        handlers::context::put_keyword,
+        handlers::context::delete_keyword,
+        handlers::context::retire_keyword,
+        handlers::context::keyword_vocabulary,
+        handlers::planning::list_placeholders,
+        handlers::planning::add_placeholder,
+        handlers::planning::update_placeholder,
+        handlers::planning::delete_placeholder,
+        handlers::planning::readiness,
+        handlers::template::list_templates,
+        handlers::template::get_template,
+        handlers::template::put_template,
+        handlers::template::delete_template,
+        handlers::export::start_export,
+        handlers::job::start_validation,
+        handlers::job::list_jobs,
+        handlers::job::get_job,
+        handlers::job::job_events,
+        handlers::audit::submission_audit,
+        handlers::audit::list_audit,
+        handlers::audit::verify_audit,
+    ),
+    modifiers(&BearerAuth),
+    security(("bearer" = [])),
+    tags(
+        (name = "applications", description = "Applications and their sequence numbering"),
+        (name = "submissions", description = "Submission units"),
+        (name = "documents", description = "Document upload, listing and full-text search"),
+        (name = "contexts", description = "Contexts of use, their priority order and the current view"),
+        (name = "keywords", description = "Keyword definitions, their lifecycle across sequences and the vocabulary in force"),
+        (name = "planning", description = "Placeholders for planned documents and readiness for export"),
+        (name = "templates", description = "Submission templates that plan new units"),
+        (name = "studies", description = "Study registry and Module 4/5 documents grouped by study"),
+        (name = "exports", description = "Package export"),
+        (name = "jobs", description = "Background exports and validations"),
+        (name = "audit", description = "21 CFR Part 11 audit trail"),
+        (name = "meta", description = "Service status and this document"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the bearer scheme and adds the 401/403 responses to every
+/// operation that doesn't opt out of the global security requirement.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let scheme = HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build();
+        openapi.components.get_or_insert_with(Default::default)
+            .add_security_scheme("bearer", SecurityScheme::Http(scheme));
+
+        let error = |description: &str| {
+            ResponseBuilder::new()
+                .description(description)
+                .content("application/json", ContentBuilder::new().schema(Some(Ref::from_schema_name("ErrorBody"))).build())
+                .build()
+        };
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [&mut item.get, &mut item.put, &mut item.post, &mut item.patch, &mut item.delete];
+            for operation in operations.into_iter().flatten() {
+                if operation.security.is_some() {
+                    continue;
+                }
+                let responses = &mut operation.responses.responses;
+                responses.entry("401".to_string())
+                    .or_insert_with(|| error("Missing, expired or invalid bearer token").into());
+                responses.entry("403".to_string())
+                    .or_insert_with(|| error("The caller's roles or applications don't allow this").into());
+            }
+        }
+    }
+}
+
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Scalar API reference with the spec embedded in the page.
+pub async fn docs() -> Html<String> {
+    Html(Scalar::new(ApiDoc::openapi()).to_html())
+}
@@ -1,9 +1,63 @@
-use axum::{routing::get, Router};
-use crate::{handlers::{health_check, submission}, AppState};
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{get, patch, post, put},
+    Router,
+};
+use crate::{handlers::{application, audit, context, document, export, health_check, job, listing, planning, search, study, submission, template}, openapi, AppState};
 
 pub fn app_router(state: AppState) -> Router {
     Router::new()
         .route("/health", get(health_check))
-        .route("/submissions/:id", get(submission::get_submission))
+        .route("/openapi.json", get(openapi::openapi_json))
+        .route("/docs", get(openapi::docs))
+        .route("/applications", get(listing::list_applications).post(application::create_application))
+        .route("/applications/:number", get(application::get_application).patch(application::update_application))
+        .route("/applications/:number/studies", get(study::list_studies).post(study::create_study))
+        .route("/applications/:number/studies/:study_id", get(study::get_study).patch(study::update_study))
+        .route("/applications/:number/studies/:study_id/documents", get(study::list_study_documents))
+        .route("/documents", get(listing::list_documents))
+        .route("/search", get(search::search_documents))
+        .route("/submissions", get(submission::list_submissions).post(submission::create_submission))
+        .route("/submissions/:id", get(submission::get_submission).patch(submission::update_submission))
+        .route(
+            "/submissions/:id/documents",
+            get(listing::list_unit_documents)
+                .post(document::attach_document)
+                .layer(DefaultBodyLimit::max(state.max_upload_bytes)),
+        )
+        .route(
+            "/submissions/:id/studies/:study_id/documents",
+            post(study::attach_study_documents).layer(DefaultBodyLimit::max(state.max_upload_bytes)),
+        )
+        .route("/submissions/:id/identical-documents", get(document::identical_documents))
+        .route("/submissions/:id/contexts", get(context::list_contexts))
+        .route("/submissions/:id/contexts/:context_id", patch(context::update_context))
+        .route("/submissions/:id/contexts/:context_id/move", post(context::move_context))
+        .route("/submissions/:id/contexts/reorder", post(context::reorder_contexts))
+        .route("/submissions/:id/current-view", get(context::current_view))
+        .route("/submissions/:id/keywords", get(context::list_keywords))
+        .route("/submissions/:id/keywords/:code", put(context::put_keyword).delete(context::delete_keyword))
+        .route("/submissions/:id/keywords/:code/retire", post(context::retire_keyword))
+        .route("/submissions/:id/vocabulary", get(context::keyword_vocabulary))
+        .route("/submissions/:id/placeholders", get(planning::list_placeholders).post(planning::add_placeholder))
+        .route(
+            "/submissions/:id/placeholders/:placeholder_id",
+            patch(planning::update_placeholder).delete(planning::delete_placeholder),
+        )
+        .route("/submissions/:id/readiness", get(planning::readiness))
+        .route("/submissions/:id/validate", post(submission::validate_submission))
+        .route("/submissions/:id/validations", post(job::start_validation))
+        .route("/submissions/:id/exports", post(export::start_export))
+        .route("/submissions/:id/jobs", get(job::list_jobs))
+        .route("/templates", get(template::list_templates))
+        .route(
+            "/templates/:name",
+            get(template::get_template).put(template::put_template).delete(template::delete_template),
+        )
+        .route("/jobs/:id", get(job::get_job))
+        .route("/jobs/:id/events", get(job::job_events))
+        .route("/submissions/:id/audit", get(audit::submission_audit))
+        .route("/audit", get(audit::list_audit))
+        .route("/audit/verify", get(audit::verify_audit))
         .with_state(state)
 }
@@ -1,5 +1,20 @@
+use ectd_service::EctdService;
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::auth::AuthConfig;
+use crate::jobs::JobRunner;
 
 #[derive(Clone)]
 pub struct AppState {
-    pub pool: sqlx::PgPool,
+    pub service: EctdService,
+    /// How bearer tokens are verified
+    pub auth: Arc<AuthConfig>,
+    /// Background export and validation jobs
+    pub jobs: JobRunner,
+    /// Exports are written below this folder; clients never choose output paths.
+    pub export_root: PathBuf,
+    /// Regional util files (DTDs, stylesheets) copied into every export
+    pub util_dir: Option<PathBuf>,
+    /// Largest accepted document upload
+    pub max_upload_bytes: usize,
 }
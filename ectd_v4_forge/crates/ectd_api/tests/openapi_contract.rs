@@ -0,0 +1,144 @@
+//! The published OpenAPI document must describe exactly what the router serves.
+//! Runs without a database: requests carry no token and path parameters are
+//! filled with values the extractors reject, so they never reach the service.
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Router,
+};
+use ectd_api::{auth::AuthConfig, jobs::JobRunner, openapi::ApiDoc, routes::app_router, AppState};
+use ectd_service::{storage::MemoryStore, EctdService};
+use http_body_util::BodyExt;
+use jsonwebtoken::{Algorithm, DecodingKey};
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+use utoipa::OpenApi;
+
+/// Served by the router but deliberately not part of the spec.
+const UNDOCUMENTED: &[&str] = &["/openapi.json", "/docs"];
+
+const METHODS: &[Method] = &[Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE];
+
+fn app() -> Router {
+    // Never connects: nothing in this test should get as far as the database
+    let pool = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(200))
+        .connect_lazy("postgres://nobody@127.0.0.1:1/none")
+        .unwrap();
+
+    app_router(AppState {
+        service: EctdService::new(pool, Arc::new(MemoryStore::new())),
+        auth: Arc::new(AuthConfig::jwt(DecodingKey::from_secret(b"contract"), Algorithm::HS256, None, None)),
+        jobs: JobRunner::new(1),
+        export_root: std::env::temp_dir().join("ectd-contract-exports"),
+        util_dir: None,
+        max_upload_bytes: 1024,
+    })
+}
+
+/// `/submissions/{id}/keywords/{code}` -> `/submissions/not-a-uuid/keywords/not-a-uuid`
+fn concrete(path: &str) -> String {
+    path.split('/')
+        .map(|seg| if seg.starts_with('{') { "not-a-uuid" } else { seg })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+async fn send(app: &Router, method: Method, uri: &str) -> (StatusCode, Vec<u8>) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes().to_vec();
+    (status, body)
+}
+
+#[tokio::test]
+async fn test_spec_matches_router() {
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3.1"), "{}", spec["openapi"]);
+
+    let app = app();
+    let paths = spec["paths"].as_object().expect("spec has paths");
+    assert!(!paths.is_empty());
+
+    for (path, item) in paths {
+        let uri = concrete(path);
+        for method in METHODS {
+            let documented = item.get(method.as_str().to_lowercase()).is_some();
+            let (status, body) = send(&app, method.clone(), &uri).await;
+
+            if documented {
+                // axum answers unrouted requests with an empty 404 or a 405
+                assert!(
+                    status != StatusCode::METHOD_NOT_ALLOWED && !(status == StatusCode::NOT_FOUND && body.is_empty()),
+                    "{} {} is in the spec but not routed ({})", method, path, status
+                );
+                // Anything the handlers return is a JSON error body
+                if !status.is_success() {
+                    let err: serde_json::Value = serde_json::from_slice(&body)
+                        .unwrap_or_else(|_| panic!("{} {} returned a non-JSON error", method, path));
+                    assert!(err["error"]["code"].is_string(), "{} {}: {}", method, path, err);
+                }
+            } else {
+                assert_eq!(
+                    status, StatusCode::METHOD_NOT_ALLOWED,
+                    "{} {} is routed but missing from the spec", method, path
+                );
+            }
+        }
+    }
+}
+
+/// Route paths as declared in `app_router`, in OpenAPI syntax (`:id` -> `{id}`).
+fn router_paths() -> Vec<String> {
+    include_str!("../src/routes/mod.rs")
+        .split(".route(")
+        .skip(1)
+        .map(|rest| rest.trim_start().trim_start_matches('"').split('"').next().unwrap())
+        .map(|path| {
+            path.split('/')
+                .map(|seg| match seg.strip_prefix(':') {
+                    Some(name) => format!("{{{}}}", name),
+                    None => seg.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect()
+}
+
+#[test]
+fn test_every_route_is_documented() {
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let mut documented: Vec<String> = spec["paths"].as_object().unwrap().keys().cloned().collect();
+    documented.extend(UNDOCUMENTED.iter().map(|p| p.to_string()));
+    documented.sort();
+
+    let mut routed = router_paths();
+    routed.sort();
+
+    assert_eq!(routed, documented);
+}
+
+#[tokio::test]
+async fn test_openapi_json_is_served() {
+    let app = app();
+    let (status, body) = send(&app, Method::GET, "/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+    let served: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(served, serde_json::to_value(ApiDoc::openapi()).unwrap());
+
+    for path in UNDOCUMENTED {
+        assert!(served["paths"].get(*path).is_none(), "{} should not be in the spec", path);
+        let (status, _) = send(&app, Method::GET, path).await;
+        assert_eq!(status, StatusCode::OK, "{}", path);
+    }
+}
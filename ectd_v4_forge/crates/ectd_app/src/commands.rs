@@ -1,6 +1,6 @@
 use tauri::{AppHandle, Emitter, State};
-use ectd_service::{EctdService, documents::AddDocumentParams, submission::InitSubmissionParams};
-use ectd_core::{get_standard_validator, models::submission_unit::*};
+use ectd_service::{EctdService, documents::{AddDocumentParams, DocumentReuse}, submission::InitSubmissionParams};
+use ectd_core::get_standard_validator;
 use uuid::Uuid;
 use std::path::PathBuf;
 use futures::StreamExt;
@@ -59,46 +59,22 @@ pub async fn init_submission(
     service: State<'_, EctdService>,
     args: InitArgs,
 ) -> Result<String, String> {
-    // 1. Construct the Unit (Skeleton)
-    let unit_id = Uuid::new_v4();
-    let std_oid = "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string();
-
-    let unit = SubmissionUnit {
-        id: unit_id.to_string(),
-        code: "original-application".to_string(),
-        code_system: std_oid.clone(),
-        status_code: "active".to_string(),
-        xmlns: "urn:hl7-org:v3".to_string(),
-        xmlns_xsi: Some("http://www.w3.org/2001/XMLSchema-instance".to_string()),
-        schema_location: None,
-
-        submission: Submission {
-            id: Uuid::new_v4().to_string(),
-            code: format!("seq-{:04}", args.sequence),
-            code_system: std_oid.clone(),
-            sequence_number: SequenceNumber { value: args.sequence },
-        },
-        application: Application {
-            id: Uuid::new_v4().to_string(),
-            code: args.app_type,
-            code_system: std_oid.clone(),
-            application_number: ApplicationNumber { code: args.app_number, code_system: std_oid.clone() },
-        },
-        applicant: Applicant {
-            sponsoring_organization: SponsoringOrganization { name: args.applicant },
-        },
-        context_of_use: vec![],
-        documents: vec![],
-        keyword_definitions: Some(vec![]),
+    let params = InitSubmissionParams {
+        app_number: args.app_number,
+        app_type: Some(args.app_type),
+        applicant_name: Some(args.applicant),
+        sequence_number: Some(args.sequence),
+        submission_code: Some(format!("seq-{:04}", args.sequence)),
+        template: None,
     };
 
-    // 2. Persist
-    let repo = ectd_db::repository::SubmissionRepository::new(service.pool.clone());
-    repo.create_submission(&unit).await.map_err(|e| e.to_string())?;
-
+    let unit_id = service.create_submission(params).await.map_err(|e| e.to_string())?;
     Ok(unit_id.to_string())
 }
 
+/// `reuse` defaults to "ask": a file identical to a document of an earlier
+/// sequence fails, naming that document, so the user can pick "reference"
+/// or "copy" and attach again.
 #[tauri::command]
 pub async fn add_document(
     service: State<'_, EctdService>,
@@ -106,6 +82,7 @@ pub async fn add_document(
     file_path: String,
     context: String,
     title: String,
+    reuse: Option<DocumentReuse>,
 ) -> Result<String, String> {
     let sub_uuid = Uuid::parse_str(&submission_id).map_err(|e| e.to_string())?;
 
@@ -114,11 +91,24 @@ pub async fn add_document(
         file_path: PathBuf::from(file_path),
         context_code: context,
         title: title,
-        priority: 1,
+        priority: None,
+        placeholder_id: None,
+        keywords: vec![],
+        folder: None,
+        reuse: reuse.unwrap_or_default(),
     };
 
-    let doc_id = service.attach_document(params).await.map_err(|e| e.to_string())?;
-    Ok(doc_id.to_string())
+    let attached = service.attach_document(params).await.map_err(|e| e.to_string())?;
+    Ok(attached.document_id.to_string())
+}
+
+#[tauri::command]
+pub async fn ingest_package(
+    service: State<'_, EctdService>,
+    path: String,
+) -> Result<String, String> {
+    let outcome = service.ingest_package(PathBuf::from(path)).await.map_err(|e| format!("{:#}", e))?;
+    Ok(outcome.unit_id.to_string())
 }
 
 #[tauri::command]
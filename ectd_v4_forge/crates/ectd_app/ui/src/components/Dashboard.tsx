@@ -49,7 +49,7 @@ export default function Dashboard({ submissionId, onExit }: DashboardProps) {
     }
   };
 
-  const handleAttach = async () => {
+  const handleAttach = async (reuse: "ask" | "reference" | "copy" = "ask") => {
     if (!filePath || !title) return;
     try {
       addLog(`⏳ Uploading: ${filePath}...`);
@@ -57,11 +57,23 @@ export default function Dashboard({ submissionId, onExit }: DashboardProps) {
         submissionId,
         filePath,
         context,
-        title
+        title,
+        reuse
       });
       addLog(`✅ Attached. UUID: ${docId}`);
       setFilePath(""); setTitle("");
     } catch (err) {
+      // An identical document went out in an earlier sequence: offer to reuse it
+      if (reuse === "ask" && String(err).includes("reuse 'reference'")) {
+        addLog(`♻️ ${err}`);
+        if (window.confirm(`${err}\n\nReference the earlier document instead of sending the file again?`)) {
+          return handleAttach("reference");
+        }
+        if (window.confirm("Send a new copy of the file instead?")) {
+          return handleAttach("copy");
+        }
+        return;
+      }
       addLog(`❌ Error: ${err}`);
     }
   };
@@ -129,7 +141,7 @@ export default function Dashboard({ submissionId, onExit }: DashboardProps) {
                 <option value="clinical-dataset">Dataset (m5)</option>
               </select>
             </div>
-            <button onClick={handleAttach} disabled={!filePath || !title} className="w-full bg-indigo-600 hover:bg-indigo-700 disabled:opacity-50 text-white font-medium py-2 rounded-lg transition-colors">Attach to Submission</button>
+            <button onClick={() => handleAttach()} disabled={!filePath || !title} className="w-full bg-indigo-600 hover:bg-indigo-700 disabled:opacity-50 text-white font-medium py-2 rounded-lg transition-colors">Attach to Submission</button>
           </div>
         </div>
 
@@ -1,11 +1,10 @@
 use clap::Args;
 use uuid::Uuid;
 use std::path::PathBuf;
-use aws_config::meta::region::RegionProviderChain;
-use aws_sdk_s3::{Client, config::Region};
 use sqlx::PgPool;
 
-use ectd_service::{EctdService, documents::AddDocumentParams};
+use ectd_service::documents::AddDocumentParams;
+use ectd_service::{DocumentReuse, KeywordRef};
 use crate::config::Config;
 
 #[derive(Debug, Args)]
@@ -26,37 +25,60 @@ pub struct AddDocArgs {
     #[arg(short, long)]
     pub title: String,
 
-    /// Priority Number (Default: 1)
-    #[arg(long, default_value_t = 1)]
-    pub priority: u32,
+    /// Priority Number (Default: the placeholder's, else the next for the context code)
+    #[arg(long)]
+    pub priority: Option<u32>,
+
+    /// The placeholder this document delivers (default: the open one for this context code, if any)
+    #[arg(long)]
+    pub placeholder: Option<Uuid>,
+
+    /// Keyword to tag the context of use with, "type:code" or a sponsor code (repeatable)
+    #[arg(short, long = "keyword")]
+    pub keywords: Vec<KeywordRef>,
+
+    /// Package folder (default: the placeholder's, else the one for the context code)
+    #[arg(long)]
+    pub folder: Option<String>,
+
+    /// If the file was already submitted: ask (stop and name the earlier document),
+    /// reference (point to it instead of sending the file) or copy
+    #[arg(long, default_value = "ask")]
+    pub reuse: DocumentReuse,
+
+    /// Reason recorded in the audit trail
+    #[arg(long)]
+    pub reason: Option<String>,
 }
 
 pub async fn execute(pool: PgPool, config: Config, args: AddDocArgs) -> Result<(), Box<dyn std::error::Error>> {
     println!("📎 Attaching Document via Service Layer...");
 
-    // 1. Init S3 (This ensures the CLI uses the same S3 logic as the Service expects)
-    let region_provider = RegionProviderChain::default_provider().or_else(Region::new(config.s3_region));
-    let aws_config = aws_config::from_env().region(region_provider).load().await;
-    let s3_config = aws_sdk_s3::config::Builder::from(&aws_config)
-        .force_path_style(true)
-        .endpoint_url(&config.s3_endpoint)
-        .build();
-    let s3_client = Client::from_conf(s3_config);
-
-    // 2. Init Service
-    let service = EctdService::new(pool, s3_client, config.s3_bucket);
+    // 1. Init Service (storage backend comes from config)
+    let service = config.build_service(pool).await
+        .with_reason(args.reason);
 
-    // 3. Delegate to Service
+    // 2. Delegate to Service
     let params = AddDocumentParams {
         submission_id: args.id,
         file_path: args.file,
         context_code: args.context,
         title: args.title,
         priority: args.priority,
+        placeholder_id: args.placeholder,
+        keywords: args.keywords,
+        folder: args.folder,
+        reuse: args.reuse,
     };
 
-    let doc_id = service.attach_document(params).await?;
+    let attached = service.attach_document(params).await?;
 
-    println!("✅ Document Attached. UUID: {}", doc_id);
+    match &attached.reused {
+        Some(earlier) => println!(
+            "♻️  Identical document reused: {} ({}) of application {} sequence {:04}; no new file is sent.",
+            earlier.id, earlier.href, earlier.application_number, earlier.sequence_number
+        ),
+        None => println!("✅ Document Attached. UUID: {}", attached.document_id),
+    }
     Ok(())
 }
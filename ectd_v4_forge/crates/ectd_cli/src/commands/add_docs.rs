@@ -0,0 +1,101 @@
+use clap::Args;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use uuid::Uuid;
+use ectd_service::{DocumentReuse, PlannedDocument, read_manifest};
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct AddDocsArgs {
+    /// The Submission Unit UUID to attach the files to
+    #[arg(short, long)]
+    pub id: Uuid,
+
+    /// CSV (header row) or YAML (list) with file, code, title and optionally
+    /// priority, keywords ("type:code", ';'-separated in CSV), placeholder and folder
+    #[arg(short, long)]
+    pub manifest: PathBuf,
+
+    /// Check the manifest and print the resulting tree; nothing is read, uploaded or recorded
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// For files already submitted: ask (stop and name the earlier document), reference or copy
+    #[arg(long, default_value = "ask")]
+    pub reuse: DocumentReuse,
+
+    /// Reason recorded in the audit trail
+    #[arg(long)]
+    pub reason: Option<String>,
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: AddDocsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await
+        .with_reason(args.reason);
+
+    let batch: Vec<_> = read_manifest(&args.manifest).await?
+        .into_iter()
+        .map(|entry| entry.into_params(args.id, args.reuse))
+        .collect();
+    println!("📋 Manifest lists {} document(s).", batch.len());
+
+    if args.dry_run {
+        let plans = service.plan_documents(args.id, batch).await?;
+        println!("🌳 Resulting tree (dry run, nothing attached):");
+        print_tree(&plans);
+        return Ok(());
+    }
+
+    println!("📎 Checking and uploading...");
+    let attached = service.attach_documents(args.id, batch).await?;
+    let reused = attached.iter().filter(|a| a.reused.is_some()).count();
+    for a in &attached {
+        if let Some(earlier) = &a.reused {
+            println!(
+                "   ♻️  {} reused from application {} sequence {:04} ({})",
+                a.document_id, earlier.application_number, earlier.sequence_number, earlier.href
+            );
+        }
+    }
+    println!("✅ {} document(s) attached, {} of them by reference.", attached.len(), reused);
+    Ok(())
+}
+
+/// A folder of the planned package: its subfolders and the documents in it.
+#[derive(Default)]
+struct Folder<'a> {
+    folders: BTreeMap<&'a str, Folder<'a>>,
+    files: Vec<(&'a str, &'a PlannedDocument)>,
+}
+
+fn print_tree(plans: &[PlannedDocument]) {
+    let mut root = Folder::default();
+    for plan in plans {
+        let mut segments: Vec<&str> = plan.href.split('/').collect();
+        let file = segments.pop().unwrap_or_default();
+        let folder = segments.into_iter().fold(&mut root, |f, s| f.folders.entry(s).or_default());
+        folder.files.push((file, plan));
+    }
+    print_folder(&root, 1);
+}
+
+fn print_folder(folder: &Folder, depth: usize) {
+    let indent = "   ".repeat(depth);
+    for (name, sub) in &folder.folders {
+        println!("{}📁 {}/", indent, name);
+        print_folder(sub, depth + 1);
+    }
+    for (name, plan) in &folder.files {
+        let keywords: Vec<String> = plan.keywords.iter().map(ToString::to_string).collect();
+        println!(
+            "{}📄 {}  [{} #{}] {}{}",
+            indent,
+            name,
+            plan.context_code,
+            plan.priority,
+            plan.title,
+            if keywords.is_empty() { String::new() } else { format!("  ({})", keywords.join(", ")) }
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use clap::{Args, Subcommand};
+use sqlx::PgPool;
+use ectd_db::application::{ApplicationRecord, RelatedApplication};
+use ectd_service::{ApplicationPatch, NewApplicationParams};
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct AppArgs {
+    #[command(subcommand)]
+    pub command: AppCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AppCommand {
+    /// Register a new application
+    Create {
+        /// Application Number (e.g. 123456)
+        number: String,
+
+        /// Application Type (nda, ind, bla, ...)
+        #[arg(long)]
+        app_type: String,
+
+        /// Applicant Name (e.g. "Acme Pharmaceuticals")
+        #[arg(long)]
+        applicant: String,
+
+        /// Regulatory region (default: us)
+        #[arg(long)]
+        region: Option<String>,
+
+        /// Product name
+        #[arg(long)]
+        product: Option<String>,
+
+        /// Related application as NUMBER:RELATIONSHIP (e.g. 012345:ind); repeatable
+        #[arg(long = "related", value_parser = parse_related)]
+        related: Vec<RelatedApplication>,
+
+        /// Reason recorded in the audit trail
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Show an application and its next sequence number
+    Show {
+        number: String,
+
+        /// Emit the application as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Change an application; all its units carry the change
+    Update {
+        number: String,
+
+        #[arg(long)]
+        app_type: Option<String>,
+
+        #[arg(long)]
+        applicant: Option<String>,
+
+        #[arg(long)]
+        region: Option<String>,
+
+        #[arg(long)]
+        product: Option<String>,
+
+        /// Replaces the related applications (NUMBER:RELATIONSHIP, repeatable)
+        #[arg(long = "related", value_parser = parse_related)]
+        related: Vec<RelatedApplication>,
+
+        /// Remove all related applications
+        #[arg(long, conflicts_with = "related")]
+        clear_related: bool,
+
+        /// Reason recorded in the audit trail
+        #[arg(long)]
+        reason: Option<String>,
+    },
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: AppArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await;
+
+    match args.command {
+        AppCommand::Create { number, app_type, applicant, region, product, related, reason } => {
+            let app = service.with_reason(reason).create_application(NewApplicationParams {
+                application_number: number,
+                application_type: app_type,
+                region,
+                applicant_name: applicant,
+                product_name: product,
+                related_applications: related,
+            }).await?;
+            println!("✅ Application created.");
+            print_application(&app);
+        }
+        AppCommand::Show { number, json } => {
+            let app = service.get_application(&number).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&app)?);
+            } else {
+                print_application(&app);
+            }
+        }
+        AppCommand::Update { number, app_type, applicant, region, product, related, clear_related, reason } => {
+            let related_applications = if clear_related {
+                Some(vec![])
+            } else if related.is_empty() {
+                None
+            } else {
+                Some(related)
+            };
+            let app = service.with_reason(reason).update_application(&number, ApplicationPatch {
+                application_type: app_type,
+                region,
+                applicant_name: applicant,
+                product_name: product,
+                related_applications,
+            }).await?;
+            println!("✅ Application updated.");
+            print_application(&app);
+        }
+    }
+    Ok(())
+}
+
+fn parse_related(value: &str) -> Result<RelatedApplication, String> {
+    let (number, relationship) = value.split_once(':')
+        .ok_or_else(|| format!("expected NUMBER:RELATIONSHIP, got '{}'", value))?;
+    Ok(RelatedApplication {
+        application_number: number.trim().to_string(),
+        relationship: relationship.trim().to_string(),
+    })
+}
+
+fn print_application(app: &ApplicationRecord) {
+    println!("🗂️  {} ({}, {})", app.application_number, app.application_type, app.region);
+    println!("   Applicant:     {}", app.applicant_name);
+    if let Some(product) = &app.product_name {
+        println!("   Product:       {}", product);
+    }
+    for related in &app.related_applications {
+        println!("   Related:       {} ({})", related.application_number, related.relationship);
+    }
+    println!("   Next sequence: {:04}", app.next_sequence);
+    println!("🔑 UUID: {}", app.id);
+}
@@ -0,0 +1,106 @@
+use clap::{Args, Subcommand};
+use sqlx::PgPool;
+use uuid::Uuid;
+use ectd_db::audit::{AuditQuery, AuditRepository};
+
+#[derive(Debug, Args)]
+pub struct AuditArgs {
+    #[command(subcommand)]
+    pub command: AuditCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuditCommand {
+    /// Print audit records (oldest first)
+    Show(ShowArgs),
+
+    /// Recompute the hash chain and report the first tampered record, if any
+    Verify,
+}
+
+#[derive(Debug, Args)]
+pub struct ShowArgs {
+    /// Only records about this entity (submission unit or document UUID)
+    #[arg(short, long)]
+    pub entity: Option<Uuid>,
+
+    /// Only the history of this submission unit: records about it and about
+    /// its documents, contexts of use and placeholders
+    #[arg(short, long)]
+    pub submission: Option<Uuid>,
+
+    /// Only records written by this actor
+    #[arg(long)]
+    pub actor: Option<String>,
+
+    /// Only records for this action (e.g. "document.attach")
+    #[arg(long)]
+    pub action: Option<String>,
+
+    /// Maximum number of records to print
+    #[arg(short, long)]
+    pub limit: Option<i64>,
+
+    /// Emit JSON instead of the human-readable listing
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(pool: PgPool, args: AuditArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let repo = AuditRepository::new(pool);
+
+    match args.command {
+        AuditCommand::Show(show) => {
+            let query = AuditQuery {
+                entity_id: show.entity,
+                submission_unit_id: show.submission,
+                actor: show.actor,
+                action: show.action,
+                limit: show.limit,
+            };
+            let records = repo.list(&query).await?;
+
+            if show.json {
+                println!("{}", serde_json::to_string_pretty(&records)?);
+                return Ok(());
+            }
+
+            if records.is_empty() {
+                println!("📭 No audit records found.");
+                return Ok(());
+            }
+
+            for r in &records {
+                println!("#{} {} {} by {}", r.seq, r.recorded_at, r.action, r.actor);
+                match r.entity_id {
+                    Some(id) => println!("   Entity: {} {}", r.entity_type, id),
+                    None => println!("   Entity: {}", r.entity_type),
+                }
+                if let Some(reason) = &r.reason {
+                    println!("   Reason: {}", reason);
+                }
+                if let Some(before) = &r.before_state {
+                    println!("   Before: {}", before);
+                }
+                if let Some(after) = &r.after_state {
+                    println!("   After:  {}", after);
+                }
+                println!("   Hash:   {}", r.record_hash);
+                println!("{:-<50}", "-");
+            }
+        }
+        AuditCommand::Verify => {
+            println!("🔍 Verifying audit chain...");
+            let result = repo.verify_chain().await?;
+
+            match result.first_broken_seq {
+                None => println!("✅ Chain intact. {} record(s) verified.", result.records_checked),
+                Some(seq) => {
+                    return Err(format!("Audit chain broken at record #{} (checked {} record(s))", seq, result.records_checked).into());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
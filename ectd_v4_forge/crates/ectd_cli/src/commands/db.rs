@@ -0,0 +1,61 @@
+use clap::{Args, Subcommand};
+use sqlx::PgPool;
+use ectd_db::schema::{self, MigrationState};
+
+#[derive(Debug, Args)]
+pub struct DbArgs {
+    #[command(subcommand)]
+    pub command: DbCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbCommand {
+    /// Apply all pending schema migrations
+    Migrate,
+
+    /// Show applied, pending and drifted migrations
+    Status,
+}
+
+pub async fn execute(pool: PgPool, args: DbArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        DbCommand::Migrate => migrate(pool).await,
+        DbCommand::Status => status(pool).await,
+    }
+}
+
+async fn migrate(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🏗️  Migrating Database Schema...");
+
+    let applied = schema::migrate(&pool).await?;
+
+    if applied.is_empty() {
+        println!("✅ Schema is up to date. Nothing to apply.");
+    } else {
+        println!("✅ Applied {} migration(s).", applied.len());
+    }
+    Ok(())
+}
+
+async fn status(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    let statuses = schema::status(&pool).await?;
+
+    println!("{:<8} {:<40} {:<11} APPLIED AT", "VERSION", "NAME", "STATE");
+    println!("{:-<80}", "-");
+
+    for s in &statuses {
+        let (icon, state) = match s.state {
+            MigrationState::Applied => ("✅", "applied"),
+            MigrationState::Pending => ("⏳", "pending"),
+            MigrationState::Modified => ("🛑", "MODIFIED"),
+            MigrationState::Unknown => ("🛑", "UNKNOWN"),
+        };
+        let applied_at = s.applied_at.map(|t| t.to_string()).unwrap_or_default();
+        println!("{:<8} {:<40} {} {:<8} {}", format!("{:04}", s.version), s.name, icon, state, applied_at);
+    }
+
+    if statuses.iter().any(|s| s.is_drift()) {
+        return Err("Schema drift detected: an applied migration was edited or the database is ahead of this build.".into());
+    }
+    Ok(())
+}
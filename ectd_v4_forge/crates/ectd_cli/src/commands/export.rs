@@ -2,10 +2,8 @@ use clap::Args;
 use std::path::PathBuf;
 use sqlx::PgPool;
 use uuid::Uuid;
-use aws_config::meta::region::RegionProviderChain;
-use aws_sdk_s3::{Client, config::Region};
 use crate::config::Config;
-use ectd_service::EctdService;
+use ectd_service::{ArchiveFormat, ExportIncomplete, ExportOptions};
 use futures::StreamExt; // For iterating the stream
 // Removed pin_mut import as we use BoxStream now
 
@@ -18,6 +16,27 @@ pub struct ExportArgs {
     /// The output directory (e.g. ./output/0001)
     #[arg(short, long)]
     pub output: PathBuf,
+
+    /// Reason recorded in the audit trail
+    #[arg(long)]
+    pub reason: Option<String>,
+
+    /// Refuse to export unless the unit carries a valid approval signature
+    /// (also enabled by ECTD_REQUIRE_APPROVAL=true)
+    #[arg(long)]
+    pub require_approval: bool,
+
+    /// Lay the sequence out as <application number>/<sequence>/ for ESG/CESP transmission
+    #[arg(long)]
+    pub transmission: bool,
+
+    /// Regional util folder (DTDs, stylesheets) to include as <sequence>/util/
+    #[arg(long)]
+    pub util: Option<PathBuf>,
+
+    /// Also write a reproducible archive: zip or tar
+    #[arg(long)]
+    pub archive: Option<ArchiveFormat>,
 }
 
 pub async fn execute(pool: PgPool, config: Config, args: ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
@@ -25,19 +44,17 @@ pub async fn execute(pool: PgPool, config: Config, args: ExportArgs) -> Result<(
     println!("📂 Output Directory: {:?}", args.output);
 
     // 1. Setup Service (The Engine)
-    let region_provider = RegionProviderChain::default_provider().or_else(Region::new(config.s3_region.clone()));
-    let aws_config = aws_config::from_env().region(region_provider).load().await;
-    let s3_config = aws_sdk_s3::config::Builder::from(&aws_config)
-        .force_path_style(true)
-        .endpoint_url(&config.s3_endpoint)
-        .build();
-    let s3_client = Client::from_conf(s3_config);
-    let bucket_name = config.s3_bucket.clone();
-
-    let service = EctdService::new(pool, s3_client, bucket_name);
+    let service = config.build_service(pool).await
+        .with_reason(args.reason.clone())
+        .with_approval_required(args.require_approval || config.require_approval);
 
     // 2. Consume Stream
-    let mut stream = service.export_submission_stream(args.id, args.output.clone());
+    let options = ExportOptions {
+        transmission_layout: args.transmission,
+        util_dir: args.util.clone(),
+        archive: args.archive,
+    };
+    let mut stream = service.export_package_stream(args.id, args.output.clone(), options);
 
     while let Some(result) = stream.next().await {
         match result {
@@ -57,6 +74,14 @@ pub async fn execute(pool: PgPool, config: Config, args: ExportArgs) -> Result<(
                 }
             }
             Err(e) => {
+                if let Some(incomplete) = e.downcast_ref::<ExportIncomplete>() {
+                    eprintln!("❌ {} document(s) could not be exported:", incomplete.failures.len());
+                    for failure in &incomplete.failures {
+                        eprintln!("   🛑 {}: {}", failure.file_name, failure.error);
+                    }
+                    eprintln!("   Re-run the same export to resume; files already downloaded are kept.");
+                    return Err("Export Incomplete".into());
+                }
                 return Err(format!("Export Failed: {}", e).into());
             }
         }
@@ -0,0 +1,55 @@
+use clap::Args;
+use sqlx::PgPool;
+use std::time::Duration;
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct GcArgs {
+    /// Only report what would be removed
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Grace period: leave anything touched within this many minutes
+    /// (uploads that are still on their way to a commit)
+    #[arg(long, default_value_t = 60)]
+    pub min_age_minutes: u64,
+
+    /// Emit the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: GcArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await;
+
+    if !args.json {
+        println!("🧹 Collecting garbage in {}{}...", service.store.describe(),
+            if args.dry_run { " (dry run)" } else { "" });
+    }
+
+    let min_age = Duration::from_secs(args.min_age_minutes * 60);
+    let report = service.collect_garbage(min_age, args.dry_run).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    for sha in &report.unreferenced_blobs {
+        println!("   🗑️  Unreferenced blob: {}", sha);
+    }
+    for key in &report.orphaned_objects {
+        println!("   🗑️  Orphaned object:   {}", key);
+    }
+
+    let count = report.unreferenced_blobs.len() + report.orphaned_objects.len();
+    if count == 0 {
+        println!("✅ Nothing to collect.");
+    } else if args.dry_run {
+        println!("📋 Would remove {} object(s), {} bytes.", count, report.bytes_reclaimed);
+    } else {
+        println!("✅ Removed {} object(s), reclaimed {} bytes.", count, report.bytes_reclaimed);
+    }
+
+    Ok(())
+}
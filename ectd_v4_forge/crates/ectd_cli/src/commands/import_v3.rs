@@ -0,0 +1,65 @@
+use clap::Args;
+use std::path::PathBuf;
+use sqlx::PgPool;
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct ImportV3Args {
+    /// The v3.2.2 application folder (containing 0000/, 0001/, ... with index.xml)
+    #[arg(short, long)]
+    pub dir: PathBuf,
+
+    /// Reason recorded in the audit trail
+    #[arg(long)]
+    pub reason: Option<String>,
+
+    /// Emit the outcome as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: ImportV3Args) -> Result<(), Box<dyn std::error::Error>> {
+    if !args.json {
+        println!("🏛️  Migrating eCTD v3.2.2 application: {:?}", args.dir);
+    }
+
+    // 1. Init Service
+    let service = config.build_service(pool).await
+        .with_reason(args.reason);
+
+    // 2. Replay the sequence history into v4.0 submission units
+    let outcome = match service.import_v3_application(&args.dir).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            eprintln!("❌ IMPORT FAILED: {:#}", e);
+            eprintln!("Sequences committed before the failure are kept; re-run to continue.");
+            return Err(e.into());
+        }
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&outcome)?);
+        return Ok(());
+    }
+
+    // 3. Report
+    for seq in &outcome.sequences {
+        if seq.already_imported {
+            println!("   ⏭️  {} already imported ({})", seq.sequence, seq.unit_id);
+        } else {
+            println!("   ✅ {} -> {} ({} documents, {} contexts of use)",
+                seq.sequence, seq.unit_id, seq.documents, seq.contexts_of_use);
+        }
+    }
+    for heading in &outcome.unmapped_headings {
+        println!("   ⚠️  No v4.0 mapping for heading '{}'; kept as the context code", heading);
+    }
+    for warning in &outcome.warnings {
+        println!("   ⚠️  {}", warning);
+    }
+
+    println!("📦 Stored in {}: {} uploaded, {} already present.",
+        service.store.describe(), outcome.uploaded, outcome.deduplicated);
+    println!("🎉 Application {} is ready to continue in eCTD v4.0.", outcome.application_number);
+    Ok(())
+}
@@ -1,8 +1,8 @@
 use clap::Args;
 use sqlx::PgPool;
-use uuid::Uuid;
-use ectd_core::models::submission_unit::*;
-use ectd_db::repository::SubmissionRepository;
+
+use ectd_service::submission::InitSubmissionParams;
+use crate::config::Config;
 
 #[derive(Debug, Args)]
 pub struct InitArgs {
@@ -10,78 +10,58 @@ pub struct InitArgs {
     #[arg(long)]
     pub app_number: String,
 
-    /// Application Type (nda, ind, bla)
-    #[arg(long, default_value = "nda")]
-    pub app_type: String,
-
-    /// Applicant Name (e.g. "Acme Pharmaceuticals")
+    /// Application Type (nda, ind, bla); required when the application is new
     #[arg(long)]
-    pub applicant: String,
-
-    /// Sequence Number (e.g. 1)
-    #[arg(long, default_value_t = 1)]
-    pub sequence: u32,
-}
-
-pub async fn execute(pool: PgPool, args: InitArgs) -> Result<(), Box<dyn std::error::Error>> {
-    println!("🚀 Initializing New Submission...");
-    println!("   Applicant: {}", args.applicant);
-    println!("   App #:     {} ({})", args.app_number, args.app_type);
-    println!("   Sequence:  {:04}", args.sequence);
+    pub app_type: Option<String>,
 
-    // 1. Construct the Skeleton (Pure Rust Logic)
-    let unit_id = Uuid::new_v4();
-    let submission_id = Uuid::new_v4();
-    let app_id = Uuid::new_v4();
+    /// Applicant Name (e.g. "Acme Pharmaceuticals"); required when the application is new
+    #[arg(long)]
+    pub applicant: Option<String>,
 
-    // Standard OID for FDA/ICH v4.0
-    let std_oid = "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string();
+    /// Sequence Number (default: the application's next one)
+    #[arg(long)]
+    pub sequence: Option<u32>,
 
-    let unit = SubmissionUnit {
-        xmlns: "urn:hl7-org:v3".to_string(),
-        xmlns_xsi: Some("http://www.w3.org/2001/XMLSchema-instance".to_string()),
-        schema_location: Some("urn:hl7-org:v3 ../../schema/rps.xsd".to_string()),
-        id: unit_id.to_string(),
-        code: "original-application".to_string(), // Default code
-        code_system: std_oid.clone(),
-        status_code: "active".to_string(),
+    /// Submission template (see `ectd_cli template ls`) to plan the unit from
+    #[arg(long)]
+    pub template: Option<String>,
 
-        submission: Submission {
-            id: submission_id.to_string(),
-            code: format!("seq-{:04}", args.sequence),
-            code_system: std_oid.clone(),
-            sequence_number: SequenceNumber { value: args.sequence },
-        },
+    /// Reason recorded in the audit trail
+    #[arg(long)]
+    pub reason: Option<String>,
+}
 
-        application: Application {
-            id: app_id.to_string(),
-            code: args.app_type,
-            code_system: std_oid.clone(),
-            application_number: ApplicationNumber {
-                code: args.app_number,
-                code_system: std_oid.clone(),
-            },
-        },
+pub async fn execute(pool: PgPool, config: Config, args: InitArgs) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🚀 Initializing New Submission...");
 
-        applicant: Applicant {
-            sponsoring_organization: SponsoringOrganization {
-                name: args.applicant,
-            },
-        },
+    // 1. Init Service (storage is not touched by init, but the service owns the audit trail)
+    let service = config.build_service(pool).await
+        .with_reason(args.reason);
 
-        // Empty containers for now
-        context_of_use: vec![],
-        documents: vec![],
-        keyword_definitions: Some(vec![]),
+    // 2. Delegate to Service
+    let params = InitSubmissionParams {
+        app_number: args.app_number,
+        app_type: args.app_type,
+        applicant_name: args.applicant,
+        sequence_number: args.sequence,
+        submission_code: None,
+        template: args.template,
     };
 
-    // 2. Persist to DB
-    let repo = SubmissionRepository::new(pool);
-    repo.create_submission(&unit).await?;
+    let unit_id = service.create_submission(params).await?;
+    let unit = service.get_submission(unit_id).await?;
 
+    println!("   Applicant: {}", unit.applicant.sponsoring_organization.name);
+    println!("   App #:     {} ({})", unit.application.application_number.code, unit.application.code);
+    println!("   Sequence:  {:04}", unit.submission.sequence_number.value);
+    println!("   Unit code: {}", unit.code);
     println!("✅ Submission Initialized successfully.");
     println!("🔑 UUID: {}", unit_id);
-    println!("📝 Next: Use 'add-doc' (coming soon) to populate this submission.");
+    let report = service.readiness(unit_id).await?;
+    if report.planned > 0 {
+        println!("📋 Planned {} document(s), {} required; see 'plan --id {} ls'.", report.planned, report.missing, unit_id);
+    }
+    println!("📝 Next: Use 'add-doc' to populate this submission.");
 
     Ok(())
 }
@@ -0,0 +1,99 @@
+use clap::{Args, Subcommand};
+use sqlx::PgPool;
+use uuid::Uuid;
+use ectd_service::{ContextPatch, KeywordRef};
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct KeywordArgs {
+    /// The Submission Unit UUID
+    #[arg(short, long)]
+    pub id: Uuid,
+
+    /// Reason recorded in the audit trail
+    #[arg(long, global = true)]
+    pub reason: Option<String>,
+
+    #[command(subcommand)]
+    pub command: KeywordCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KeywordCommand {
+    /// List the keywords in force for the unit, including earlier sequences' definitions
+    Ls,
+
+    /// Define a keyword in this unit, or rename one from an earlier sequence
+    Define {
+        /// "type:code" (e.g. "manufacturer:acme-plant-3") or a sponsor keyword code
+        keyword: KeywordRef,
+
+        /// Display name
+        name: String,
+    },
+
+    /// Retire a keyword from this sequence on
+    Retire {
+        keyword: KeywordRef,
+    },
+
+    /// Remove this unit's definition of a keyword
+    Rm {
+        keyword: KeywordRef,
+    },
+
+    /// Replace the keywords of a context of use (none clears them)
+    Tag {
+        /// Context of use UUID
+        context: Uuid,
+
+        keywords: Vec<KeywordRef>,
+    },
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: KeywordArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await
+        .with_reason(args.reason);
+    let id = args.id;
+
+    match args.command {
+        KeywordCommand::Ls => {
+            let vocabulary = service.keyword_vocabulary(id).await?;
+            if vocabulary.is_empty() {
+                println!("📭 No keywords defined for this application yet.");
+            }
+            for k in &vocabulary {
+                let keyword_type = k.keyword_type.map(|t| t.code()).unwrap_or(k.code_system.as_str());
+                println!(
+                    "{} {:<36} \"{}\"  seq {:04}  {} context(s)",
+                    if k.is_active() { "🏷️ " } else { "🚫" },
+                    format!("{}:{}", keyword_type, k.code),
+                    k.display_name,
+                    k.sequence_number,
+                    k.usage
+                );
+            }
+        }
+        KeywordCommand::Define { keyword, name } => {
+            service.put_keyword(id, keyword.keyword_type, &keyword.code, &name).await?;
+            println!("✅ Keyword {} is \"{}\".", keyword, name);
+        }
+        KeywordCommand::Retire { keyword } => {
+            service.retire_keyword(id, keyword.keyword_type, &keyword.code).await?;
+            println!("🚫 Keyword {} retired from this sequence on.", keyword);
+        }
+        KeywordCommand::Rm { keyword } => {
+            service.delete_keyword(id, keyword.keyword_type, &keyword.code).await?;
+            println!("🗑️  This unit's definition of {} removed.", keyword);
+        }
+        KeywordCommand::Tag { context, keywords } => {
+            let cou = service.update_context(id, context, ContextPatch {
+                keywords: Some(keywords),
+                ..Default::default()
+            }).await?;
+            let tags: Vec<&str> = cou.keywords.iter().map(|k| k.code.as_str()).collect();
+            println!("✅ Context {} ({}) tagged: {}", context, cou.code, if tags.is_empty() { "-".to_string() } else { tags.join(", ") });
+        }
+    }
+    Ok(())
+}
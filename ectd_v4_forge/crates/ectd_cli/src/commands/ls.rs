@@ -0,0 +1,155 @@
+use clap::Args;
+use sqlx::PgPool;
+use time::{format_description::well_known::Rfc3339, macros::format_description, Date, OffsetDateTime};
+use ectd_db::listing::{PageRequest, SubmissionFilter, SubmissionSort};
+use ectd_db::repository::SubmissionSummary;
+use ectd_service::EctdService;
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct LsArgs {
+    /// Only this application number
+    #[arg(short, long)]
+    pub app: Option<String>,
+
+    /// Only applicants whose name contains this (case-insensitive)
+    #[arg(long)]
+    pub applicant: Option<String>,
+
+    /// Lowest sequence number to list
+    #[arg(long)]
+    pub from: Option<i32>,
+
+    /// Highest sequence number to list
+    #[arg(long)]
+    pub to: Option<i32>,
+
+    /// Only units with this status (active, suspended)
+    #[arg(long)]
+    pub status: Option<String>,
+
+    /// Only units (and with --documents, documents) with a context of use of this code
+    #[arg(long)]
+    pub context: Option<String>,
+
+    /// Only units created on or after this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_parser = parse_when)]
+    pub since: Option<OffsetDateTime>,
+
+    /// Only units created before this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_parser = parse_when)]
+    pub until: Option<OffsetDateTime>,
+
+    /// application, newest or oldest
+    #[arg(long, default_value_t = SubmissionSort::Application)]
+    pub sort: SubmissionSort,
+
+    /// Also list each unit's documents
+    #[arg(short, long)]
+    pub documents: bool,
+
+    /// Units per page
+    #[arg(short, long, default_value_t = 50)]
+    pub limit: i64,
+
+    /// Continue a previous listing (printed at the end of a full page)
+    #[arg(long)]
+    pub cursor: Option<String>,
+
+    /// Emit the page as JSON instead of a tree
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: LsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await;
+
+    let filter = SubmissionFilter {
+        application_number: args.app.clone(),
+        applicant: args.applicant.clone(),
+        sequence_from: args.from,
+        sequence_to: args.to,
+        status_code: args.status.clone(),
+        context_code: args.context.clone(),
+        created_after: args.since,
+        created_before: args.until,
+        applications: None,
+    };
+    let page = PageRequest { cursor: args.cursor.clone(), limit: Some(args.limit) };
+    let units = service.list_submissions(&filter, args.sort, &page).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&units)?);
+        return Ok(());
+    }
+
+    if units.items.is_empty() {
+        println!("📭 No submission units found.");
+        return Ok(());
+    }
+
+    // Group by application, keeping the order of the listing
+    let mut applications: Vec<(&str, Vec<&SubmissionSummary>)> = Vec::new();
+    for unit in &units.items {
+        match applications.iter_mut().find(|(app, _)| *app == unit.application_number) {
+            Some((_, group)) => group.push(unit),
+            None => applications.push((&unit.application_number, vec![unit])),
+        }
+    }
+
+    for (app, group) in &applications {
+        let head = group[0];
+        println!("📁 {} ({}) {}", app, head.application_code.to_uppercase(), head.applicant_name);
+
+        for (i, unit) in group.iter().enumerate() {
+            let last = i + 1 == group.len();
+            let (branch, indent) = if last { ("└──", "    ") } else { ("├──", "│   ") };
+            println!(
+                "{} {:04} {} [{}] {} document(s), {}  {}",
+                branch, unit.sequence_number, unit.submission_code, unit.status_code,
+                unit.document_count, unit.created_at.date(), unit.id
+            );
+            if args.documents {
+                print_documents(&service, &filter, unit, indent).await?;
+            }
+        }
+    }
+
+    if let Some(cursor) = &units.next_cursor {
+        println!("… more units: ectd_cli ls <same filters> --cursor {}", cursor);
+    }
+
+    Ok(())
+}
+
+async fn print_documents(
+    service: &EctdService,
+    filter: &SubmissionFilter,
+    unit: &SubmissionSummary,
+    indent: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut documents = Vec::new();
+    let mut page = PageRequest { cursor: None, limit: Some(ectd_db::listing::MAX_PAGE_SIZE) };
+    loop {
+        let batch = service.list_documents(filter, Some(unit.id), &page).await?;
+        documents.extend(batch.items);
+        match batch.next_cursor {
+            Some(cursor) => page.cursor = Some(cursor),
+            None => break,
+        }
+    }
+
+    for (i, doc) in documents.iter().enumerate() {
+        let branch = if i + 1 == documents.len() { "└──" } else { "├──" };
+        let contexts = if doc.context_codes.is_empty() { "no context".to_string() } else { doc.context_codes.join(", ") };
+        println!("{}{} 📄 {}  \"{}\"  [{}]", indent, branch, doc.href, doc.title, contexts);
+    }
+    Ok(())
+}
+
+fn parse_when(s: &str) -> Result<OffsetDateTime, String> {
+    if let Ok(date) = Date::parse(s, format_description!("[year]-[month]-[day]")) {
+        return Ok(date.midnight().assume_utc());
+    }
+    OffsetDateTime::parse(s, &Rfc3339).map_err(|_| format!("'{}' is not a date (YYYY-MM-DD) or RFC 3339 timestamp", s))
+}
@@ -16,3 +16,18 @@ pub enum Commands {
 pub mod forge_data;
 pub mod export;
 pub mod add_doc;
+pub mod db;
+pub mod audit;
+pub mod sign;
+pub mod gc;
+pub mod verify;
+pub mod import_v3;
+pub mod ls;
+pub mod app;
+pub mod plan;
+pub mod template;
+pub mod keyword;
+pub mod search;
+pub mod study;
+pub mod priority;
+pub mod add_docs;
@@ -0,0 +1,191 @@
+use clap::{Args, Subcommand};
+use sqlx::PgPool;
+use time::{macros::format_description, Date};
+use uuid::Uuid;
+use ectd_db::planning::Placeholder;
+use ectd_service::{NewPlaceholderParams, PlaceholderPatch};
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct PlanArgs {
+    /// The Submission Unit UUID being planned
+    #[arg(short, long)]
+    pub id: Uuid,
+
+    /// Reason recorded in the audit trail
+    #[arg(long, global = true)]
+    pub reason: Option<String>,
+
+    #[command(subcommand)]
+    pub command: PlanCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PlanCommand {
+    /// Expect a document under a context code
+    Add {
+        /// The eCTD Context Code (e.g. "clinical-overview")
+        code: String,
+
+        /// Expected document title
+        #[arg(short, long)]
+        title: Option<String>,
+
+        /// Who delivers the document
+        #[arg(short, long)]
+        owner: Option<String>,
+
+        /// Due date (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_date)]
+        due: Option<Date>,
+
+        #[arg(long)]
+        note: Option<String>,
+
+        /// Priority of the context of use, unless the attach gives one
+        #[arg(long)]
+        priority: Option<u32>,
+
+        /// Package folder for the document, instead of the default for its code
+        #[arg(long)]
+        folder: Option<String>,
+
+        /// Don't hold up the export while it is missing
+        #[arg(long)]
+        optional: bool,
+    },
+
+    /// List the planned documents, open ones first
+    Ls,
+
+    /// Change an open placeholder
+    Update {
+        placeholder: Uuid,
+
+        #[arg(long)]
+        code: Option<String>,
+
+        #[arg(short, long)]
+        title: Option<String>,
+
+        #[arg(short, long)]
+        owner: Option<String>,
+
+        #[arg(long, value_parser = parse_date)]
+        due: Option<Date>,
+
+        #[arg(long)]
+        note: Option<String>,
+
+        #[arg(long)]
+        priority: Option<u32>,
+
+        #[arg(long)]
+        folder: Option<String>,
+
+        /// Required (true) or optional (false)
+        #[arg(long)]
+        required: Option<bool>,
+    },
+
+    /// Drop an open placeholder from the plan
+    Rm {
+        placeholder: Uuid,
+    },
+
+    /// Report what is still missing; fails while the unit isn't ready for export
+    Status {
+        /// Emit the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: PlanArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await
+        .with_reason(args.reason);
+    let id = args.id;
+
+    match args.command {
+        PlanCommand::Add { code, title, owner, due, note, priority, folder, optional } => {
+            let placeholder = service.add_placeholder(id, NewPlaceholderParams {
+                code, title, owner, due_date: due, note, required: !optional, priority, folder,
+            }).await?;
+            println!("📌 Placeholder added.");
+            print_placeholder(&placeholder);
+        }
+        PlanCommand::Ls => {
+            let placeholders = service.list_placeholders(id).await?;
+            if placeholders.is_empty() {
+                println!("📭 Nothing planned for this unit.");
+            }
+            for placeholder in &placeholders {
+                print_placeholder(placeholder);
+            }
+        }
+        PlanCommand::Update { placeholder, code, title, owner, due, note, priority, folder, required } => {
+            let placeholder = service.update_placeholder(id, placeholder, PlaceholderPatch {
+                code, title, owner, due_date: due, note, required, priority, folder,
+            }).await?;
+            println!("✅ Placeholder updated.");
+            print_placeholder(&placeholder);
+        }
+        PlanCommand::Rm { placeholder } => {
+            service.remove_placeholder(id, placeholder).await?;
+            println!("🗑️  Placeholder {} removed.", placeholder);
+        }
+        PlanCommand::Status { json } => {
+            let report = service.readiness(id).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                for placeholder in &report.open {
+                    print_placeholder(placeholder);
+                }
+                println!(
+                    "📋 {} of {} planned document(s) attached, {} required missing, {} overdue.",
+                    report.fulfilled, report.planned, report.missing, report.overdue
+                );
+            }
+            if !report.ready {
+                return Err(format!("Unit {} is not ready for export: {} required document(s) missing.", id, report.missing).into());
+            }
+            println!("✅ Ready for export.");
+        }
+    }
+    Ok(())
+}
+
+fn print_placeholder(p: &Placeholder) {
+    let icon = match (p.is_open(), p.overdue, p.required) {
+        (false, _, _) => "✅",
+        (true, true, _) => "⏰",
+        (true, false, true) => "⏳",
+        (true, false, false) => "○",
+    };
+    let due = p.due_date.map(|d| format!("due {}", d)).unwrap_or_else(|| "no due date".to_string());
+    println!(
+        "{} {}{}  {}  \"{}\"  {}  {}",
+        icon,
+        p.code,
+        if p.required { "" } else { " (optional)" },
+        p.owner.as_deref().unwrap_or("unassigned"),
+        p.title.as_deref().unwrap_or("untitled"),
+        due,
+        p.id
+    );
+    if let Some(folder) = &p.folder {
+        println!("   📁 {}", folder);
+    }
+    if let Some(document_id) = p.document_id {
+        println!("   📄 document {}", document_id);
+    }
+    if let Some(note) = &p.note {
+        println!("   📝 {}", note);
+    }
+}
+
+fn parse_date(s: &str) -> Result<Date, String> {
+    Date::parse(s, format_description!("[year]-[month]-[day]"))
+        .map_err(|_| format!("'{}' is not a date (YYYY-MM-DD)", s))
+}
@@ -0,0 +1,95 @@
+use clap::{Args, Subcommand};
+use sqlx::PgPool;
+use uuid::Uuid;
+use ectd_db::priority::ViewContext;
+use ectd_service::{MoveDirection, ReorderContextsParams};
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct PriorityArgs {
+    /// The Submission Unit UUID
+    #[arg(short, long)]
+    pub id: Uuid,
+
+    /// Reason recorded in the audit trail
+    #[arg(long, global = true)]
+    pub reason: Option<String>,
+
+    #[command(subcommand)]
+    pub command: PriorityCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PriorityCommand {
+    /// List the current view by code and priority, including earlier sequences' contexts
+    Ls {
+        /// Only this context code
+        code: Option<String>,
+    },
+
+    /// Move a context of use before the previous one of its code
+    Up {
+        /// Context of use UUID
+        context: Uuid,
+    },
+
+    /// Move a context of use after the next one of its code
+    Down {
+        /// Context of use UUID
+        context: Uuid,
+    },
+
+    /// Order all of the unit's contexts of a code, first to last
+    Set {
+        code: String,
+
+        /// Context of use UUIDs
+        #[arg(required = true)]
+        contexts: Vec<Uuid>,
+    },
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: PriorityArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await
+        .with_reason(args.reason);
+    let id = args.id;
+
+    let (contexts, changed) = match args.command {
+        PriorityCommand::Ls { code } => {
+            let view: Vec<ViewContext> = service.current_view(id).await?
+                .into_iter()
+                .filter(|c| code.as_ref().is_none_or(|code| c.code == *code))
+                .collect();
+            if view.is_empty() {
+                println!("📭 No contexts of use in force.");
+                return Ok(());
+            }
+            (view, false)
+        }
+        PriorityCommand::Up { context } => (service.move_context(id, context, MoveDirection::Up).await?, true),
+        PriorityCommand::Down { context } => (service.move_context(id, context, MoveDirection::Down).await?, true),
+        PriorityCommand::Set { code, contexts } => {
+            let params = ReorderContextsParams { code, context_ids: contexts };
+            (service.reorder_contexts(id, params).await?, true)
+        }
+    };
+
+    if changed {
+        println!("✅ Contexts of use renumbered:");
+    }
+    let mut code = "";
+    for c in &contexts {
+        if c.code != code {
+            code = &c.code;
+            println!("🔢 {}", code);
+        }
+        println!(
+            "   #{:<3} {:<9}  {}  {}",
+            c.priority,
+            if c.submission_unit_id == id { "this unit".to_string() } else { format!("seq {:04}", c.sequence_number) },
+            c.context_of_use_id,
+            c.title.as_deref().unwrap_or("-")
+        );
+    }
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use clap::Args;
 use sqlx::PgPool;
-use ectd_db::schema::rebuild_database;
+use ectd_db::schema::migrate;
 
 #[derive(Debug, Args)]
 pub struct RebuildArgs {
@@ -20,8 +20,8 @@ pub async fn execute(pool: PgPool, args: RebuildArgs) -> Result<(), Box<dyn std:
             .execute(&pool).await?;
     }
 
-    rebuild_database(&pool).await?;
+    let applied = migrate(&pool).await?;
 
-    println!("✅ Database Schema Applied Successfully.");
+    println!("✅ Database Schema Applied Successfully ({} migration(s)).", applied.len());
     Ok(())
 }
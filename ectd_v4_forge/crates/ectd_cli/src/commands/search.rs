@@ -0,0 +1,93 @@
+use clap::Args;
+use sqlx::PgPool;
+use ectd_db::listing::{PageRequest, SubmissionFilter};
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Words to find in titles, keywords and PDF text ("exact phrase", or, -excluded)
+    #[arg(required_unless_present = "reindex")]
+    pub query: Option<String>,
+
+    /// Only this application number
+    #[arg(short, long)]
+    pub app: Option<String>,
+
+    /// Lowest sequence number to search
+    #[arg(long)]
+    pub from: Option<i32>,
+
+    /// Highest sequence number to search
+    #[arg(long)]
+    pub to: Option<i32>,
+
+    /// Only documents with a context of use of this code
+    #[arg(long)]
+    pub context: Option<String>,
+
+    /// Matches per page
+    #[arg(short, long, default_value_t = 20)]
+    pub limit: i64,
+
+    /// Continue a previous search (printed at the end of a full page)
+    #[arg(long)]
+    pub cursor: Option<String>,
+
+    /// Emit the page as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Extract text from stored PDFs that are not searchable yet, instead of searching
+    #[arg(long, conflicts_with = "query")]
+    pub reindex: bool,
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: SearchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await;
+
+    if args.reindex {
+        println!("🔤 Extracting text from unindexed PDFs in {}...", service.store.describe());
+        let report = service.reindex_text().await?;
+        for failure in &report.failed {
+            println!("   ⚠️  {}", failure);
+        }
+        println!("✅ Indexed {} file(s), {} failed.", report.indexed, report.failed.len());
+        return Ok(());
+    }
+
+    let query = args.query.unwrap_or_default();
+    let filter = SubmissionFilter {
+        application_number: args.app,
+        sequence_from: args.from,
+        sequence_to: args.to,
+        context_code: args.context,
+        ..Default::default()
+    };
+    let page = PageRequest { cursor: args.cursor, limit: Some(args.limit) };
+    let hits = service.search_documents(&query, &filter, &page).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&hits)?);
+        return Ok(());
+    }
+
+    if hits.items.is_empty() {
+        println!("📭 No documents match \"{}\".", query);
+        return Ok(());
+    }
+
+    for hit in &hits.items {
+        let contexts = if hit.context_codes.is_empty() { "no context".to_string() } else { hit.context_codes.join(", ") };
+        println!(
+            "📄 {} seq {:04}  {}  \"{}\"  [{}]  {:.3}",
+            hit.application_number, hit.sequence_number, hit.href, hit.title, contexts, hit.rank
+        );
+        println!("   {}", hit.snippet.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+
+    if let Some(cursor) = &hits.next_cursor {
+        println!("… more matches: ectd_cli search <same query> --cursor {}", cursor);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use clap::Args;
+use uuid::Uuid;
+use sqlx::PgPool;
+
+use ectd_db::signature::SignatureMeaning;
+use ectd_service::SignParams;
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct SignArgs {
+    /// The Submission Unit UUID to sign
+    #[arg(short, long)]
+    pub id: Uuid,
+
+    /// Meaning of the signature: author, review or approve
+    #[arg(short, long)]
+    pub meaning: SignatureMeaning,
+
+    /// Optional comment stored with the signature
+    #[arg(short, long)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct SignaturesArgs {
+    /// The Submission Unit UUID whose signatures to list
+    #[arg(short, long)]
+    pub id: Uuid,
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: SignArgs) -> Result<(), Box<dyn std::error::Error>> {
+    println!("✍️  Signing Submission Unit {} as '{}' ({})...", args.id, config.actor, args.meaning);
+
+    let service = config.build_service(pool).await;
+    let record = service.sign_submission(SignParams {
+        submission_id: args.id,
+        meaning: args.meaning,
+        comment: args.comment,
+    }).await?;
+
+    println!("✅ Signature recorded. UUID: {}", record.id);
+    println!("🔒 Bound to content digest: {}", record.content_digest);
+    Ok(())
+}
+
+pub async fn list(pool: PgPool, config: Config, args: SignaturesArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await;
+    let statuses = service.signature_status(args.id).await?;
+
+    if statuses.is_empty() {
+        println!("📭 No signatures on Submission Unit {}.", args.id);
+        return Ok(());
+    }
+
+    for s in &statuses {
+        let icon = if s.valid { "✅" } else { "❌" };
+        println!("{} [{}] {} at {}", icon, s.signature.meaning, s.signature.signer, s.signature.signed_at);
+        if let Some(comment) = &s.signature.comment {
+            println!("   Comment: {}", comment);
+        }
+        if !s.valid {
+            println!("   Invalidated: the submission changed after this signature was applied.");
+        }
+    }
+    Ok(())
+}
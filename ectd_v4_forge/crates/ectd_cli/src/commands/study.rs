@@ -0,0 +1,218 @@
+use clap::{Args, Subcommand};
+use sqlx::PgPool;
+use std::path::PathBuf;
+use uuid::Uuid;
+use ectd_db::study::Study;
+use ectd_service::{DocumentReuse, KeywordRef, NewStudyParams, StudyDocumentParams, StudyDocumentRole, StudyPatch};
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct StudyArgs {
+    /// Reason recorded in the audit trail
+    #[arg(long, global = true)]
+    pub reason: Option<String>,
+
+    #[command(subcommand)]
+    pub command: StudyCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum StudyCommand {
+    /// Register a study of an application
+    Add {
+        /// Application Number
+        #[arg(long)]
+        app: String,
+
+        /// Study id; becomes its study-id keyword and folder name (e.g. ABC-101)
+        study: String,
+
+        #[arg(short, long)]
+        title: String,
+
+        /// Category (e.g. pk, efficacy, safety); repeatable
+        #[arg(long = "category")]
+        categories: Vec<String>,
+
+        /// Context code of its report, protocol and appendices (default: clinical-study-report)
+        #[arg(long)]
+        report_code: Option<String>,
+    },
+
+    /// List the application's studies
+    Ls {
+        #[arg(long)]
+        app: String,
+    },
+
+    /// Show a study and its documents in every sequence
+    Show {
+        #[arg(long)]
+        app: String,
+
+        study: String,
+
+        /// Emit the study as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Change a study's title, categories or report code
+    Update {
+        #[arg(long)]
+        app: String,
+
+        study: String,
+
+        #[arg(short, long)]
+        title: Option<String>,
+
+        /// Replaces the categories; repeatable
+        #[arg(long = "category")]
+        categories: Vec<String>,
+
+        /// Remove all categories
+        #[arg(long, conflicts_with = "categories")]
+        clear_categories: bool,
+
+        #[arg(long)]
+        report_code: Option<String>,
+    },
+
+    /// Attach a study's documents to a unit, all or none, tagged and ordered by role
+    Attach {
+        /// The Submission Unit UUID
+        #[arg(short, long)]
+        id: Uuid,
+
+        study: String,
+
+        #[arg(long)]
+        protocol: Vec<PathBuf>,
+
+        /// Study report body
+        #[arg(long)]
+        report: Vec<PathBuf>,
+
+        #[arg(long)]
+        appendix: Vec<PathBuf>,
+
+        /// Case report forms
+        #[arg(long)]
+        crf: Vec<PathBuf>,
+
+        #[arg(long)]
+        dataset: Vec<PathBuf>,
+
+        /// Data definition (define.xml)
+        #[arg(long)]
+        define: Vec<PathBuf>,
+
+        /// Further keyword for every document, "type:code" or a sponsor code (repeatable)
+        #[arg(short, long = "keyword")]
+        keywords: Vec<KeywordRef>,
+
+        /// For files already submitted: ask (stop and name the earlier document), reference or copy
+        #[arg(long, default_value = "ask")]
+        reuse: DocumentReuse,
+    },
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: StudyArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await
+        .with_reason(args.reason);
+
+    match args.command {
+        StudyCommand::Add { app, study, title, categories, report_code } => {
+            let created = service.create_study(&app, NewStudyParams {
+                study_id: study,
+                title,
+                categories,
+                report_code,
+            }).await?;
+            println!("✅ Study registered:");
+            print_study(&created);
+        }
+        StudyCommand::Ls { app } => {
+            let studies = service.list_studies(&app).await?;
+            if studies.is_empty() {
+                println!("📭 No studies registered for application {}.", app);
+            }
+            for s in &studies {
+                println!(
+                    "🔬 {:<16} {:<24} {} [{}]",
+                    s.study_id,
+                    s.report_code,
+                    s.title,
+                    s.categories.join(", ")
+                );
+            }
+        }
+        StudyCommand::Show { app, study, json } => {
+            let record = service.get_study(&app, &study).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&record)?);
+                return Ok(());
+            }
+            print_study(&record);
+            let documents = service.study_documents(&app, &study).await?;
+            if documents.is_empty() {
+                println!("   📭 No documents yet.");
+            }
+            for d in &documents {
+                println!(
+                    "   seq {:04}  {:<24} #{:<3} {}  {}",
+                    d.sequence_number,
+                    d.code,
+                    d.priority,
+                    d.title.as_deref().unwrap_or("-"),
+                    d.href.as_deref().unwrap_or("")
+                );
+            }
+        }
+        StudyCommand::Update { app, study, title, categories, clear_categories, report_code } => {
+            let categories = if clear_categories {
+                Some(Vec::new())
+            } else {
+                Some(categories).filter(|c| !c.is_empty())
+            };
+            let updated = service.update_study(&app, &study, StudyPatch { title, categories, report_code }).await?;
+            println!("✅ Study updated:");
+            print_study(&updated);
+        }
+        StudyCommand::Attach { id, study, protocol, report, appendix, crf, dataset, define, keywords, reuse } => {
+            let documents: Vec<StudyDocumentParams> = [
+                (StudyDocumentRole::Protocol, protocol),
+                (StudyDocumentRole::Report, report),
+                (StudyDocumentRole::Appendix, appendix),
+                (StudyDocumentRole::Crf, crf),
+                (StudyDocumentRole::Dataset, dataset),
+                (StudyDocumentRole::Define, define),
+            ]
+            .into_iter()
+            .flat_map(|(role, files)| files.into_iter().map(move |file_path| (role, file_path)))
+            .map(|(role, file_path)| StudyDocumentParams { role, file_path, title: None, keywords: keywords.clone(), reuse })
+            .collect();
+
+            println!("📎 Attaching {} document(s) of study {}...", documents.len(), study);
+            let attached = service.attach_study_documents(id, &study, documents).await?;
+            for a in &attached {
+                match &a.reused {
+                    Some(earlier) => println!(
+                        "   ♻️  {} reused from application {} sequence {:04} ({})",
+                        a.document_id, earlier.application_number, earlier.sequence_number, earlier.href
+                    ),
+                    None => println!("   ✅ {}", a.document_id),
+                }
+            }
+            println!("✅ {} document(s) attached.", attached.len());
+        }
+    }
+    Ok(())
+}
+
+fn print_study(s: &Study) {
+    println!("🔬 Study {}: {}", s.study_id, s.title);
+    println!("   Report code: {}", s.report_code);
+    println!("   Categories:  {}", if s.categories.is_empty() { "-".to_string() } else { s.categories.join(", ") });
+}
@@ -0,0 +1,106 @@
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+use sqlx::PgPool;
+use ectd_db::template::SubmissionTemplate;
+use crate::config::Config;
+
+#[derive(Debug, Args)]
+pub struct TemplateArgs {
+    #[command(subcommand)]
+    pub command: TemplateCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TemplateCommand {
+    /// List the submission templates
+    Ls,
+
+    /// Show a template (--json prints it in the form `import` reads)
+    Show {
+        name: String,
+
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Create or replace a template from a JSON file
+    Import {
+        file: PathBuf,
+
+        /// Reason recorded in the audit trail
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Remove a template; units created from it are unaffected
+    Rm {
+        name: String,
+
+        /// Reason recorded in the audit trail
+        #[arg(long)]
+        reason: Option<String>,
+    },
+}
+
+pub async fn execute(pool: PgPool, config: Config, args: TemplateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let service = config.build_service(pool).await;
+
+    match args.command {
+        TemplateCommand::Ls => {
+            for t in service.list_templates().await? {
+                let required = t.contexts.iter().filter(|c| c.required).count();
+                println!(
+                    "🧩 {:<24} {:<24} {:>2} required, {:>2} optional  {}",
+                    t.name,
+                    t.unit_code,
+                    required,
+                    t.contexts.len() - required,
+                    t.description.as_deref().unwrap_or("")
+                );
+            }
+        }
+        TemplateCommand::Show { name, json } => {
+            let t = service.get_template(&name).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&t)?);
+            } else {
+                print_template(&t);
+            }
+        }
+        TemplateCommand::Import { file, reason } => {
+            let text = std::fs::read_to_string(&file)
+                .map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+            let template: SubmissionTemplate = serde_json::from_str(&text)
+                .map_err(|e| format!("{:?} is not a valid template: {}", file, e))?;
+            let (t, created) = service.with_reason(reason).save_template(template).await?;
+            println!("✅ Template {} {}.", t.name, if created { "created" } else { "replaced" });
+            print_template(&t);
+        }
+        TemplateCommand::Rm { name, reason } => {
+            service.with_reason(reason).delete_template(&name).await?;
+            println!("🗑️  Template {} removed.", name);
+        }
+    }
+    Ok(())
+}
+
+fn print_template(t: &SubmissionTemplate) {
+    println!("🧩 {}  {}", t.name, t.description.as_deref().unwrap_or(""));
+    println!("   Unit code:        {}", t.unit_code);
+    println!("   Application type: {}", t.application_type.as_deref().unwrap_or("any"));
+    for c in &t.contexts {
+        let priority = c.priority.map(|p| format!("priority {}", p)).unwrap_or_default();
+        let folder = c.folder.as_deref().unwrap_or_else(|| ectd_core::resolve_folder_path(&c.code));
+        println!(
+            "   {} {:<28} {:<12} {:<30} {}",
+            if c.required { "●" } else { "○" },
+            c.code,
+            priority,
+            folder,
+            c.title.as_deref().unwrap_or("")
+        );
+    }
+    for k in &t.keywords {
+        println!("   🏷️  {}:{} = {}", k.keyword_type.code(), k.code, k.display_name);
+    }
+}
@@ -0,0 +1,54 @@
+use clap::Args;
+use std::path::PathBuf;
+use ectd_service::verify_package;
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// Exported package: the sequence folder or a transmission root
+    #[arg(short, long)]
+    pub dir: PathBuf,
+
+    /// Emit the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(args: VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !args.json {
+        println!("🔍 Verifying package: {:?}", args.dir);
+    }
+
+    // 1. Re-read the package from disk (no database involved)
+    let report = verify_package(&args.dir)?;
+
+    // 2. Report
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("   Sequence folder: {:?}", report.root);
+        println!("   Files checked:   {}", report.files_checked);
+        println!("{:-<50}", "-");
+
+        for finding in &report.findings {
+            let icon = if finding.severity.contains("High") { "🛑" } else { "⚠️" };
+            println!("{} [{}] {}", icon, finding.code, finding.severity);
+            println!("   Msg: {}", finding.message);
+            if let Some(target) = &finding.target_id {
+                println!("   Ref: {}", target);
+            }
+            println!("{:-<50}", "-");
+        }
+    }
+
+    // 3. Go / no-go
+    if report.is_go() {
+        if !args.json {
+            println!("✅ GO: package is intact and passes validation.");
+        }
+        Ok(())
+    } else {
+        Err(format!("NO-GO: {} blocking finding(s) in {:?}",
+            report.findings.iter().filter(|f| f.severity.contains("High Error")).count(),
+            report.root).into())
+    }
+}
@@ -1,13 +1,15 @@
 use std::env;
 use dotenvy::dotenv;
 use anyhow::{Context, Result};
+use sqlx::PgPool;
+use ectd_service::{EctdService, StorageBackend};
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub database_url: String,
-    pub s3_endpoint: String,
-    pub s3_bucket: String,
-    pub s3_region: String,
+    pub storage: StorageBackend,
+    pub actor: String,
+    pub require_approval: bool,
 }
 
 impl Config {
@@ -18,14 +20,25 @@ impl Config {
             database_url: env::var("DATABASE_URL")
                 .context("DATABASE_URL must be set")?,
 
-            s3_endpoint: env::var("S3_ENDPOINT")
-                .unwrap_or_else(|_| "http://localhost:9000".to_string()),
+            // STORAGE_BACKEND=s3 (default, MinIO) | local | memory
+            storage: StorageBackend::from_env()?,
 
-            s3_bucket: env::var("S3_BUCKET")
-                .unwrap_or_else(|_| "ectd-documents".to_string()),
+            // Recorded in the audit trail. Falls back to the OS login name.
+            actor: env::var("ECTD_ACTOR")
+                .or_else(|_| env::var("USER"))
+                .or_else(|_| env::var("USERNAME"))
+                .unwrap_or_else(|_| "unknown".to_string()),
 
-            s3_region: env::var("AWS_REGION")
-                .unwrap_or_else(|_| "us-east-1".to_string()),
+            // SOP switch: refuse to export units without a valid approval signature.
+            require_approval: env::var("ECTD_REQUIRE_APPROVAL")
+                .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
         })
     }
+
+    /// The one place commands get a service from: storage backend plus audit actor.
+    pub async fn build_service(&self, pool: PgPool) -> EctdService {
+        let store = self.storage.build().await;
+        EctdService::new(pool, store).with_actor(self.actor.clone())
+    }
 }
@@ -1,6 +1,6 @@
 // ectd_cli/src/main.rs
 use clap::{Parser, Subcommand};
-use sqlx::postgres::PgPoolOptions;
+use sqlx::postgres::{PgPool, PgPoolOptions};
 
 use ectd_cli::commands;
 use ectd_cli::config::Config;
@@ -33,11 +33,59 @@ enum Commands {
     /// Rebuild the database schema from embedded assets
     Rebuild(commands::rebuild::RebuildArgs),
 
+    /// Manage versioned schema migrations
+    Db(commands::db::DbArgs),
+
+    /// Inspect and verify the audit trail
+    Audit(commands::audit::AuditArgs),
+
+    /// Electronically sign a submission unit (author, review or approve)
+    Sign(commands::sign::SignArgs),
+
+    /// List signatures on a submission unit and whether they are still valid
+    Signatures(commands::sign::SignaturesArgs),
+
+    /// Migrate a legacy eCTD v3.2.2 application (all sequences) to v4.0
+    ImportV3(commands::import_v3::ImportV3Args),
+
+    /// Re-read an exported package and decide whether it is fit to transmit
+    Verify(commands::verify::VerifyArgs),
+
+    /// Remove unreferenced blobs and orphaned objects from document storage
+    Gc(commands::gc::GcArgs),
+
     /// Initialize a new empty submission with metadata
     Init(commands::init::InitArgs),
 
     /// Add a document to an existing submission
     AddDoc(commands::add_doc::AddDocArgs),
+
+    /// List applications, sequences and (optionally) documents as a tree
+    Ls(commands::ls::LsArgs),
+
+    /// Full-text search over document titles, keywords and PDF text
+    Search(commands::search::SearchArgs),
+
+    /// Create, show and change applications
+    App(commands::app::AppArgs),
+
+    /// Plan the documents of a sequence and check it is ready for export
+    Plan(commands::plan::PlanArgs),
+
+    /// Manage the submission templates `init --template` plans units from
+    Template(commands::template::TemplateArgs),
+
+    /// Define, retire and attach keywords (sponsor, study id, manufacturer, ...)
+    Keyword(commands::keyword::KeywordArgs),
+
+    /// Register studies and attach their Module 4/5 documents as a group
+    Study(commands::study::StudyArgs),
+
+    /// Show and change the priority order of contexts of use
+    Priority(commands::priority::PriorityArgs),
+
+    /// Add the documents a CSV or YAML manifest lists to a submission, all or none
+    AddDocs(commands::add_docs::AddDocsArgs),
 }
 
 #[tokio::main]
@@ -51,16 +99,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     match cli.command {
         Commands::Ingest(args) => {
             // 2. Connect to the Brain (Postgres)
-            let pool = PgPoolOptions::new()
-                .max_connections(5)
-                .connect(&config.database_url)
-                .await?;
+            let pool = connect(&config).await?;
             commands::ingest::execute(pool, config, args).await?;
         }
         Commands::Validate(args) => {
             // Note: Validate doesn't need the 'pool', keeping it pure logic.
             commands::validate::execute(args).await?;
         }
+        Commands::ImportV3(args) => {
+            let pool = connect(&config).await?;
+            commands::import_v3::execute(pool, config, args).await?;
+        }
+        Commands::Verify(args) => {
+            // Like Validate, verification only reads the package from disk.
+            commands::verify::execute(args).await?;
+        }
         Commands::ImportStandard(args) => {
             commands::import_standard::run(args)?;
         }
@@ -68,34 +121,97 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             commands::forge_data::run(args)?;
         }
         Commands::Export(args) => {
-            let pool = PgPoolOptions::new()
-                .max_connections(5)
-                .connect(&config.database_url)
-                .await?;
+            let pool = connect(&config).await?;
             commands::export::execute(pool, config, args).await?;
         }
         Commands::Rebuild(args) => {
+            // Not `connect` either: it repairs databases that fail the check
             let pool = PgPoolOptions::new()
                 .max_connections(5)
                 .connect(&config.database_url)
                 .await?;
             commands::rebuild::execute(pool, args).await?;
         }
-        Commands::Init(args) => {
+        Commands::Db(args) => {
+            // Not `connect`: this is how an outdated schema gets migrated
             let pool = PgPoolOptions::new()
                 .max_connections(5)
                 .connect(&config.database_url)
                 .await?;
-            commands::init::execute(pool, args).await?;
+            commands::db::execute(pool, args).await?;
+        }
+        Commands::Audit(args) => {
+            let pool = connect(&config).await?;
+            commands::audit::execute(pool, args).await?;
+        }
+        Commands::Sign(args) => {
+            let pool = connect(&config).await?;
+            commands::sign::execute(pool, config, args).await?;
+        }
+        Commands::Signatures(args) => {
+            let pool = connect(&config).await?;
+            commands::sign::list(pool, config, args).await?;
+        }
+        Commands::Gc(args) => {
+            let pool = connect(&config).await?;
+            commands::gc::execute(pool, config, args).await?;
+        }
+        Commands::Init(args) => {
+            let pool = connect(&config).await?;
+            commands::init::execute(pool, config, args).await?;
         }
         Commands::AddDoc(args) => {
-            let pool = PgPoolOptions::new()
-                .max_connections(5)
-                .connect(&config.database_url)
-                .await?;
+            let pool = connect(&config).await?;
             commands::add_doc::execute(pool, config, args).await?;
         }
+        Commands::Ls(args) => {
+            let pool = connect(&config).await?;
+            commands::ls::execute(pool, config, args).await?;
+        }
+        Commands::Search(args) => {
+            let pool = connect(&config).await?;
+            commands::search::execute(pool, config, args).await?;
+        }
+        Commands::App(args) => {
+            let pool = connect(&config).await?;
+            commands::app::execute(pool, config, args).await?;
+        }
+        Commands::Plan(args) => {
+            let pool = connect(&config).await?;
+            commands::plan::execute(pool, config, args).await?;
+        }
+        Commands::Template(args) => {
+            let pool = connect(&config).await?;
+            commands::template::execute(pool, config, args).await?;
+        }
+        Commands::Keyword(args) => {
+            let pool = connect(&config).await?;
+            commands::keyword::execute(pool, config, args).await?;
+        }
+        Commands::Study(args) => {
+            let pool = connect(&config).await?;
+            commands::study::execute(pool, config, args).await?;
+        }
+        Commands::Priority(args) => {
+            let pool = connect(&config).await?;
+            commands::priority::execute(pool, config, args).await?;
+        }
+        Commands::AddDocs(args) => {
+            let pool = connect(&config).await?;
+            commands::add_docs::execute(pool, config, args).await?;
+        }
     }
 
     Ok(())
 }
+
+/// Connects for a command that works on the stored data, refusing to run
+/// against a schema with pending or drifted migrations.
+async fn connect(config: &Config) -> Result<PgPool, Box<dyn std::error::Error>> {
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&config.database_url)
+        .await?;
+    ectd_db::schema::ensure_current(&pool).await?;
+    Ok(pool)
+}
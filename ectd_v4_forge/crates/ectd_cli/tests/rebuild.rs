@@ -0,0 +1,27 @@
+use std::process::Command;
+
+/// The scratch database's URL: DATABASE_URL with its database name swapped.
+fn url_of(pool: &sqlx::PgPool) -> String {
+    let base = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let (server, _) = base.rsplit_once('/').expect("DATABASE_URL names a database");
+    let database = pool.connect_options().get_database().expect("scratch database").to_string();
+    format!("{}/{}", server, database)
+}
+
+#[sqlx::test(migrations = false)]
+async fn test_rebuild_reset_repairs_a_database_with_a_pending_migration(pool: sqlx::PgPool) {
+    ectd_db::schema::migrate(&pool).await.unwrap();
+    sqlx::query("DELETE FROM schema_migrations WHERE version = (SELECT MAX(version) FROM schema_migrations)")
+        .execute(&pool).await.unwrap();
+    assert!(ectd_db::schema::ensure_current(&pool).await.is_err());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ectd_cli"))
+        .args(["rebuild", "--reset"])
+        .env("DATABASE_URL", url_of(&pool))
+        .env("STORAGE_BACKEND", "memory")
+        .output()
+        .expect("Failed to run rebuild");
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    ectd_db::schema::ensure_current(&pool).await.unwrap();
+}
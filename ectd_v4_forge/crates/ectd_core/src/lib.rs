@@ -1,5 +1,8 @@
 pub mod models;
 pub mod validation;
+pub mod media_type;
+pub mod text;
+pub mod v3;
 
 use validation::{ValidationEngine, rules, rules_pdf};
 
@@ -157,5 +160,58 @@ mod tests {
         assert_eq!(kw.code, "my-term");
         assert_eq!(kw.value.item.display_name.value, "My Custom Term");
     }
+
+    #[test]
+    fn test_content_digest_detects_changes() {
+        let unit: SubmissionUnit = from_str(SAMPLE_XML).unwrap();
+        let original = unit.content_digest().unwrap();
+
+        // Deterministic for identical content
+        let again: SubmissionUnit = from_str(SAMPLE_XML).unwrap();
+        assert_eq!(original, again.content_digest().unwrap());
+
+        // A replaced file changes the digest
+        let mut changed: SubmissionUnit = from_str(SAMPLE_XML).unwrap();
+        changed.documents[0].text.checksum = "0".repeat(64);
+        assert_ne!(original, changed.content_digest().unwrap());
+
+        // So does a metadata edit
+        let mut changed: SubmissionUnit = from_str(SAMPLE_XML).unwrap();
+        changed.documents[0].title.value = "Revised Cover Letter".to_string();
+        assert_ne!(original, changed.content_digest().unwrap());
+    }
+
+    #[test]
+    fn test_exported_xml_round_trips() {
+        // What export writes must be readable by ingest and verify.
+        let unit: SubmissionUnit = from_str(SAMPLE_XML).unwrap();
+        let xml = unit.to_xml().unwrap();
+        assert!(xml.contains("<submissionUnit "), "Root element must be submissionUnit");
+
+        let reparsed: SubmissionUnit = from_str(&xml).expect("Exported XML must parse");
+        assert_eq!(reparsed.id, unit.id);
+        assert_eq!(reparsed.documents.len(), 1);
+        assert_eq!(reparsed.context_of_use.len(), 1);
+        assert!(reparsed.context_of_use[0].related_context_of_use.is_none());
+        assert_eq!(reparsed.to_xml().unwrap(), xml);
+    }
+
+    #[test]
+    fn test_document_reference_resolves_in_this_or_an_earlier_sequence() {
+        use crate::validation::{rules::RuleDocumentReference, ValidationRule};
+
+        let unit: SubmissionUnit = from_str(SAMPLE_XML).unwrap();
+        assert!(RuleDocumentReference::default().check(&unit).is_empty());
+
+        // The document was sent in sequence 0001; this sequence only references it
+        let mut reusing: SubmissionUnit = from_str(SAMPLE_XML).unwrap();
+        reusing.documents.clear();
+        let findings = RuleDocumentReference::default().check(&reusing);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].target_id.as_deref(), Some("cccccccc-cccc-cccc-cccc-cccccccccccc"));
+
+        let earlier = RuleDocumentReference { inherited: vec!["DDDDDDDD-DDDD-DDDD-DDDD-DDDDDDDDDDDD".to_string()] };
+        assert!(earlier.check(&reusing).is_empty());
+    }
 }
 pub mod sdtm;
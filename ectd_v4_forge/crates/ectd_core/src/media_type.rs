@@ -0,0 +1,90 @@
+//! Content-based media type detection for submission files.
+//! File extensions and declared `mediaType` attributes are both claims;
+//! the leading bytes of the file are evidence.
+
+pub const PDF: &str = "application/pdf";
+pub const XPT: &str = "application/x-sas-xport";
+pub const XML: &str = "application/xml";
+pub const DOCX: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+pub const JPEG: &str = "image/jpeg";
+
+/// Bytes `detect` needs to see. DOCX is recognized from the ZIP entry names,
+/// which Word writes within the first few local file headers.
+pub const SNIFF_LEN: usize = 8192;
+
+/// Detects the media type from the leading bytes of a file (up to `SNIFF_LEN`).
+/// Returns None for content that isn't one of the supported types.
+pub fn detect(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"%PDF-") {
+        return Some(PDF);
+    }
+    if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(JPEG);
+    }
+    // SAS Transport v5 ("LIBRARY") and v8 ("LIBV8")
+    if head.starts_with(b"HEADER RECORD*******LIBRARY HEADER RECORD!!!!!!!")
+        || head.starts_with(b"HEADER RECORD*******LIBV8   HEADER RECORD!!!!!!!")
+    {
+        return Some(XPT);
+    }
+    if head.starts_with(b"PK\x03\x04") && contains(head, b"word/") {
+        return Some(DOCX);
+    }
+    if looks_like_xml(head) {
+        return Some(XML);
+    }
+    None
+}
+
+/// Maps aliases onto the names `detect` returns, so a declared
+/// `text/xml` matches detected XML.
+pub fn normalize(media_type: &str) -> String {
+    let lower = media_type.trim().to_ascii_lowercase();
+    match lower.as_str() {
+        "text/xml" => XML.to_string(),
+        "image/jpg" | "image/pjpeg" => JPEG.to_string(),
+        "application/x-xport" | "application/x-sas-transport" => XPT.to_string(),
+        _ => lower,
+    }
+}
+
+fn looks_like_xml(head: &[u8]) -> bool {
+    let head = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(head); // UTF-8 BOM
+    let start = head.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(head.len());
+    head[start..].starts_with(b"<?xml")
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_supported_types() {
+        assert_eq!(detect(b"%PDF-1.7\n%\xE2\xE3"), Some(PDF));
+        assert_eq!(detect(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10]), Some(JPEG));
+        assert_eq!(
+            detect(b"HEADER RECORD*******LIBRARY HEADER RECORD!!!!!!!000000000000000000000000000000"),
+            Some(XPT)
+        );
+        assert_eq!(detect(b"PK\x03\x04\x14\x00\x06\x00[Content_Types].xmlPK\x03\x04word/document.xml"), Some(DOCX));
+        assert_eq!(detect(b"\xEF\xBB\xBF  <?xml version=\"1.0\"?><root/>"), Some(XML));
+    }
+
+    #[test]
+    fn test_unknown_and_lookalikes() {
+        assert_eq!(detect(b""), None);
+        assert_eq!(detect(b"PK\x03\x04xl/workbook.xml"), None); // XLSX, not DOCX
+        assert_eq!(detect(b"plain text <?xml"), None);
+    }
+
+    #[test]
+    fn test_normalize_aliases() {
+        assert_eq!(normalize("text/xml"), XML);
+        assert_eq!(normalize(" Application/PDF "), PDF);
+        assert_eq!(normalize("image/jpg"), JPEG);
+    }
+}
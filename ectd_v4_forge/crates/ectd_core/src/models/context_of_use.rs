@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 // ---------------------------------------------------------------------------
 // 3. The Connector: Context of Use (CoU)
 // Reference: PDF Section 4.2.5
 // ---------------------------------------------------------------------------
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ContextOfUse {
     #[serde(rename = "@id")]
     pub id: String, // UUID
@@ -24,11 +25,11 @@ pub struct ContextOfUse {
 
     // Link to the physical document
     // Rule eCTD4-027: Required for active CoU
-    #[serde(rename = "documentReference")]
+    #[serde(rename = "documentReference", skip_serializing_if = "Option::is_none")]
     pub document_reference: Option<DocumentReference>,
 
     // Lifecycle: Replacing an old CoU?
-    #[serde(rename = "relatedContextOfUse", default)]
+    #[serde(rename = "relatedContextOfUse", default, skip_serializing_if = "Option::is_none")]
     pub related_context_of_use: Option<RelatedContextOfUse>,
 
     // Keywords attached to this CoU
@@ -36,25 +37,25 @@ pub struct ContextOfUse {
     pub keywords: Vec<Keyword>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PriorityNumber {
     #[serde(rename = "@value")]
     pub value: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct DocumentReference {
     #[serde(rename = "id")]
     pub id: DocumentIdRef,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct DocumentIdRef {
     #[serde(rename = "@root")]
     pub root: String, // The UUID of the <document> element
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RelatedContextOfUse {
     #[serde(rename = "id")]
     pub id: DocumentIdRef, // Points to the PREVIOUS CoU UUID
@@ -67,7 +68,7 @@ pub struct RelatedContextOfUse {
 // 4. The Keywords
 // Reference: PDF Section 4.2.8
 // ---------------------------------------------------------------------------
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Keyword {
     #[serde(rename = "@code")]
     pub code: String,
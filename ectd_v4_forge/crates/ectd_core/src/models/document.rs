@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 // ---------------------------------------------------------------------------
 // 5. The Physical Document
 // Reference: PDF Section 4.2.13
 // ---------------------------------------------------------------------------
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Document {
     #[serde(rename = "@id")]
     pub id: String, // UUID
@@ -18,13 +19,13 @@ pub struct Document {
     pub text: DocumentText,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct DocumentTitle {
     #[serde(rename = "@value")]
     pub value: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct DocumentText {
     // Rule eCTD4-050: Document Path
     #[serde(rename = "reference")]
@@ -41,7 +42,7 @@ pub struct DocumentText {
     pub media_type: String, // "application/pdf"
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct DocumentReferencePath {
     #[serde(rename = "@value")]
     pub value: String, // "m1/us/cover.pdf"
@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 // ---------------------------------------------------------------------------
 // 6. Keyword Definitions (Custom Vocabulary)
 // Reference: PDF Section 4.2.14
 // ---------------------------------------------------------------------------
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct KeywordDefinition {
     #[serde(rename = "@code")]
     pub code: String,
@@ -14,15 +15,26 @@ pub struct KeywordDefinition {
 
     #[serde(rename = "value")]
     pub value: KeywordDefinitionValue,
+
+    // "active", or "inactive" once a later sequence retires it
+    #[serde(rename = "@statusCode", default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<String>,
+}
+
+impl KeywordDefinition {
+    /// Definitions without a status are active.
+    pub fn is_active(&self) -> bool {
+        self.status_code.as_deref().is_none_or(|s| s == "active")
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct KeywordDefinitionValue {
     #[serde(rename = "item")]
     pub item: KeywordDefinitionItem,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct KeywordDefinitionItem {
     #[serde(rename = "@code")]
     pub code: String,
@@ -31,8 +43,67 @@ pub struct KeywordDefinitionItem {
     pub display_name: DisplayName,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct DisplayName {
     #[serde(rename = "@value")]
     pub value: String,
 }
+
+// ---------------------------------------------------------------------------
+// 7. Keyword Types
+// Each type is its own code system; a keyword on a Context of Use names the
+// code system of its type and the code of a definition in it.
+// ---------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeywordType {
+    /// Free sponsor vocabulary
+    #[default]
+    Sponsor,
+    StudyId,
+    Manufacturer,
+    Substance,
+    DosageForm,
+    Indication,
+}
+
+impl KeywordType {
+    pub const ALL: [KeywordType; 6] = [
+        KeywordType::Sponsor,
+        KeywordType::StudyId,
+        KeywordType::Manufacturer,
+        KeywordType::Substance,
+        KeywordType::DosageForm,
+        KeywordType::Indication,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            KeywordType::Sponsor => "sponsor",
+            KeywordType::StudyId => "study-id",
+            KeywordType::Manufacturer => "manufacturer",
+            KeywordType::Substance => "substance",
+            KeywordType::DosageForm => "dosage-form",
+            KeywordType::Indication => "indication",
+        }
+    }
+
+    pub const fn code_system(&self) -> &'static str {
+        match self {
+            KeywordType::Sponsor => "urn:oid:2.16.840.1.113883.3.989.2.1.1.1",
+            KeywordType::StudyId => "urn:oid:2.16.840.1.113883.3.989.2.1.1.1.1",
+            KeywordType::Manufacturer => "urn:oid:2.16.840.1.113883.3.989.2.1.1.1.2",
+            KeywordType::Substance => "urn:oid:2.16.840.1.113883.3.989.2.1.1.1.3",
+            KeywordType::DosageForm => "urn:oid:2.16.840.1.113883.3.989.2.1.1.1.4",
+            KeywordType::Indication => "urn:oid:2.16.840.1.113883.3.989.2.1.1.1.5",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<KeywordType> {
+        Self::ALL.into_iter().find(|t| t.code() == code)
+    }
+
+    pub fn from_code_system(code_system: &str) -> Option<KeywordType> {
+        Self::ALL.into_iter().find(|t| t.code_system() == code_system)
+    }
+}
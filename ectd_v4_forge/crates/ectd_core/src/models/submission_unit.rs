@@ -1,13 +1,15 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use quick_xml::se::to_string;
 use anyhow::Result;
+use sha2::{Digest, Sha256};
 
 // ---------------------------------------------------------------------------
 // 1. The Root Container: <submissionUnit>
 // Reference: PDF Section 4.2.2
 // ---------------------------------------------------------------------------
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")] // eCTD v4.0 tags are usually camelCase
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename = "submissionUnit", rename_all = "camelCase")] // eCTD v4.0 tags are usually camelCase
 pub struct SubmissionUnit {
     // -------------------
     // Root Attributes
@@ -15,10 +17,10 @@ pub struct SubmissionUnit {
     #[serde(rename = "@xmlns")]
     pub xmlns: String, // usually "urn:hl7-org:v3"
 
-    #[serde(rename = "@xmlns:xsi")]
+    #[serde(rename = "@xmlns:xsi", skip_serializing_if = "Option::is_none")]
     pub xmlns_xsi: Option<String>,
 
-    #[serde(rename = "@xsi:schemaLocation")]
+    #[serde(rename = "@xsi:schemaLocation", skip_serializing_if = "Option::is_none")]
     pub schema_location: Option<String>,
 
     // -------------------
@@ -70,7 +72,7 @@ pub struct SubmissionUnit {
     pub documents: Vec<Document>,
 
     // Reference: PDF 4.2.14
-    #[serde(rename = "keywordDefinition", default)]
+    #[serde(rename = "keywordDefinition", default, skip_serializing_if = "Option::is_none")]
     pub keyword_definitions: Option<Vec<KeywordDefinition>>,
 }
 
@@ -84,12 +86,31 @@ impl SubmissionUnit {
         // The eCTD standard requires UTF-8 and version 1.0
         Ok(format!(r#"<?xml version="1.0" encoding="UTF-8"?>{}"#, xml_body))
     }
+
+    /// SHA-256 binding the unit's XML and every document checksum.
+    /// E-signatures record this value; any later change to metadata or files alters it.
+    pub fn content_digest(&self) -> Result<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(self.to_xml()?.as_bytes());
+
+        let mut checksums: Vec<(&str, &str)> = self.documents
+            .iter()
+            .map(|d| (d.id.as_str(), d.text.checksum.as_str()))
+            .collect();
+        checksums.sort();
+
+        for (id, checksum) in checksums {
+            hasher.update(format!("\n{}  {}", checksum, id).as_bytes());
+        }
+
+        Ok(hex::encode(hasher.finalize()))
+    }
 }
 
 // ---------------------------------------------------------------------------
 // 2. Metadata Blocks (Submission, Application, Applicant)
 // ---------------------------------------------------------------------------
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Submission {
     #[serde(rename = "@id")]
@@ -106,14 +127,14 @@ pub struct Submission {
     pub sequence_number: SequenceNumber,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SequenceNumber {
     #[serde(rename = "@value")]
     pub value: u32, // 0001
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Application {
     #[serde(rename = "@id")]
@@ -129,7 +150,7 @@ pub struct Application {
     pub application_number: ApplicationNumber,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ApplicationNumber {
     // Rule US-eCTD4-510: 6 digits only
@@ -140,13 +161,13 @@ pub struct ApplicationNumber {
     pub code_system: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Applicant {
     pub sponsoring_organization: SponsoringOrganization,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SponsoringOrganization {
     #[serde(rename = "name")]
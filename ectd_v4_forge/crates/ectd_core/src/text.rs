@@ -0,0 +1,144 @@
+//! Plain-text extraction from submission files, for the full-text search index.
+//! Only PDFs carry text we can read; scanned pages without a text layer
+//! simply come out empty.
+
+use lopdf::Document as PdfDocument;
+use std::path::Path;
+
+/// Upper bound on the text kept per file. Postgres caps a tsvector at 1 MB,
+/// and the opening pages of a report are what people search for.
+pub const MAX_TEXT_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedText {
+    /// Page texts joined by newlines, whitespace collapsed
+    pub content: String,
+    pub page_count: u32,
+    /// The content was cut at [`MAX_TEXT_BYTES`]
+    pub truncated: bool,
+}
+
+/// Extracts the text layer of a PDF, page by page. Fonts or pages lopdf
+/// cannot decode are skipped rather than failing the whole file; only an
+/// unreadable file is an error.
+pub fn extract_pdf_text(path: &Path) -> Result<ExtractedText, lopdf::Error> {
+    let pdf = PdfDocument::load(path)?;
+    let pages: Vec<u32> = pdf.get_pages().keys().copied().collect();
+
+    let mut content = String::new();
+    let mut truncated = false;
+    for page in &pages {
+        let text: String = pdf.extract_text_chunks(&[*page])
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let text = normalize(&text);
+        if text.is_empty() {
+            continue;
+        }
+
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        if content.len() + text.len() > MAX_TEXT_BYTES {
+            content.push_str(truncate_at_char(&text, MAX_TEXT_BYTES.saturating_sub(content.len())));
+            truncated = true;
+            break;
+        }
+        content.push_str(&text);
+    }
+
+    Ok(ExtractedText { content, page_count: pages.len() as u32, truncated })
+}
+
+/// Collapses whitespace and drops control characters (Postgres rejects NUL in text).
+fn normalize(text: &str) -> String {
+    text.split(|c: char| c.is_whitespace() || c.is_control())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn truncate_at_char(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::{Content, Operation};
+    use lopdf::{dictionary, Object, Stream};
+
+    fn write_pdf(path: &Path, pages: &[&str]) {
+        let mut doc = PdfDocument::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+
+        let mut kids = Vec::new();
+        for text in pages {
+            let content = Content {
+                operations: vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![72.into(), 720.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(*text)]),
+                    Operation::new("ET", vec![]),
+                ],
+            };
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            let page_id = doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            });
+            kids.push(page_id.into());
+        }
+
+        doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => pages.len() as i64,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }));
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        doc.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_extracts_text_of_every_page() {
+        let path = std::env::temp_dir().join(format!("ectd-text-{}.pdf", uuid::Uuid::new_v4()));
+        write_pdf(&path, &["Stability report", "Batch   42 at 25C/60%RH"]);
+
+        let extracted = extract_pdf_text(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(extracted.page_count, 2);
+        assert!(!extracted.truncated);
+        assert_eq!(extracted.content, "Stability report\nBatch 42 at 25C/60%RH");
+    }
+
+    #[test]
+    fn test_normalize_and_truncate() {
+        assert_eq!(normalize("  a\t\tb\u{0}c \n"), "a b c");
+        assert_eq!(truncate_at_char("héllo", 2), "h");
+        assert_eq!(truncate_at_char("abc", 10), "abc");
+    }
+}
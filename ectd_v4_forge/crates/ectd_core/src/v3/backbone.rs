@@ -0,0 +1,365 @@
+use anyhow::{Context, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::str::FromStr;
+
+// ---------------------------------------------------------------------------
+// eCTD v3.2.2 backbones (index.xml and the regional XML) are read as a stream
+// of events: the CTD heading tree is deep and mostly fixed, and all we need
+// from it is each <leaf> together with the heading it sits under.
+// ---------------------------------------------------------------------------
+
+/// The v3.2.2 leaf lifecycle operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeafOperation {
+    New,
+    Append,
+    Replace,
+    Delete,
+}
+
+impl FromStr for LeafOperation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "new" => Ok(LeafOperation::New),
+            "append" => Ok(LeafOperation::Append),
+            "replace" => Ok(LeafOperation::Replace),
+            "delete" => Ok(LeafOperation::Delete),
+            other => Err(format!("Unknown leaf operation '{}'", other)),
+        }
+    }
+}
+
+/// Points at the leaf a lifecycle operation acts on (`modified-file`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LeafRef {
+    /// Sequence folder of the target leaf, e.g. "0000"
+    pub sequence: String,
+    /// The target leaf's ID attribute
+    pub leaf_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leaf {
+    pub id: String,
+    pub title: String,
+    /// Nearest CTD heading element, e.g. "m2-3-quality-overall-summary"
+    pub heading: String,
+    /// Titles of enclosing node-extensions, outermost first
+    pub node_extensions: Vec<String>,
+    pub operation: LeafOperation,
+    /// File path relative to the leaf's own sequence folder (None for deletes)
+    pub href: Option<String>,
+    /// File path relative to the application folder (None for deletes)
+    pub file: Option<String>,
+    pub checksum: Option<String>,
+    pub checksum_type: Option<String>,
+    pub modified_file: Option<LeafRef>,
+}
+
+/// Administrative header of the US regional XML.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegionalHeader {
+    pub applicant: Option<String>,
+    pub application_number: Option<String>,
+    pub application_type: Option<String>,
+    pub submission_type: Option<String>,
+    pub sequence_number: Option<String>,
+}
+
+struct Frame {
+    name: String,
+    leaf: Option<Leaf>,
+    extension_title: Option<String>,
+}
+
+/// Extracts every leaf from a backbone.
+/// `sequence` is the sequence folder ("0001") and `xml_dir` the backbone's
+/// folder inside it ("" for index.xml, "m1/us" for us-regional.xml); hrefs
+/// are resolved against both.
+pub fn parse_leaves(xml: &str, sequence: &str, xml_dir: &str) -> Result<Vec<Leaf>> {
+    let base = join(sequence, xml_dir);
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut leaves = Vec::new();
+
+    loop {
+        match reader.read_event().context("Malformed v3.2.2 backbone")? {
+            Event::Start(e) => {
+                let frame = open_frame(&e, &stack, sequence, &base)?;
+                stack.push(frame);
+            }
+            Event::Empty(e) => {
+                let frame = open_frame(&e, &stack, sequence, &base)?;
+                if let Some(leaf) = frame.leaf {
+                    leaves.push(leaf);
+                }
+            }
+            Event::Text(t) => {
+                let text = t.unescape()?;
+                let depth = stack.len();
+                if depth >= 2 && stack[depth - 1].name == "title" {
+                    let parent = &mut stack[depth - 2];
+                    if let Some(leaf) = parent.leaf.as_mut() {
+                        leaf.title.push_str(&text);
+                    } else if parent.name == "node-extension" {
+                        parent.extension_title.get_or_insert_with(String::new).push_str(&text);
+                    }
+                }
+            }
+            Event::End(_) => {
+                if let Some(frame) = stack.pop() {
+                    if let Some(leaf) = frame.leaf {
+                        leaves.push(leaf);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(leaves)
+}
+
+/// Reads applicant, application and submission details from a US regional XML.
+/// Covers both the 2.01 layout and the older DTD-based one; fields are looked
+/// up by element/attribute name wherever they appear.
+pub fn parse_regional_header(xml: &str) -> Result<RegionalHeader> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut header = RegionalHeader::default();
+    let mut current = String::new();
+
+    loop {
+        match reader.read_event().context("Malformed regional XML")? {
+            Event::Start(e) | Event::Empty(e) => {
+                current = local_name(&e);
+                for attr in e.attributes().flatten() {
+                    let value = attr.unescape_value()?.to_string();
+                    match attr.key.local_name().as_ref() {
+                        b"application-type" => { header.application_type.get_or_insert(value); }
+                        b"submission-type" => { header.submission_type.get_or_insert(value); }
+                        _ => {}
+                    }
+                }
+            }
+            Event::Text(t) => {
+                let text = t.unescape()?.trim().to_string();
+                match current.as_str() {
+                    "company-name" => { header.applicant.get_or_insert(text); }
+                    "application-number" => { header.application_number.get_or_insert(text); }
+                    "sequence-number" => { header.sequence_number.get_or_insert(text); }
+                    _ => {}
+                }
+            }
+            Event::End(_) => current.clear(),
+            Event::Eof => break,
+            // The clinical sections follow the header; nothing more to learn
+            _ => {}
+        }
+    }
+
+    Ok(header)
+}
+
+fn open_frame(e: &BytesStart, stack: &[Frame], sequence: &str, base: &str) -> Result<Frame> {
+    let name = local_name(e);
+    let leaf = if name == "leaf" { Some(read_leaf(e, stack, sequence, base)?) } else { None };
+    Ok(Frame { name, leaf, extension_title: None })
+}
+
+fn read_leaf(e: &BytesStart, stack: &[Frame], sequence: &str, base: &str) -> Result<Leaf> {
+    let mut id = String::new();
+    let mut operation = LeafOperation::New;
+    let mut href = None;
+    let mut checksum = None;
+    let mut checksum_type = None;
+    let mut modified_file = None;
+
+    for attr in e.attributes() {
+        let attr = attr?;
+        let value = attr.unescape_value()?.to_string();
+        match attr.key.local_name().as_ref() {
+            b"ID" => id = value,
+            b"operation" => operation = value.parse().map_err(anyhow::Error::msg)?,
+            b"href" if !value.is_empty() => href = Some(value),
+            b"checksum" if !value.is_empty() => checksum = Some(value),
+            b"checksum-type" => checksum_type = Some(value),
+            b"modified-file" if !value.is_empty() => modified_file = parse_modified_file(base, &value),
+            _ => {}
+        }
+    }
+
+    let file = match &href {
+        Some(h) => Some(resolve(base, h).context(format!("Leaf {} points outside the application: {}", id, h))?),
+        None => None,
+    };
+    let href = file.as_ref().map(|f| {
+        // Relative to the folder the file lives in, normally the leaf's own sequence
+        let prefix = format!("{}/", sequence);
+        f.strip_prefix(&prefix).unwrap_or_else(|| f.split_once('/').map_or(f.as_str(), |(_, rest)| rest)).to_string()
+    });
+
+    let heading = stack
+        .iter()
+        .rev()
+        .map(|f| f.name.as_str())
+        .find(|n| *n != "node-extension" && *n != "title")
+        .unwrap_or_default()
+        .to_string();
+    let node_extensions = stack
+        .iter()
+        .filter(|f| f.name == "node-extension")
+        .map(|f| f.extension_title.clone().unwrap_or_default())
+        .collect();
+
+    Ok(Leaf {
+        id,
+        title: String::new(),
+        heading,
+        node_extensions,
+        operation,
+        href,
+        file,
+        checksum,
+        checksum_type,
+        modified_file,
+    })
+}
+
+/// "../0000/index.xml#leaf-12" seen from `base` -> sequence "0000", leaf "leaf-12"
+fn parse_modified_file(base: &str, value: &str) -> Option<LeafRef> {
+    let (path, leaf_id) = value.split_once('#')?;
+    let resolved = resolve(base, path)?;
+    let sequence = resolved.split('/').next()?.to_string();
+    Some(LeafRef { sequence, leaf_id: leaf_id.to_string() })
+}
+
+/// Resolves a '/'-separated `href` against `base` (relative to the application
+/// folder). None when it would climb out of the application folder.
+pub(crate) fn resolve(base: &str, href: &str) -> Option<String> {
+    let mut parts: Vec<&str> = base.split('/').filter(|p| !p.is_empty()).collect();
+    for part in href.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            p => parts.push(p),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+fn join(a: &str, b: &str) -> String {
+    if b.is_empty() { a.to_string() } else { format!("{}/{}", a, b) }
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+    <ectd:ectd xmlns:ectd="http://www.ich.org/ectd" xmlns:xlink="http://www.w3c.org/1999/xlink" dtd-version="3.2">
+        <m1-administrative-information-and-prescribing-information>
+            <leaf ID="regional" operation="new" xlink:href="m1/us/us-regional.xml" checksum="abc" checksum-type="md5">
+                <title>US Regional</title>
+            </leaf>
+        </m1-administrative-information-and-prescribing-information>
+        <m2-common-technical-document-summaries>
+            <m2-3-quality-overall-summary>
+                <leaf ID="qos-v2" operation="replace" xlink:href="m2/23-qos/qos.pdf"
+                      modified-file="../0000/index.xml#qos" checksum="def" checksum-type="md5">
+                    <title>Quality Overall Summary &amp; Addendum</title>
+                </leaf>
+            </m2-3-quality-overall-summary>
+        </m2-common-technical-document-summaries>
+        <m5-clinical-study-reports>
+            <m5-3-clinical-study-reports>
+                <node-extension>
+                    <title>Study 101</title>
+                    <leaf ID="csr-101" operation="new" xlink:href="m5/53-clin-stud-rep/csr-101.pdf" checksum="123" checksum-type="md5">
+                        <title>CSR 101</title>
+                    </leaf>
+                </node-extension>
+                <leaf ID="old-csr" operation="delete" modified-file="../0000/index.xml#csr-100"/>
+            </m5-3-clinical-study-reports>
+        </m5-clinical-study-reports>
+    </ectd:ectd>"#;
+
+    #[test]
+    fn test_parse_leaves_and_lifecycle() {
+        let leaves = parse_leaves(INDEX_XML, "0001", "").unwrap();
+        assert_eq!(leaves.len(), 4);
+
+        let qos = &leaves[1];
+        assert_eq!(qos.heading, "m2-3-quality-overall-summary");
+        assert_eq!(qos.title, "Quality Overall Summary & Addendum");
+        assert_eq!(qos.operation, LeafOperation::Replace);
+        assert_eq!(qos.href.as_deref(), Some("m2/23-qos/qos.pdf"));
+        assert_eq!(qos.file.as_deref(), Some("0001/m2/23-qos/qos.pdf"));
+        assert_eq!(qos.modified_file, Some(LeafRef { sequence: "0000".into(), leaf_id: "qos".into() }));
+
+        let csr = &leaves[2];
+        assert_eq!(csr.heading, "m5-3-clinical-study-reports");
+        assert_eq!(csr.node_extensions, vec!["Study 101".to_string()]);
+
+        let deleted = &leaves[3];
+        assert_eq!(deleted.operation, LeafOperation::Delete);
+        assert!(deleted.href.is_none());
+        assert_eq!(deleted.modified_file.as_ref().unwrap().leaf_id, "csr-100");
+    }
+
+    #[test]
+    fn test_regional_hrefs_resolve_from_the_regional_folder() {
+        let xml = r#"<fda-regional:fda-regional xmlns:fda-regional="http://www.ich.org/fda" xmlns:xlink="http://www.w3c.org/1999/xlink">
+            <admin>
+                <applicant-info><company-name>Acme Pharmaceuticals</company-name></applicant-info>
+                <application-set><application>
+                    <application-information>
+                        <application-number application-type="nda">123456</application-number>
+                    </application-information>
+                    <submission-information>
+                        <submission-id submission-type="amendment">0000</submission-id>
+                        <sequence-number>0002</sequence-number>
+                    </submission-information>
+                </application></application-set>
+            </admin>
+            <m1-regional>
+                <m1-2-cover-letters>
+                    <leaf ID="cover" operation="replace" xlink:href="cover.pdf"
+                          modified-file="../../../0001/m1/us/us-regional.xml#cover"><title>Cover</title></leaf>
+                </m1-2-cover-letters>
+            </m1-regional>
+        </fda-regional:fda-regional>"#;
+
+        let header = parse_regional_header(xml).unwrap();
+        assert_eq!(header.applicant.as_deref(), Some("Acme Pharmaceuticals"));
+        assert_eq!(header.application_number.as_deref(), Some("123456"));
+        assert_eq!(header.application_type.as_deref(), Some("nda"));
+        assert_eq!(header.submission_type.as_deref(), Some("amendment"));
+        assert_eq!(header.sequence_number.as_deref(), Some("0002"));
+
+        let leaves = parse_leaves(xml, "0002", "m1/us").unwrap();
+        assert_eq!(leaves[0].heading, "m1-2-cover-letters");
+        assert_eq!(leaves[0].href.as_deref(), Some("m1/us/cover.pdf"));
+        assert_eq!(leaves[0].modified_file.as_ref().unwrap().sequence, "0001");
+    }
+
+    #[test]
+    fn test_resolve_stays_inside_the_application() {
+        assert_eq!(resolve("0001/m1/us", "../../m2/a.pdf").as_deref(), Some("0001/m2/a.pdf"));
+        assert_eq!(resolve("0001", "../0000/index.xml").as_deref(), Some("0000/index.xml"));
+        assert_eq!(resolve("0001", "../../etc/passwd"), None);
+    }
+}
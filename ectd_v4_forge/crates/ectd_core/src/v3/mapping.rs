@@ -0,0 +1,80 @@
+// ---------------------------------------------------------------------------
+// v3.2.2 heading -> v4.0 Context of Use
+// Follows the ICH M8 transition mapping at the granularity of the context
+// codes this crate uses (see `resolve_folder_path`). Headings are matched by
+// prefix, most specific first, so "m1-14-1-draft-labeling" lands on labeling.
+// ---------------------------------------------------------------------------
+
+const HEADING_MAP: &[(&str, &str)] = &[
+    // Module 1: Administrative (US regional)
+    ("m1-1", "form-356h"),
+    ("m1-2", "cover-letter"),
+    ("m1-14", "product-labeling"),
+
+    // Module 2: Summaries
+    ("m2-3", "quality-overall-summary"),
+    ("m2-4", "nonclinical-overview"),
+    ("m2-5", "clinical-overview"),
+
+    // Module 3: Quality
+    ("m3-2-s", "drug-substance"),
+    ("m3-2-p", "drug-product"),
+    ("m3-2-r", "regional-information"),
+
+    // Module 4: Nonclinical
+    ("m4-2-1", "pharmacology"),
+    ("m4-2-3", "toxicology"),
+    ("m4-2", "nonclinical-study-report"),
+
+    // Module 5: Clinical
+    ("m5-3-7", "case-report-forms"),
+    ("m5-3", "clinical-study-report"),
+];
+
+/// The v4.0 context code for a leaf, or None when its heading has no mapping
+/// (the importer then keeps the v3 heading name as the code and reports it).
+pub fn context_code_for(heading: &str, href: &str) -> Option<&'static str> {
+    // Datasets sit under study headings in v3 but have their own contexts in v4
+    if heading.starts_with("m5-") {
+        let file = href.rsplit('/').next().unwrap_or(href).to_lowercase();
+        if file == "define.xml" {
+            return Some("data-definition");
+        }
+        if file.ends_with(".xpt") {
+            let analysis = href.to_lowercase().split('/').any(|p| p == "analysis" || p == "adam");
+            return Some(if analysis { "analysis-dataset" } else { "clinical-dataset" });
+        }
+    }
+
+    HEADING_MAP
+        .iter()
+        .find(|(prefix, _)| {
+            heading == *prefix
+                || heading.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('-'))
+        })
+        .map(|(_, code)| *code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headings_map_by_most_specific_prefix() {
+        assert_eq!(context_code_for("m1-2-cover-letters", "m1/us/cover.pdf"), Some("cover-letter"));
+        assert_eq!(context_code_for("m1-14-1-draft-labeling", "m1/us/label.pdf"), Some("product-labeling"));
+        assert_eq!(context_code_for("m1-1-forms", "m1/us/356h.pdf"), Some("form-356h"));
+        assert_eq!(context_code_for("m4-2-3-2-repeat-dose-toxicity", "a.pdf"), Some("toxicology"));
+        assert_eq!(context_code_for("m4-2-2-pharmacokinetics", "a.pdf"), Some("nonclinical-study-report"));
+        assert_eq!(context_code_for("m5-3-7-case-report-forms", "crf.pdf"), Some("case-report-forms"));
+        assert_eq!(context_code_for("m1-3-administrative-information", "a.pdf"), None);
+    }
+
+    #[test]
+    fn test_datasets_map_by_file() {
+        let base = "m5-3-5-1-study-reports-of-controlled-clinical-studies";
+        assert_eq!(context_code_for(base, "m5/datasets/101/tabulations/sdtm/dm.xpt"), Some("clinical-dataset"));
+        assert_eq!(context_code_for(base, "m5/datasets/101/analysis/adam/adsl.xpt"), Some("analysis-dataset"));
+        assert_eq!(context_code_for(base, "m5/datasets/101/tabulations/sdtm/define.xml"), Some("data-definition"));
+    }
+}
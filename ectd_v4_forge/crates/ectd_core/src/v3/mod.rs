@@ -0,0 +1,83 @@
+//! Reader for legacy eCTD v3.2.2 applications, for migration to v4.0.
+
+pub mod backbone;
+pub mod mapping;
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+pub use backbone::{Leaf, LeafOperation, LeafRef, RegionalHeader};
+
+/// Backbone file at the root of every v3.2.2 sequence folder.
+pub const INDEX_FILE: &str = "index.xml";
+
+/// One v3.2.2 sequence: index.xml and the regional XML merged into one list of leaves.
+#[derive(Debug, Clone)]
+pub struct Sequence {
+    /// Folder name, e.g. "0003"
+    pub folder: String,
+    pub number: u32,
+    pub header: RegionalHeader,
+    /// In backbone order (index.xml first, the regional leaves in place of the regional XML)
+    pub leaves: Vec<Leaf>,
+}
+
+/// Reads every sequence folder (four digits) of an application, oldest first.
+pub fn read_application(app_dir: &Path) -> Result<Vec<Sequence>> {
+    let mut folders: Vec<String> = fs::read_dir(app_dir)
+        .context(format!("Failed to read application folder: {:?}", app_dir))?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().join(INDEX_FILE).is_file())
+        .filter_map(|e| e.file_name().to_str().map(str::to_string))
+        .filter(|name| name.len() == 4 && name.chars().all(|c| c.is_ascii_digit()))
+        .collect();
+    folders.sort();
+
+    if folders.is_empty() {
+        anyhow::bail!("No v3.2.2 sequence folders (NNNN/{}) found in {:?}", INDEX_FILE, app_dir);
+    }
+
+    folders.iter().map(|f| read_sequence(app_dir, f)).collect()
+}
+
+/// Reads `<app_dir>/<folder>/index.xml` and the regional XML it references.
+pub fn read_sequence(app_dir: &Path, folder: &str) -> Result<Sequence> {
+    let index_path = app_dir.join(folder).join(INDEX_FILE);
+    let index_xml = fs::read_to_string(&index_path)
+        .context(format!("Failed to read {:?}", index_path))?;
+
+    let mut header = RegionalHeader::default();
+    let mut leaves = Vec::new();
+
+    for leaf in backbone::parse_leaves(&index_xml, folder, "")? {
+        if !is_regional_backbone(&leaf) {
+            leaves.push(leaf);
+            continue;
+        }
+
+        // Module 1 lives in the regional XML; its hrefs are relative to its own folder
+        let href = leaf.href.clone().unwrap_or_default();
+        let regional_path = app_dir.join(folder).join(&href);
+        let regional_xml = fs::read_to_string(&regional_path)
+            .context(format!("Failed to read regional backbone {:?}", regional_path))?;
+        let regional_dir = href.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+        header = backbone::parse_regional_header(&regional_xml)?;
+        leaves.extend(backbone::parse_leaves(&regional_xml, folder, regional_dir)?);
+    }
+
+    Ok(Sequence {
+        folder: folder.to_string(),
+        number: folder.parse().context(format!("Invalid sequence folder: {}", folder))?,
+        header,
+        leaves,
+    })
+}
+
+/// The Module 1 leaf in index.xml that points at the regional backbone
+/// (e.g. m1/us/us-regional.xml) rather than at a document.
+fn is_regional_backbone(leaf: &Leaf) -> bool {
+    leaf.heading.starts_with("m1-")
+        && leaf.href.as_deref().is_some_and(|h| h.to_lowercase().ends_with("regional.xml"))
+}
@@ -1,11 +1,12 @@
 use crate::models::submission_unit::SubmissionUnit;
 use serde::Serialize;
+use utoipa::ToSchema;
 
 pub mod rules;
 pub mod rules_pdf;
 
 // The structure of a failure
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct ValidationError {
     pub code: String,      // e.g., "eCTD4-013"
     pub severity: String,  // "High Error", "Warning"
@@ -103,4 +103,168 @@ impl ValidationRule for RuleEctd4_048 {
     }
 }
 
+// =========================================================================
+// RULE: KW-001
+// "Every keyword on a Context of Use resolves to an active definition"
+// Source: PDF Section 4.2.8 / 4.2.14
+// Definitions live in the sequence that introduced them, so the definitions
+// of earlier sequences of the application are passed in; this unit's own
+// definitions take precedence over them.
+// =========================================================================
+#[derive(Default)]
+pub struct RuleKeywordResolution {
+    /// (code system, code) of the keywords active after the earlier sequences
+    pub inherited: Vec<(String, String)>,
+}
+
+impl ValidationRule for RuleKeywordResolution {
+    fn rule_id(&self) -> &str { "KW-001" }
+
+    fn check(&self, unit: &SubmissionUnit) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let definitions = unit.keyword_definitions.as_deref().unwrap_or_default();
+
+        for cou in &unit.context_of_use {
+            for keyword in &cou.keywords {
+                let own = definitions.iter()
+                    .find(|d| d.code == keyword.code && d.code_system == keyword.code_system);
+                let problem = match own {
+                    Some(def) if def.is_active() => None,
+                    Some(_) => Some("is retired in this sequence"),
+                    None if self.inherited.iter().any(|(cs, c)| *cs == keyword.code_system && *c == keyword.code) => None,
+                    None => Some("has no active definition"),
+                };
+                if let Some(problem) = problem {
+                    errors.push(ValidationError {
+                        code: self.rule_id().to_string(),
+                        severity: "High Error".to_string(),
+                        message: format!(
+                            "Keyword '{}' ({}) on context of use '{}' {}",
+                            keyword.code, keyword.code_system, cou.code, problem
+                        ),
+                        target_id: Some(cou.id.clone()),
+                    });
+                }
+            }
+        }
+        errors
+    }
+}
+
+// =========================================================================
+// RULE: PRI-001
+// "Priority numbers of a context code are unique and consecutive"
+// Source: PDF Section 4.2.5 (priorityNumber)
+// Priorities order the contexts of a code in the current view, which
+// includes the contexts of earlier sequences still in force, so those are
+// passed in. Only codes this unit has active contexts for are checked.
+// =========================================================================
+#[derive(Default)]
+pub struct RulePriorityOrder {
+    /// (code, priority) of the earlier sequences' contexts in the current view
+    pub inherited: Vec<(String, u32)>,
+}
+
+impl ValidationRule for RulePriorityOrder {
+    fn rule_id(&self) -> &str { "PRI-001" }
+
+    fn check(&self, unit: &SubmissionUnit) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let own: Vec<_> = unit.context_of_use.iter()
+            .filter(|c| c.status_code == "active")
+            .collect();
+
+        let mut codes: Vec<&str> = own.iter().map(|c| c.code.as_str()).collect();
+        codes.sort();
+        codes.dedup();
+
+        for code in codes {
+            let siblings: Vec<_> = own.iter().filter(|c| c.code == code).collect();
+            let mut priorities: Vec<u32> = self.inherited.iter()
+                .filter(|(c, _)| c == code)
+                .map(|(_, p)| *p)
+                .chain(siblings.iter().map(|c| c.priority_number.value))
+                .collect();
+            priorities.sort();
+
+            for cou in &siblings {
+                let priority = cou.priority_number.value;
+                let taken = priorities.iter().filter(|p| **p == priority).count();
+                if taken > 1 {
+                    errors.push(ValidationError {
+                        code: self.rule_id().to_string(),
+                        severity: "Medium Error".to_string(),
+                        message: format!(
+                            "Priority {} of context of use '{}' is used by {} contexts in the current view",
+                            priority, code, taken
+                        ),
+                        target_id: Some(cou.id.clone()),
+                    });
+                }
+            }
+
+            priorities.dedup();
+            let missing: Vec<String> = (1..=priorities.last().copied().unwrap_or(0))
+                .filter(|p| !priorities.contains(p))
+                .map(|p| p.to_string())
+                .collect();
+            if !missing.is_empty() {
+                errors.push(ValidationError {
+                    code: self.rule_id().to_string(),
+                    severity: "Low Error".to_string(),
+                    message: format!(
+                        "Priorities of context of use '{}' skip {} in the current view",
+                        code,
+                        missing.join(", ")
+                    ),
+                    target_id: Some(siblings[0].id.clone()),
+                });
+            }
+        }
+        errors
+    }
+}
+
+// =========================================================================
+// RULE: DOC-001
+// "Every document reference resolves to a document of the application"
+// Source: PDF Section 4.2.5 (documentReference) / 4.2.13
+// A context may reference a document this unit sends or one submitted in an
+// earlier sequence of the same application, so the ids of the latter are
+// passed in. Contexts without a reference are not checked here.
+// =========================================================================
+#[derive(Default)]
+pub struct RuleDocumentReference {
+    /// Ids of the documents of the application's earlier sequences
+    pub inherited: Vec<String>,
+}
+
+impl ValidationRule for RuleDocumentReference {
+    fn rule_id(&self) -> &str { "DOC-001" }
+
+    fn check(&self, unit: &SubmissionUnit) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        for cou in &unit.context_of_use {
+            let Some(reference) = &cou.document_reference else { continue };
+            let id = reference.id.root.as_str();
+            let resolved = unit.documents.iter().any(|d| d.id.eq_ignore_ascii_case(id))
+                || self.inherited.iter().any(|d| d.eq_ignore_ascii_case(id));
+            if !resolved {
+                errors.push(ValidationError {
+                    code: self.rule_id().to_string(),
+                    severity: "High Error".to_string(),
+                    message: format!(
+                        "Context of use '{}' references document {}, which is neither in this sequence \
+                         nor in an earlier sequence of application {}",
+                        cou.code, id, unit.application.application_number.code
+                    ),
+                    target_id: Some(cou.id.clone()),
+                });
+            }
+        }
+        errors
+    }
+}
+
 // TODO: Implement remaining rules from eCTD v4.0 Specification (Section 4.2)
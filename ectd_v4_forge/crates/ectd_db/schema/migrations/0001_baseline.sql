@@ -1,6 +1,9 @@
 -- =====================================================================
--- eCTD Forge Schema - Build Order
+-- eCTD Forge Schema - Migration 0001: Baseline
 -- =====================================================================
+-- The original table layout, assembled from the building blocks below.
+-- Every statement is idempotent so databases created by the legacy
+-- `rebuild` command can adopt the migration ledger without a reset.
 
 -- Phase 0: Foundation
 -- @include foundation/01_extensions.sql
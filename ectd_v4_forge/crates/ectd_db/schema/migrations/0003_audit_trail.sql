@@ -0,0 +1,38 @@
+-- =====================================================================
+-- Migration 0003: Audit Trail (21 CFR Part 11)
+-- =====================================================================
+-- Append-only, hash-chained record of every mutating service call.
+-- Each row's record_hash covers its content plus the previous row's hash,
+-- so any edit or deletion breaks the chain from that point onwards.
+
+CREATE TABLE audit_log (
+    seq BIGSERIAL PRIMARY KEY,
+    id UUID NOT NULL UNIQUE,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    actor VARCHAR(255) NOT NULL,
+    action VARCHAR(64) NOT NULL,
+    entity_type VARCHAR(64) NOT NULL,
+    entity_id UUID,
+    before_state JSONB,
+    after_state JSONB,
+    reason TEXT,
+    prev_hash CHAR(64) NOT NULL,
+    record_hash CHAR(64) NOT NULL UNIQUE
+);
+
+CREATE INDEX idx_audit_log_entity ON audit_log(entity_type, entity_id);
+
+-- Immutability: the database itself refuses UPDATE, DELETE and TRUNCATE.
+CREATE FUNCTION audit_log_reject_mutation() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only (% rejected)', TG_OP;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_no_update
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_reject_mutation();
+
+CREATE TRIGGER audit_log_no_truncate
+    BEFORE TRUNCATE ON audit_log
+    FOR EACH STATEMENT EXECUTE FUNCTION audit_log_reject_mutation();
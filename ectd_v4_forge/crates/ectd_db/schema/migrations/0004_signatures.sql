@@ -0,0 +1,28 @@
+-- =====================================================================
+-- Migration 0004: Electronic Signatures
+-- =====================================================================
+-- A signature binds a signer and a meaning to the content digest of a
+-- submission unit (XML + document checksums) at the moment of signing.
+-- It is never updated: a later change simply stops matching the digest.
+
+CREATE TABLE submission_signatures (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    submission_unit_id UUID NOT NULL REFERENCES submission_units(id) ON DELETE CASCADE,
+    signer VARCHAR(255) NOT NULL,
+    meaning VARCHAR(16) NOT NULL CHECK (meaning IN ('author', 'review', 'approve')),
+    content_digest CHAR(64) NOT NULL,
+    comment TEXT,
+    signed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_signatures_unit ON submission_signatures(submission_unit_id);
+
+CREATE FUNCTION reject_row_mutation() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION '% is immutable (% rejected)', TG_TABLE_NAME, TG_OP;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER submission_signatures_no_update
+    BEFORE UPDATE ON submission_signatures
+    FOR EACH ROW EXECUTE FUNCTION reject_row_mutation();
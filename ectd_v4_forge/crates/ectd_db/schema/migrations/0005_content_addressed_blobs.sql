@@ -0,0 +1,36 @@
+-- =====================================================================
+-- Migration 0005: Content-Addressed Document Storage
+-- =====================================================================
+-- Document bytes are stored once per distinct SHA-256 and keyed by it.
+-- Many documents (across sequences and applications) may share one blob.
+-- Documents created before this migration keep blob_sha256 = NULL and
+-- remain stored under their own UUID.
+
+CREATE TABLE blobs (
+    sha256 CHAR(64) PRIMARY KEY,
+    size_bytes BIGINT NOT NULL CHECK (size_bytes >= 0),
+    ref_count INTEGER NOT NULL DEFAULT 0 CHECK (ref_count >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE documents ADD COLUMN blob_sha256 CHAR(64) REFERENCES blobs(sha256);
+
+CREATE INDEX idx_documents_blob ON documents(blob_sha256);
+
+-- Reference counts follow the documents table whichever code path writes it
+-- (attach, ingest, cascading deletes). A blob at zero is garbage.
+CREATE FUNCTION blobs_track_refs() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.blob_sha256 IS NOT NULL THEN
+        UPDATE blobs SET ref_count = ref_count + 1 WHERE sha256 = NEW.blob_sha256;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.blob_sha256 IS NOT NULL THEN
+        UPDATE blobs SET ref_count = ref_count - 1 WHERE sha256 = OLD.blob_sha256;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER documents_blob_refs
+    AFTER INSERT OR DELETE OR UPDATE OF blob_sha256 ON documents
+    FOR EACH ROW EXECUTE FUNCTION blobs_track_refs();
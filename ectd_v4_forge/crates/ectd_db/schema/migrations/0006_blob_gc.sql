@@ -0,0 +1,11 @@
+-- =====================================================================
+-- Migration 0006: Blob Garbage Collection
+-- =====================================================================
+-- touched_at moves every time a blob is (re-)registered, including when an
+-- upload is deduplicated against it. Garbage collection only removes blobs
+-- that are unreferenced AND untouched for a grace period, so an attach that
+-- is between "blob registered" and "document inserted" is never swept.
+
+ALTER TABLE blobs ADD COLUMN touched_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE INDEX idx_blobs_unreferenced ON blobs(touched_at) WHERE ref_count = 0;
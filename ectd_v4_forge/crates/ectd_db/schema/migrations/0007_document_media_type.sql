@@ -0,0 +1,13 @@
+-- =====================================================================
+-- Migration 0007: Detected Document Media Types
+-- =====================================================================
+-- media_type is now detected from file content at attach/ingest time.
+-- Widen it for OOXML types and drop the silent PDF default so a missing
+-- value is an error rather than a wrong answer.
+
+ALTER TABLE documents ALTER COLUMN media_type TYPE VARCHAR(255);
+
+UPDATE documents SET media_type = 'application/pdf' WHERE media_type IS NULL;
+
+ALTER TABLE documents ALTER COLUMN media_type DROP DEFAULT;
+ALTER TABLE documents ALTER COLUMN media_type SET NOT NULL;
@@ -0,0 +1,25 @@
+-- =====================================================================
+-- Migration 0008: Background Jobs
+-- =====================================================================
+-- Exports and full-package validation run in the background. Each run is a
+-- row here, so its status and outcome survive the request that started it
+-- (and the API process). `progress` holds the latest progress event.
+
+CREATE TABLE jobs (
+    id UUID PRIMARY KEY,
+    kind VARCHAR(32) NOT NULL CHECK (kind IN ('export', 'validation')),
+    submission_unit_id UUID NOT NULL REFERENCES submission_units(id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'succeeded', 'failed')),
+    requested_by VARCHAR(255) NOT NULL,
+    parameters JSONB NOT NULL DEFAULT '{}',
+    progress JSONB,
+    result JSONB,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_jobs_unit ON jobs(submission_unit_id, created_at DESC);
+CREATE INDEX idx_jobs_unfinished ON jobs(status) WHERE status IN ('queued', 'running');
@@ -0,0 +1,15 @@
+-- =====================================================================
+-- Migration 0009: Listing and Pagination
+-- =====================================================================
+-- Units, documents and applications are listed with keyset (cursor)
+-- pagination. created_at is a sort key, so it can no longer be NULL, and
+-- the sort orders and the context-of-use filter get supporting indexes.
+
+UPDATE submission_units SET created_at = NOW() WHERE created_at IS NULL;
+ALTER TABLE submission_units ALTER COLUMN created_at SET NOT NULL;
+
+CREATE INDEX idx_units_application_sequence ON submission_units(application_number, sequence_number, id);
+CREATE INDEX idx_units_created ON submission_units(created_at, id);
+CREATE INDEX idx_documents_unit ON documents(submission_unit_id, xlink_href, id);
+CREATE INDEX idx_contexts_unit_code ON contexts_of_use(submission_unit_id, code);
+CREATE INDEX idx_contexts_document ON contexts_of_use(document_reference_id);
@@ -0,0 +1,73 @@
+-- =====================================================================
+-- Migration 0010: Full-Text Document Search
+-- =====================================================================
+-- Text is extracted from PDFs at attach/ingest time and stored once per
+-- distinct SHA-256, like the blobs themselves. Each document carries a
+-- weighted search vector: title (A), the unit's keyword names (B) and the
+-- extracted text (C). Triggers keep it current whichever table changes.
+
+CREATE TABLE document_texts (
+    sha256 CHAR(64) PRIMARY KEY,
+    content TEXT NOT NULL,
+    page_count INTEGER NOT NULL CHECK (page_count >= 0),
+    truncated BOOLEAN NOT NULL DEFAULT FALSE,
+    extracted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE documents ADD COLUMN search_vector TSVECTOR;
+
+CREATE INDEX idx_documents_search ON documents USING GIN (search_vector);
+CREATE INDEX idx_documents_checksum ON documents (LOWER(checksum));
+
+CREATE FUNCTION document_search_vector(p_title TEXT, p_unit_id UUID, p_checksum TEXT)
+RETURNS TSVECTOR AS $$
+    SELECT setweight(to_tsvector('english', COALESCE(p_title, '')), 'A')
+        || setweight(to_tsvector('english', COALESCE(
+               (SELECT string_agg(display_name, ' ') FROM keyword_definitions
+                WHERE submission_unit_id = p_unit_id), '')), 'B')
+        || setweight(to_tsvector('english', COALESCE(
+               (SELECT content FROM document_texts WHERE sha256 = LOWER(p_checksum)), '')), 'C');
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION documents_set_search_vector() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector := document_search_vector(NEW.title, NEW.submission_unit_id, NEW.checksum);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER documents_search_vector
+    BEFORE INSERT OR UPDATE OF title, checksum, submission_unit_id ON documents
+    FOR EACH ROW EXECUTE FUNCTION documents_set_search_vector();
+
+CREATE FUNCTION document_texts_refresh_search() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE documents
+    SET search_vector = document_search_vector(title, submission_unit_id, checksum)
+    WHERE LOWER(checksum) = NEW.sha256;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER document_texts_search
+    AFTER INSERT OR UPDATE ON document_texts
+    FOR EACH ROW EXECUTE FUNCTION document_texts_refresh_search();
+
+CREATE FUNCTION keyword_definitions_refresh_search() RETURNS TRIGGER AS $$
+DECLARE
+    unit_id UUID := CASE WHEN TG_OP = 'DELETE' THEN OLD.submission_unit_id ELSE NEW.submission_unit_id END;
+BEGIN
+    UPDATE documents
+    SET search_vector = document_search_vector(title, submission_unit_id, checksum)
+    WHERE submission_unit_id = unit_id;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER keyword_definitions_search
+    AFTER INSERT OR UPDATE OR DELETE ON keyword_definitions
+    FOR EACH ROW EXECUTE FUNCTION keyword_definitions_refresh_search();
+
+-- Existing documents get their title and keywords now; their text is
+-- extracted by `ectd_cli search --reindex`.
+UPDATE documents SET search_vector = document_search_vector(title, submission_unit_id, checksum);
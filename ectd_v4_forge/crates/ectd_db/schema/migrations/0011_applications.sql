@@ -0,0 +1,71 @@
+-- =====================================================================
+-- Migration 0011: Applications
+-- =====================================================================
+-- Application number, type and applicant used to be copied onto every
+-- submission unit (with placeholder defaults). They now live once per
+-- application; units reference it, and a sequence number can only be used
+-- once per application. The application id is what every exported unit of
+-- the application carries, so it is taken from the earliest sequence.
+
+CREATE TABLE applications (
+    id UUID PRIMARY KEY,
+    application_number VARCHAR(64) NOT NULL UNIQUE,
+    application_type VARCHAR(64) NOT NULL,
+    region VARCHAR(16) NOT NULL DEFAULT 'us',
+    -- The sponsoring organization named in every unit of the application
+    applicant_name VARCHAR(255) NOT NULL,
+    product_name VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Other applications this one refers to (e.g. a DMF, or the IND behind an
+-- NDA). They need not be managed here, so they are kept by number.
+CREATE TABLE related_applications (
+    application_id UUID NOT NULL REFERENCES applications(id) ON DELETE CASCADE,
+    related_number VARCHAR(64) NOT NULL,
+    relationship VARCHAR(64) NOT NULL,
+    PRIMARY KEY (application_id, related_number)
+);
+
+DO $$
+DECLARE
+    duplicates TEXT;
+BEGIN
+    SELECT string_agg(application_number || ' sequence ' || sequence_number, ', ')
+    INTO duplicates
+    FROM (SELECT application_number, sequence_number FROM submission_units
+          GROUP BY application_number, sequence_number HAVING COUNT(*) > 1) d;
+    IF duplicates IS NOT NULL THEN
+        RAISE EXCEPTION 'Duplicate sequence numbers must be resolved before this migration: %', duplicates;
+    END IF;
+END $$;
+
+INSERT INTO applications (id, application_number, application_type, applicant_name, created_at)
+SELECT DISTINCT ON (application_number)
+       application_id_uuid, application_number, application_code, applicant_name, created_at
+FROM submission_units
+ORDER BY application_number, sequence_number, created_at;
+
+-- Type and applicant as of the latest sequence
+UPDATE applications a
+SET application_type = latest.application_code, applicant_name = latest.applicant_name
+FROM (SELECT DISTINCT ON (application_number) application_number, application_code, applicant_name
+      FROM submission_units
+      ORDER BY application_number, sequence_number DESC) latest
+WHERE latest.application_number = a.application_number;
+
+ALTER TABLE submission_units ADD COLUMN application_id UUID REFERENCES applications(id);
+
+UPDATE submission_units u SET application_id = a.id
+FROM applications a
+WHERE a.application_number = u.application_number;
+
+ALTER TABLE submission_units ALTER COLUMN application_id SET NOT NULL;
+ALTER TABLE submission_units
+    ADD CONSTRAINT unique_sequence_per_application UNIQUE (application_id, sequence_number);
+
+ALTER TABLE submission_units
+    DROP COLUMN application_id_uuid,
+    DROP COLUMN application_code,
+    DROP COLUMN application_number,
+    DROP COLUMN applicant_name;
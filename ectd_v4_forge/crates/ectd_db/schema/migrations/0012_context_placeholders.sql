@@ -0,0 +1,26 @@
+-- =====================================================================
+-- Migration 0012: Planned Contexts of Use
+-- =====================================================================
+-- A sequence is planned before its documents exist: each placeholder is a
+-- context of use the unit is expected to contain, with who delivers it and
+-- by when. Attaching a document with the same code fulfils it. Placeholders
+-- are kept apart from contexts_of_use so they never reach the backbone.
+
+CREATE TABLE context_placeholders (
+    id UUID PRIMARY KEY,
+    submission_unit_id UUID NOT NULL REFERENCES submission_units(id) ON DELETE CASCADE,
+    code VARCHAR(64) NOT NULL,
+    -- Expected document title, for whoever writes it
+    title VARCHAR(512),
+    owner VARCHAR(255),
+    due_date DATE,
+    note TEXT,
+    -- Set when a document is attached for it; NULL while the placeholder is open
+    context_of_use_id UUID UNIQUE REFERENCES contexts_of_use(id) ON DELETE SET NULL,
+    fulfilled_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_context_placeholders_open
+    ON context_placeholders (submission_unit_id, code)
+    WHERE context_of_use_id IS NULL;
@@ -0,0 +1,66 @@
+-- =====================================================================
+-- Migration 0013: Submission Templates
+-- =====================================================================
+-- A template describes a kind of sequence: its unit code, the contexts of
+-- use it must (or may) contain with their priorities and folders, and the
+-- keywords it starts with. Creating a unit from a template plans those
+-- contexts as placeholders, so placeholders learn whether they are
+-- required, their priority and their folder.
+
+CREATE TABLE submission_templates (
+    name VARCHAR(64) PRIMARY KEY,
+    description TEXT,
+    -- unitCode, applicationType, contexts[], keywords[] (see ectd_db::template)
+    definition JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE context_placeholders
+    -- Optional placeholders don't hold up an export
+    ADD COLUMN required BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN priority INTEGER CHECK (priority > 0),
+    -- Package folder for the document, instead of the default for its code
+    ADD COLUMN folder TEXT;
+
+INSERT INTO submission_templates (name, description, definition) VALUES
+('nda-original', 'NDA original application', '{
+    "applicationType": "nda",
+    "unitCode": "original-application",
+    "contexts": [
+        {"code": "cover-letter", "title": "Cover Letter", "priority": 1},
+        {"code": "form-356h", "title": "Form FDA 356h", "priority": 1},
+        {"code": "product-labeling", "title": "Draft Labeling Text", "priority": 1},
+        {"code": "quality-overall-summary", "title": "Quality Overall Summary", "priority": 1},
+        {"code": "nonclinical-overview", "title": "Nonclinical Overview", "priority": 1},
+        {"code": "clinical-overview", "title": "Clinical Overview", "priority": 1},
+        {"code": "drug-substance", "title": "Drug Substance", "priority": 1},
+        {"code": "drug-product", "title": "Drug Product", "priority": 1},
+        {"code": "nonclinical-study-report", "priority": 1, "required": false},
+        {"code": "clinical-study-report", "priority": 1},
+        {"code": "data-definition", "priority": 1, "required": false}
+    ],
+    "keywords": []
+}'),
+('ind-annual-report', 'IND annual report', '{
+    "applicationType": "ind",
+    "unitCode": "annual-report",
+    "contexts": [
+        {"code": "cover-letter", "title": "Cover Letter", "priority": 1},
+        {"code": "form-1571", "title": "Form FDA 1571", "priority": 1},
+        {"code": "annual-report", "title": "Annual Report", "priority": 1, "folder": "m1/us/annual-report"},
+        {"code": "clinical-study-report", "priority": 1, "required": false}
+    ],
+    "keywords": []
+}'),
+('labeling-supplement', 'Labeling supplement to an NDA or BLA', '{
+    "unitCode": "labeling-supplement",
+    "contexts": [
+        {"code": "cover-letter", "title": "Cover Letter", "priority": 1},
+        {"code": "form-356h", "title": "Form FDA 356h", "priority": 1},
+        {"code": "product-labeling", "title": "Clean Labeling", "priority": 1},
+        {"code": "product-labeling", "title": "Annotated Labeling", "priority": 2},
+        {"code": "product-labeling", "title": "Labeling History", "priority": 3, "required": false}
+    ],
+    "keywords": []
+}');
@@ -0,0 +1,27 @@
+-- =====================================================================
+-- Migration 0014: Keywords on Contexts of Use
+-- =====================================================================
+-- A keyword definition is submitted once, in the sequence that introduces
+-- it, and stays in force for the later sequences of the application until
+-- one of them renames or retires it. Each keyword type (sponsor, study id,
+-- manufacturer, ...) is its own code system, so the same code may be
+-- defined under several types.
+
+ALTER TABLE keyword_definitions
+    ADD COLUMN status_code VARCHAR(16) NOT NULL DEFAULT 'active'
+        CHECK (status_code IN ('active', 'inactive'));
+
+ALTER TABLE keyword_definitions
+    DROP CONSTRAINT keyword_definitions_submission_unit_id_code_key,
+    ADD CONSTRAINT unique_keyword_per_unit UNIQUE (submission_unit_id, code_system, code);
+
+-- Keywords a context of use is tagged with; they must resolve to a definition
+-- of this unit or an earlier sequence (checked by the service and validation).
+CREATE TABLE context_keywords (
+    context_of_use_id UUID NOT NULL REFERENCES contexts_of_use(id) ON DELETE CASCADE,
+    code VARCHAR(64) NOT NULL,
+    code_system VARCHAR(128) NOT NULL,
+    PRIMARY KEY (context_of_use_id, code_system, code)
+);
+
+CREATE INDEX idx_context_keywords_keyword ON context_keywords(code_system, code);
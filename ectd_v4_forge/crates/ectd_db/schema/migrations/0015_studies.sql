@@ -0,0 +1,21 @@
+-- =====================================================================
+-- Migration 0015: Study Registry
+-- =====================================================================
+-- Module 4/5 content is grouped by study: every document of a study (protocol,
+-- report body, appendices, CRFs, datasets) is a context of use tagged with the
+-- study's "study-id" keyword. The registry holds what is known about each
+-- study; its documents are found through that keyword, in any sequence.
+
+CREATE TABLE studies (
+    id UUID PRIMARY KEY,
+    application_id UUID NOT NULL REFERENCES applications(id) ON DELETE CASCADE,
+    -- The study-id keyword code, e.g. "ABC-101"
+    study_id VARCHAR(64) NOT NULL,
+    title VARCHAR(512) NOT NULL,
+    -- e.g. {"pk", "efficacy"}
+    categories TEXT[] NOT NULL DEFAULT '{}',
+    -- Context of use code of the report, protocol and appendices
+    report_code VARCHAR(64) NOT NULL DEFAULT 'clinical-study-report',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT unique_study_per_application UNIQUE (application_id, study_id)
+);
@@ -0,0 +1,12 @@
+-- =====================================================================
+-- Migration 0016: Audit History per Submission Unit
+-- =====================================================================
+-- A unit's history holds the records about the unit itself (entity_id) and
+-- those about what belongs to it (documents, contexts of use, placeholders),
+-- whose before/after state names the unit as "submissionUnitId". Records are
+-- immutable, so the owning unit is found through an expression index rather
+-- than a column that existing rows could never be given.
+
+CREATE INDEX idx_audit_log_entity_id ON audit_log(entity_id);
+CREATE INDEX idx_audit_log_submission_unit
+    ON audit_log ((COALESCE(after_state, before_state) ->> 'submissionUnitId'));
@@ -0,0 +1,102 @@
+-- =====================================================================
+-- Migration 0017: Search by the Keywords a Document Is Tagged With
+-- =====================================================================
+-- Keywords tag contexts of use, and a definition lives only in the sequence
+-- that introduces it. A document's keyword weight (B) therefore comes from
+-- the keywords of the contexts of use that reference it, each resolved to
+-- the definition in force for that context's sequence (the latest of this
+-- or an earlier sequence; retired keywords drop out).
+
+DROP FUNCTION document_search_vector(TEXT, UUID, TEXT);
+
+CREATE FUNCTION document_search_vector(p_document_id UUID, p_title TEXT, p_checksum TEXT)
+RETURNS TSVECTOR AS $$
+    SELECT setweight(to_tsvector('english', COALESCE(p_title, '')), 'A')
+        || setweight(to_tsvector('english', COALESCE(
+               (SELECT string_agg(k.display_name, ' ')
+                FROM contexts_of_use c
+                JOIN submission_units cu ON cu.id = c.submission_unit_id
+                JOIN context_keywords ck ON ck.context_of_use_id = c.id
+                CROSS JOIN LATERAL (
+                    SELECT d.display_name, d.status_code
+                    FROM keyword_definitions d
+                    JOIN submission_units u ON u.id = d.submission_unit_id
+                    WHERE u.application_id = cu.application_id
+                      AND u.sequence_number <= cu.sequence_number
+                      AND d.code_system = ck.code_system AND d.code = ck.code
+                    ORDER BY u.sequence_number DESC
+                    LIMIT 1
+                ) k
+                WHERE c.document_reference_id = p_document_id AND k.status_code = 'active'), '')), 'B')
+        || setweight(to_tsvector('english', COALESCE(
+               (SELECT content FROM document_texts WHERE sha256 = LOWER(p_checksum)), '')), 'C');
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION documents_set_search_vector() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector := document_search_vector(NEW.id, NEW.title, NEW.checksum);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION document_texts_refresh_search() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE documents
+    SET search_vector = document_search_vector(id, title, checksum)
+    WHERE LOWER(checksum) = NEW.sha256;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- A definition is in force for its own sequence and the later ones, so a
+-- change reaches the documents their contexts of use reference.
+CREATE OR REPLACE FUNCTION keyword_definitions_refresh_search() RETURNS TRIGGER AS $$
+DECLARE
+    unit_id UUID := CASE WHEN TG_OP = 'DELETE' THEN OLD.submission_unit_id ELSE NEW.submission_unit_id END;
+BEGIN
+    UPDATE documents
+    SET search_vector = document_search_vector(id, title, checksum)
+    WHERE id IN (
+        SELECT c.document_reference_id
+        FROM submission_units this
+        JOIN submission_units u
+          ON u.application_id = this.application_id AND u.sequence_number >= this.sequence_number
+        JOIN contexts_of_use c ON c.submission_unit_id = u.id
+        WHERE this.id = unit_id
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION context_keywords_refresh_search() RETURNS TRIGGER AS $$
+DECLARE
+    cou_id UUID := CASE WHEN TG_OP = 'DELETE' THEN OLD.context_of_use_id ELSE NEW.context_of_use_id END;
+BEGIN
+    UPDATE documents
+    SET search_vector = document_search_vector(id, title, checksum)
+    WHERE id = (SELECT document_reference_id FROM contexts_of_use WHERE id = cou_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER context_keywords_search
+    AFTER INSERT OR DELETE ON context_keywords
+    FOR EACH ROW EXECUTE FUNCTION context_keywords_refresh_search();
+
+-- Keywords cascade away with their context of use, after it is gone; the
+-- document it referenced is refreshed here instead.
+CREATE FUNCTION contexts_of_use_refresh_search() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE documents
+    SET search_vector = document_search_vector(id, title, checksum)
+    WHERE id = OLD.document_reference_id
+       OR (TG_OP = 'UPDATE' AND id = NEW.document_reference_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER contexts_of_use_search
+    AFTER DELETE OR UPDATE OF document_reference_id ON contexts_of_use
+    FOR EACH ROW EXECUTE FUNCTION contexts_of_use_refresh_search();
+
+UPDATE documents SET search_vector = document_search_vector(id, title, checksum);
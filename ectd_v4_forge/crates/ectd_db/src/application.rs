@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// An application (NDA, IND, BLA, DMF, …) and the facts its sequences share.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationRecord {
+    /// The application id carried by every exported unit
+    pub id: Uuid,
+    pub application_number: String,
+    /// nda, ind, bla, anda, dmf, …
+    pub application_type: String,
+    /// Regulatory region, e.g. "us"
+    pub region: String,
+    /// Sponsoring organization named in every unit
+    pub applicant_name: String,
+    pub product_name: Option<String>,
+    pub related_applications: Vec<RelatedApplication>,
+    /// What the next new sequence will be numbered
+    pub next_sequence: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Another application this one refers to; it need not be managed here.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RelatedApplication {
+    pub application_number: String,
+    /// e.g. "cross-reference", "dmf", "ind"
+    pub relationship: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewApplication {
+    pub application_number: String,
+    pub application_type: String,
+    pub region: String,
+    pub applicant_name: String,
+    pub product_name: Option<String>,
+    pub related_applications: Vec<RelatedApplication>,
+}
+
+/// Editable application fields; `None` leaves a field as is.
+/// `related_applications` replaces the whole list when present.
+#[derive(Debug, Clone, Default)]
+pub struct ApplicationUpdate {
+    pub application_type: Option<String>,
+    pub region: Option<String>,
+    pub applicant_name: Option<String>,
+    pub product_name: Option<String>,
+    pub related_applications: Option<Vec<RelatedApplication>>,
+}
+
+#[derive(FromRow)]
+struct ApplicationRow {
+    id: Uuid,
+    application_number: String,
+    application_type: String,
+    region: String,
+    applicant_name: String,
+    product_name: Option<String>,
+    next_sequence: i32,
+    created_at: OffsetDateTime,
+}
+
+impl ApplicationRow {
+    fn with_related(self, related_applications: Vec<RelatedApplication>) -> ApplicationRecord {
+        ApplicationRecord {
+            id: self.id,
+            application_number: self.application_number,
+            application_type: self.application_type,
+            region: self.region,
+            applicant_name: self.applicant_name,
+            product_name: self.product_name,
+            related_applications,
+            next_sequence: self.next_sequence,
+            created_at: self.created_at,
+        }
+    }
+}
+
+pub struct ApplicationRepository {
+    pool: PgPool,
+}
+
+impl ApplicationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates the application unless one with this number exists, in the
+    /// caller's transaction. Returns None when it already existed (nothing is
+    /// changed then).
+    pub async fn create(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        app: &NewApplication,
+    ) -> Result<Option<ApplicationRecord>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO applications (id, application_number, application_type, region, applicant_name, product_name)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (application_number) DO NOTHING
+            RETURNING id
+            "#,
+            Uuid::new_v4(),
+            app.application_number,
+            app.application_type,
+            app.region,
+            app.applicant_name,
+            app.product_name
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let Some(id) = id else { return Ok(None) };
+        for related in &app.related_applications {
+            insert_related(tx, id, related).await?;
+        }
+
+        fetch(tx, &app.application_number).await
+    }
+
+    pub async fn get(&self, application_number: &str) -> Result<Option<ApplicationRecord>, sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        fetch(&mut conn, application_number).await
+    }
+
+    /// The application as changed, in the caller's transaction; None when no
+    /// application has this number.
+    pub async fn update(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        application_number: &str,
+        update: &ApplicationUpdate,
+    ) -> Result<Option<ApplicationRecord>, sqlx::Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            UPDATE applications SET
+                application_type = COALESCE($2, application_type),
+                region = COALESCE($3, region),
+                applicant_name = COALESCE($4, applicant_name),
+                product_name = COALESCE($5, product_name)
+            WHERE application_number = $1
+            RETURNING id
+            "#,
+            application_number,
+            update.application_type,
+            update.region,
+            update.applicant_name,
+            update.product_name
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let Some(id) = id else { return Ok(None) };
+        if let Some(related) = &update.related_applications {
+            sqlx::query!("DELETE FROM related_applications WHERE application_id = $1", id)
+                .execute(&mut **tx)
+                .await?;
+            for r in related {
+                insert_related(tx, id, r).await?;
+            }
+        }
+
+        fetch(tx, application_number).await
+    }
+}
+
+/// The application with its related applications, on `conn` (which may be
+/// a transaction that just changed it).
+async fn fetch(
+    conn: &mut sqlx::PgConnection,
+    application_number: &str,
+) -> Result<Option<ApplicationRecord>, sqlx::Error> {
+    let row = sqlx::query_as!(
+        ApplicationRow,
+        r#"
+        SELECT a.id, a.application_number, a.application_type, a.region, a.applicant_name,
+               a.product_name, a.created_at,
+               COALESCE((SELECT MAX(u.sequence_number) FROM submission_units u
+                         WHERE u.application_id = a.id), 0) + 1 AS "next_sequence!"
+        FROM applications a
+        WHERE a.application_number = $1
+        "#,
+        application_number
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some(row) = row else { return Ok(None) };
+    let related = sqlx::query_as!(
+        RelatedApplication,
+        r#"
+        SELECT related_number AS application_number, relationship
+        FROM related_applications
+        WHERE application_id = $1
+        ORDER BY related_number
+        "#,
+        row.id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(Some(row.with_related(related)))
+}
+
+async fn insert_related(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    application_id: Uuid,
+    related: &RelatedApplication,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO related_applications (application_id, related_number, relationship)
+        VALUES ($1, $2, $3)
+        "#,
+        application_id,
+        related.application_number,
+        related.relationship
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
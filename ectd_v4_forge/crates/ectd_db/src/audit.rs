@@ -0,0 +1,311 @@
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// `prev_hash` of the very first record in the chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Serializes appends so the chain never forks under concurrent writers.
+const AUDIT_LOCK_KEY: i64 = 0x6563_7464_00a1;
+
+/// What happened, by whom and why. Before/after are free-form JSON snapshots.
+#[derive(Debug, Clone)]
+pub struct NewAuditEntry {
+    pub actor: String,
+    pub action: String,      // "submission.create", "document.attach", "submission.export"
+    pub entity_type: String, // "submission_unit", "document"
+    pub entity_id: Option<Uuid>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub seq: i64,
+    pub id: Uuid,
+    #[serde(with = "time::serde::rfc3339")]
+    pub recorded_at: OffsetDateTime,
+    pub actor: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<Uuid>,
+    pub before_state: Option<Value>,
+    pub after_state: Option<Value>,
+    pub reason: Option<String>,
+    pub prev_hash: String,
+    pub record_hash: String,
+}
+
+impl AuditRecord {
+    /// Recomputes this record's hash from its content and `prev_hash`.
+    pub fn compute_hash(&self) -> String {
+        compute_record_hash(
+            &self.prev_hash,
+            self.id,
+            self.recorded_at,
+            &self.actor,
+            &self.action,
+            &self.entity_type,
+            self.entity_id,
+            self.before_state.as_ref(),
+            self.after_state.as_ref(),
+            self.reason.as_deref(),
+        )
+    }
+}
+
+/// Filters for reading the trail. All fields are optional and combined with AND.
+#[derive(Debug, Default, Clone)]
+pub struct AuditQuery {
+    pub entity_id: Option<Uuid>,
+    /// Records about the unit or about what belongs to it (its documents,
+    /// contexts of use, placeholders), found by the state's "submissionUnitId"
+    pub submission_unit_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Result of walking the whole chain from genesis.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainVerification {
+    pub records_checked: u64,
+    /// Sequence number of the first record whose hash or link doesn't match.
+    pub first_broken_seq: Option<i64>,
+}
+
+impl ChainVerification {
+    pub fn is_intact(&self) -> bool {
+        self.first_broken_seq.is_none()
+    }
+}
+
+pub struct AuditRepository {
+    pool: PgPool,
+}
+
+impl AuditRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Appends one record to the chain in a transaction of its own, for
+    /// events that change nothing else. The table itself rejects UPDATE/DELETE.
+    pub async fn append(&self, entry: NewAuditEntry) -> Result<AuditRecord, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let record = append(&mut tx, entry).await?;
+        tx.commit().await?;
+        Ok(record)
+    }
+
+    /// Reads the trail oldest-first.
+    pub async fn list(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>, sqlx::Error> {
+        sqlx::query_as!(
+            AuditRecord,
+            r#"
+            SELECT seq, id, recorded_at, actor, action, entity_type, entity_id,
+                   before_state, after_state, reason, prev_hash, record_hash
+            FROM audit_log
+            WHERE ($1::uuid IS NULL OR entity_id = $1)
+              AND ($2::text IS NULL OR actor = $2)
+              AND ($3::text IS NULL OR action = $3)
+              AND ($5::uuid IS NULL
+                   OR entity_id = $5
+                   OR COALESCE(after_state, before_state) ->> 'submissionUnitId' = $5::text)
+            ORDER BY seq ASC
+            LIMIT $4
+            "#,
+            query.entity_id,
+            query.actor,
+            query.action,
+            query.limit.unwrap_or(i64::MAX),
+            query.submission_unit_id
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Walks the entire chain, recomputing every hash and link.
+    pub async fn verify_chain(&self) -> Result<ChainVerification, sqlx::Error> {
+        let records = self.list(&AuditQuery::default()).await?;
+
+        let mut expected_prev = GENESIS_HASH.to_string();
+        let mut checked = 0;
+
+        for record in &records {
+            checked += 1;
+            if record.prev_hash != expected_prev || record.compute_hash() != record.record_hash {
+                return Ok(ChainVerification {
+                    records_checked: checked,
+                    first_broken_seq: Some(record.seq),
+                });
+            }
+            expected_prev = record.record_hash.clone();
+        }
+
+        Ok(ChainVerification { records_checked: checked, first_broken_seq: None })
+    }
+}
+
+/// Appends one record to the chain in the caller's transaction, so the record
+/// commits or rolls back with the change it describes. Appends are serialized
+/// until that transaction ends.
+pub async fn append(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    entry: NewAuditEntry,
+) -> Result<AuditRecord, sqlx::Error> {
+    sqlx::query!("SELECT pg_advisory_xact_lock($1)", AUDIT_LOCK_KEY)
+        .execute(&mut **tx)
+        .await?;
+
+    let prev_hash = sqlx::query_scalar!(
+        r#"SELECT record_hash FROM audit_log ORDER BY seq DESC LIMIT 1"#
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .unwrap_or_else(|| GENESIS_HASH.to_string());
+
+    // Postgres stores microseconds; truncate first so the hash survives a round trip.
+    let now = OffsetDateTime::now_utc();
+    let recorded_at = now
+        .replace_nanosecond(now.nanosecond() / 1_000 * 1_000)
+        .unwrap_or(now);
+    let id = Uuid::new_v4();
+
+    let record_hash = compute_record_hash(
+        &prev_hash,
+        id,
+        recorded_at,
+        &entry.actor,
+        &entry.action,
+        &entry.entity_type,
+        entry.entity_id,
+        entry.before.as_ref(),
+        entry.after.as_ref(),
+        entry.reason.as_deref(),
+    );
+
+    let record = sqlx::query_as!(
+        AuditRecord,
+        r#"
+        INSERT INTO audit_log
+        (id, recorded_at, actor, action, entity_type, entity_id,
+         before_state, after_state, reason, prev_hash, record_hash)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING seq, id, recorded_at, actor, action, entity_type, entity_id,
+                  before_state, after_state, reason, prev_hash, record_hash
+        "#,
+        id,
+        recorded_at,
+        entry.actor,
+        entry.action,
+        entry.entity_type,
+        entry.entity_id,
+        entry.before,
+        entry.after,
+        entry.reason,
+        prev_hash,
+        record_hash
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(record)
+}
+
+/// SHA-256 over a length-prefixed encoding of every field, chained to `prev_hash`.
+/// JSON is re-serialized from `serde_json::Value` (sorted keys), which is stable across
+/// the JSONB round trip.
+#[allow(clippy::too_many_arguments)]
+fn compute_record_hash(
+    prev_hash: &str,
+    id: Uuid,
+    recorded_at: OffsetDateTime,
+    actor: &str,
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<Uuid>,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    reason: Option<&str>,
+) -> String {
+    let fields = [
+        prev_hash.to_string(),
+        id.to_string(),
+        (recorded_at.unix_timestamp_nanos() / 1_000).to_string(),
+        actor.to_string(),
+        action.to_string(),
+        entity_type.to_string(),
+        entity_id.map(|u| u.to_string()).unwrap_or_default(),
+        before.map(Value::to_string).unwrap_or_default(),
+        after.map(Value::to_string).unwrap_or_default(),
+        reason.unwrap_or_default().to_string(),
+    ];
+
+    let mut hasher = Sha256::new();
+    for field in &fields {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample(prev_hash: &str) -> AuditRecord {
+        let mut record = AuditRecord {
+            seq: 1,
+            id: Uuid::nil(),
+            recorded_at: OffsetDateTime::UNIX_EPOCH,
+            actor: "qa.user".to_string(),
+            action: "document.attach".to_string(),
+            entity_type: "document".to_string(),
+            entity_id: Some(Uuid::nil()),
+            before_state: None,
+            after_state: Some(json!({ "title": "Cover Letter", "checksum": "abc" })),
+            reason: Some("Initial upload".to_string()),
+            prev_hash: prev_hash.to_string(),
+            record_hash: String::new(),
+        };
+        record.record_hash = record.compute_hash();
+        record
+    }
+
+    #[test]
+    fn test_hash_is_chained_to_previous_record() {
+        let a = sample(GENESIS_HASH);
+        let b = sample(&a.record_hash);
+        assert_ne!(a.record_hash, b.record_hash);
+    }
+
+    #[test]
+    fn test_any_field_change_breaks_the_hash() {
+        let original = sample(GENESIS_HASH);
+
+        let mut tampered = original.clone();
+        tampered.actor = "someone.else".to_string();
+        assert_ne!(tampered.compute_hash(), original.record_hash);
+
+        let mut tampered = original.clone();
+        tampered.after_state = Some(json!({ "title": "Cover Letter", "checksum": "def" }));
+        assert_ne!(tampered.compute_hash(), original.record_hash);
+    }
+
+    #[test]
+    fn test_json_key_order_does_not_matter() {
+        // JSONB hands keys back in its own order; the hash must not care.
+        let a: Value = serde_json::from_str(r#"{"b": 1, "a": 2}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"a": 2, "b": 1}"#).unwrap();
+        assert_eq!(a.to_string(), b.to_string());
+    }
+}
@@ -0,0 +1,121 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// One stored object, shared by every document with the same SHA-256.
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobRecord {
+    pub sha256: String,
+    pub size_bytes: i64,
+    /// Number of documents pointing at this blob (maintained by trigger).
+    pub ref_count: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// Last (re-)registration; garbage collection waits for this to age.
+    #[serde(with = "time::serde::rfc3339")]
+    pub touched_at: OffsetDateTime,
+}
+
+pub struct BlobRepository {
+    pool: PgPool,
+}
+
+impl BlobRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Records that the store holds `sha256`. Idempotent; call after the upload succeeded.
+    pub async fn register(&self, sha256: &str, size_bytes: i64) -> Result<BlobRecord, sqlx::Error> {
+        sqlx::query_as!(
+            BlobRecord,
+            r#"
+            INSERT INTO blobs (sha256, size_bytes)
+            VALUES ($1, $2)
+            ON CONFLICT (sha256) DO UPDATE SET touched_at = NOW()
+            RETURNING sha256 AS "sha256!", size_bytes, ref_count, created_at, touched_at
+            "#,
+            sha256,
+            size_bytes
+        )
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    pub async fn get(&self, sha256: &str) -> Result<Option<BlobRecord>, sqlx::Error> {
+        sqlx::query_as!(
+            BlobRecord,
+            r#"SELECT sha256 AS "sha256!", size_bytes, ref_count, created_at, touched_at FROM blobs WHERE sha256 = $1"#,
+            sha256
+        )
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Storage key per document of a unit: the blob hash, or the document UUID
+    /// for documents stored before content addressing.
+    pub async fn storage_keys(&self, unit_id: Uuid) -> Result<HashMap<Uuid, String>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, COALESCE(blob_sha256, id::text) AS "key!"
+            FROM documents
+            WHERE submission_unit_id = $1
+            "#,
+            unit_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| (r.id, r.key)).collect())
+    }
+
+    /// Blobs no document points at, untouched for at least `min_age`.
+    pub async fn unreferenced(&self, min_age: Duration) -> Result<Vec<BlobRecord>, sqlx::Error> {
+        sqlx::query_as!(
+            BlobRecord,
+            r#"
+            SELECT sha256 AS "sha256!", size_bytes, ref_count, created_at, touched_at
+            FROM blobs
+            WHERE ref_count = 0
+              AND touched_at < NOW() - make_interval(secs => $1)
+            ORDER BY touched_at
+            "#,
+            min_age.as_secs_f64()
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Removes the blob row, but only while nothing references it.
+    /// Returns false if a document claimed it in the meantime.
+    pub async fn delete_if_unreferenced(&self, sha256: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM blobs WHERE sha256 = $1 AND ref_count = 0",
+            sha256
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Every key the database still knows about: blob hashes plus the UUID keys
+    /// of documents stored before content addressing.
+    pub async fn known_keys(&self) -> Result<HashSet<String>, sqlx::Error> {
+        let keys = sqlx::query_scalar!(
+            r#"
+            SELECT sha256 AS "key!" FROM blobs
+            UNION ALL
+            SELECT id::text AS "key!" FROM documents WHERE blob_sha256 IS NULL
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(keys.into_iter().collect())
+    }
+}
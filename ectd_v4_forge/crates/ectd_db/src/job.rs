@@ -0,0 +1,211 @@
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{FromRow, PgPool};
+use std::fmt;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// What a background job does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobKind {
+    Export,
+    Validation,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::Export => "export",
+            JobKind::Validation => "validation",
+        }
+    }
+}
+
+impl fmt::Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// `queued` -> `running` -> `succeeded` | `failed`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JobRecord {
+    pub id: Uuid,
+    /// "export" or "validation"
+    pub kind: String,
+    pub submission_unit_id: Uuid,
+    /// "queued", "running", "succeeded" or "failed"
+    pub status: String,
+    pub requested_by: String,
+    pub parameters: Value,
+    /// The latest progress event, for jobs that report progress
+    pub progress: Option<Value>,
+    /// Outcome of a finished job (export location, validation findings, failed files)
+    pub result: Option<Value>,
+    pub error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub started_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
+}
+
+impl JobRecord {
+    pub fn is_finished(&self) -> bool {
+        self.status == JobStatus::Succeeded.as_str() || self.status == JobStatus::Failed.as_str()
+    }
+}
+
+pub struct JobRepository {
+    pool: PgPool,
+}
+
+impl JobRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        id: Uuid,
+        kind: JobKind,
+        unit_id: Uuid,
+        requested_by: &str,
+        parameters: &Value,
+    ) -> Result<JobRecord, sqlx::Error> {
+        sqlx::query_as!(
+            JobRecord,
+            r#"
+            INSERT INTO jobs (id, kind, submission_unit_id, requested_by, parameters)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, kind, submission_unit_id, status, requested_by, parameters,
+                      progress, result, error, created_at, started_at, finished_at
+            "#,
+            id,
+            kind.as_str(),
+            unit_id,
+            requested_by,
+            parameters
+        )
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<JobRecord>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRecord,
+            r#"
+            SELECT id, kind, submission_unit_id, status, requested_by, parameters,
+                   progress, result, error, created_at, started_at, finished_at
+            FROM jobs WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Jobs of one submission unit, newest first.
+    pub async fn list_for_unit(&self, unit_id: Uuid) -> Result<Vec<JobRecord>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRecord,
+            r#"
+            SELECT id, kind, submission_unit_id, status, requested_by, parameters,
+                   progress, result, error, created_at, started_at, finished_at
+            FROM jobs WHERE submission_unit_id = $1
+            ORDER BY created_at DESC, id DESC
+            "#,
+            unit_id
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn mark_running(&self, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE jobs SET status = 'running', started_at = NOW() WHERE id = $1 AND status = 'queued'",
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_progress(&self, id: Uuid, progress: &Value) -> Result<(), sqlx::Error> {
+        sqlx::query!("UPDATE jobs SET progress = $2 WHERE id = $1", id, progress)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Records the outcome. `error` is set for failed jobs; `result` may be
+    /// set either way (a failed export still reports which files failed).
+    pub async fn finish(
+        &self,
+        id: Uuid,
+        status: JobStatus,
+        result: Option<&Value>,
+        error: Option<&str>,
+    ) -> Result<JobRecord, sqlx::Error> {
+        sqlx::query_as!(
+            JobRecord,
+            r#"
+            UPDATE jobs SET status = $2, result = $3, error = $4, finished_at = NOW()
+            WHERE id = $1
+            RETURNING id, kind, submission_unit_id, status, requested_by, parameters,
+                      progress, result, error, created_at, started_at, finished_at
+            "#,
+            id,
+            status.as_str(),
+            result,
+            error
+        )
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Fails every job left queued or running by a process that is gone.
+    /// Call once at startup, before accepting new jobs.
+    pub async fn fail_interrupted(&self, reason: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE jobs SET status = 'failed', error = $1, finished_at = NOW()
+            WHERE status IN ('queued', 'running')
+            "#,
+            reason
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,152 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use ectd_core::models::context_of_use::Keyword;
+use ectd_core::models::keyword_definition::KeywordType;
+
+/// A keyword as it stands for a unit: the latest definition from this unit
+/// or an earlier sequence of the same application.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KeywordEntry {
+    /// Absent for code systems this tool doesn't know (e.g. imported ones)
+    #[serde(rename = "type")]
+    pub keyword_type: Option<KeywordType>,
+    pub code: String,
+    pub code_system: String,
+    pub display_name: String,
+    /// "active", or "inactive" once retired
+    pub status_code: String,
+    /// The sequence whose definition is in force
+    pub sequence_number: i32,
+    pub submission_unit_id: Uuid,
+    /// Contexts of use of this unit tagged with it
+    pub usage: i64,
+}
+
+impl KeywordEntry {
+    pub fn is_active(&self) -> bool {
+        self.status_code == "active"
+    }
+}
+
+struct KeywordEntryRow {
+    code: String,
+    code_system: String,
+    display_name: String,
+    status_code: String,
+    sequence_number: i32,
+    submission_unit_id: Uuid,
+    usage: i64,
+}
+
+pub struct KeywordRepository {
+    pool: PgPool,
+}
+
+impl KeywordRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Every keyword defined for the unit's application up to and including
+    /// the unit's sequence, each with the definition from the latest of them.
+    pub async fn vocabulary(&self, unit_id: Uuid) -> Result<Vec<KeywordEntry>, sqlx::Error> {
+        self.entries(unit_id, true).await
+    }
+
+    /// Like `vocabulary`, as it stood before the unit: only earlier sequences count.
+    pub async fn inherited(&self, unit_id: Uuid) -> Result<Vec<KeywordEntry>, sqlx::Error> {
+        self.entries(unit_id, false).await
+    }
+
+    async fn entries(&self, unit_id: Uuid, include_unit: bool) -> Result<Vec<KeywordEntry>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            KeywordEntryRow,
+            r#"
+            SELECT DISTINCT ON (k.code_system, k.code)
+                   k.code, k.code_system, k.display_name, k.status_code,
+                   u.sequence_number, u.id AS submission_unit_id,
+                   (SELECT COUNT(*)
+                    FROM context_keywords ck
+                    JOIN contexts_of_use c ON c.id = ck.context_of_use_id
+                    WHERE c.submission_unit_id = this.id
+                      AND ck.code_system = k.code_system AND ck.code = k.code) AS "usage!"
+            FROM submission_units this
+            JOIN submission_units u
+              ON u.application_id = this.application_id
+             AND (u.sequence_number < this.sequence_number OR ($2 AND u.id = this.id))
+            JOIN keyword_definitions k ON k.submission_unit_id = u.id
+            WHERE this.id = $1
+            ORDER BY k.code_system, k.code, u.sequence_number DESC
+            "#,
+            unit_id,
+            include_unit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| KeywordEntry {
+            keyword_type: KeywordType::from_code_system(&r.code_system),
+            code: r.code,
+            code_system: r.code_system,
+            display_name: r.display_name,
+            status_code: r.status_code,
+            sequence_number: r.sequence_number,
+            submission_unit_id: r.submission_unit_id,
+            usage: r.usage,
+        }).collect())
+    }
+
+    /// Replaces the keywords of a context of use, in the caller's transaction.
+    /// Returns false when the context doesn't belong to this unit.
+    pub async fn set_context_keywords(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        context_id: Uuid,
+        keywords: &[Keyword],
+    ) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM contexts_of_use WHERE id = $1 AND submission_unit_id = $2) AS "exists!""#,
+            context_id,
+            unit_id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        if !exists {
+            return Ok(false);
+        }
+
+        sqlx::query!("DELETE FROM context_keywords WHERE context_of_use_id = $1", context_id)
+            .execute(&mut **tx)
+            .await?;
+        insert_context_keywords(tx, context_id, keywords).await?;
+
+        Ok(true)
+    }
+}
+
+/// Tags a new context of use with its keywords, in the caller's transaction.
+pub(crate) async fn insert_context_keywords(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    context_id: Uuid,
+    keywords: &[Keyword],
+) -> Result<(), sqlx::Error> {
+    for keyword in keywords {
+        sqlx::query!(
+            r#"
+            INSERT INTO context_keywords (context_of_use_id, code, code_system)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+            context_id,
+            keyword.code,
+            keyword.code_system
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
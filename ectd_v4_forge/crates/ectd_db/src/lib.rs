@@ -1,2 +1,14 @@
+pub mod application;
+pub mod audit;
+pub mod blob;
+pub mod job;
+pub mod keyword;
+pub mod listing;
+pub mod planning;
+pub mod priority;
+pub mod search;
+pub mod signature;
+pub mod study;
+pub mod template;
 pub mod repository;
 pub mod schema;
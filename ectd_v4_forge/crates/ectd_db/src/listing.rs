@@ -0,0 +1,510 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use crate::repository::SubmissionSummary;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Error)]
+pub enum ListingError {
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("invalid or expired cursor; start again without one")]
+    InvalidCursor,
+}
+
+/// One page of a listing. `next_cursor` is opaque; pass it back to get the
+/// next page, with the same filters and sort. It is absent on the last page.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// Which page to fetch.
+#[derive(Debug, Clone, Default)]
+pub struct PageRequest {
+    pub cursor: Option<String>,
+    /// Defaults to [`DEFAULT_PAGE_SIZE`], capped at [`MAX_PAGE_SIZE`]
+    pub limit: Option<i64>,
+}
+
+impl PageRequest {
+    pub(crate) fn size(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    pub(crate) fn after<C: DeserializeOwned>(&self) -> Result<Option<C>, ListingError> {
+        self.cursor.as_deref().map(decode_cursor).transpose()
+    }
+}
+
+/// One row of the application listing, with its sequences aggregated.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationSummary {
+    pub application_number: String,
+    pub application_type: String,
+    pub region: String,
+    pub applicant_name: String,
+    pub product_name: Option<String>,
+    pub sequence_count: i64,
+    /// None until the first sequence is created
+    pub latest_sequence: Option<i32>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_created_at: Option<OffsetDateTime>,
+}
+
+/// One row of the document listing, with the unit it belongs to.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSummary {
+    pub id: Uuid,
+    pub submission_unit_id: Uuid,
+    pub application_number: String,
+    pub sequence_number: i32,
+    pub href: String,
+    pub title: String,
+    pub media_type: String,
+    pub checksum: String,
+    /// Codes of the unit's contexts of use that reference the document; later
+    /// sequences that reuse it have their own
+    pub context_codes: Vec<String>,
+}
+
+/// Filters for units and documents. All fields are optional and combined with AND.
+#[derive(Debug, Clone, Default)]
+pub struct SubmissionFilter {
+    pub application_number: Option<String>,
+    /// Case-insensitive substring of the applicant name
+    pub applicant: Option<String>,
+    pub sequence_from: Option<i32>,
+    pub sequence_to: Option<i32>,
+    pub status_code: Option<String>,
+    /// Units (or documents) with a context of use of this code
+    pub context_code: Option<String>,
+    pub created_after: Option<OffsetDateTime>,
+    pub created_before: Option<OffsetDateTime>,
+    /// Only these applications; `None` means all (for callers with limited access)
+    pub applications: Option<Vec<String>>,
+}
+
+impl SubmissionFilter {
+    pub(crate) fn applicant_pattern(&self) -> Option<String> {
+        self.applicant.as_deref().map(|a| {
+            let escaped = a.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            format!("%{}%", escaped)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmissionSort {
+    /// By application number, then sequence number
+    #[default]
+    Application,
+    /// Most recently created first
+    Newest,
+    /// Least recently created first
+    Oldest,
+}
+
+impl SubmissionSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubmissionSort::Application => "application",
+            SubmissionSort::Newest => "newest",
+            SubmissionSort::Oldest => "oldest",
+        }
+    }
+}
+
+impl fmt::Display for SubmissionSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SubmissionSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "application" => Ok(SubmissionSort::Application),
+            "newest" => Ok(SubmissionSort::Newest),
+            "oldest" => Ok(SubmissionSort::Oldest),
+            other => Err(format!("Unknown sort '{}' (expected application, newest or oldest)", other)),
+        }
+    }
+}
+
+/// Sort keys of the last unit on a page; the next page starts after them.
+/// Short field names keep cursors short.
+#[derive(Debug, Serialize, Deserialize)]
+struct UnitCursor {
+    #[serde(rename = "o")]
+    sort: SubmissionSort,
+    #[serde(rename = "a")]
+    application_number: String,
+    #[serde(rename = "s")]
+    sequence_number: i32,
+    #[serde(rename = "c", with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    #[serde(rename = "i")]
+    id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DocumentCursor {
+    #[serde(rename = "a")]
+    application_number: String,
+    #[serde(rename = "s")]
+    sequence_number: i32,
+    #[serde(rename = "h")]
+    href: String,
+    #[serde(rename = "i")]
+    id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApplicationCursor {
+    #[serde(rename = "a")]
+    application_number: String,
+}
+
+/// Cursors are hex-encoded JSON: opaque to clients, trivial to debug.
+fn encode_cursor<C: Serialize>(cursor: &C) -> String {
+    hex::encode(serde_json::to_vec(cursor).expect("cursor serializes"))
+}
+
+fn decode_cursor<C: DeserializeOwned>(cursor: &str) -> Result<C, ListingError> {
+    let bytes = hex::decode(cursor).map_err(|_| ListingError::InvalidCursor)?;
+    serde_json::from_slice(&bytes).map_err(|_| ListingError::InvalidCursor)
+}
+
+/// Fetched one row more than the page size; the extra row only says "there is more".
+pub(crate) fn into_page<T, C: Serialize>(mut rows: Vec<T>, size: i64, cursor_of: impl Fn(&T) -> C) -> Page<T> {
+    let more = rows.len() as i64 > size;
+    rows.truncate(size as usize);
+    let next_cursor = if more { rows.last().map(|last| encode_cursor(&cursor_of(last))) } else { None };
+    Page { items: rows, next_cursor }
+}
+
+pub struct ListingRepository {
+    pool: PgPool,
+}
+
+impl ListingRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Applications, by application number. Only `applicant`, `application_number`
+    /// and `applications` of the filter apply.
+    pub async fn list_applications(
+        &self,
+        filter: &SubmissionFilter,
+        page: &PageRequest,
+    ) -> Result<Page<ApplicationSummary>, ListingError> {
+        let size = page.size();
+        let after: Option<ApplicationCursor> = page.after()?;
+
+        let rows = sqlx::query_as!(
+            ApplicationSummary,
+            r#"
+            SELECT a.application_number, a.application_type, a.region, a.applicant_name, a.product_name,
+                   COUNT(u.id) AS "sequence_count!",
+                   MAX(u.sequence_number) AS latest_sequence,
+                   MAX(u.created_at) AS last_created_at
+            FROM applications a
+            LEFT JOIN submission_units u ON u.application_id = a.id
+            WHERE ($1::text IS NULL OR a.application_number = $1)
+              AND ($2::text IS NULL OR a.applicant_name ILIKE $2)
+              AND ($3::text[] IS NULL OR a.application_number = ANY($3))
+              AND ($4::text IS NULL OR a.application_number > $4)
+            GROUP BY a.id
+            ORDER BY a.application_number
+            LIMIT $5
+            "#,
+            filter.application_number,
+            filter.applicant_pattern(),
+            filter.applications.as_deref(),
+            after.map(|c| c.application_number),
+            size + 1
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(into_page(rows, size, |a| ApplicationCursor { application_number: a.application_number.clone() }))
+    }
+
+    pub async fn list_submissions(
+        &self,
+        filter: &SubmissionFilter,
+        sort: SubmissionSort,
+        page: &PageRequest,
+    ) -> Result<Page<SubmissionSummary>, ListingError> {
+        let size = page.size();
+        let after: Option<UnitCursor> = page.after()?;
+        if after.as_ref().is_some_and(|c| c.sort != sort) {
+            return Err(ListingError::InvalidCursor);
+        }
+
+        // One statement for every sort order: the CASE arms that don't match
+        // the requested sort evaluate to NULL and drop out of the ordering.
+        let rows = sqlx::query_as!(
+            SubmissionSummary,
+            r#"
+            SELECT u.id, a.application_number, a.application_type AS application_code, a.applicant_name,
+                   u.sequence_number, u.code, u.submission_code, u.status_code, u.created_at,
+                   (SELECT COUNT(DISTINCT c.document_reference_id) FROM contexts_of_use c
+                    WHERE c.submission_unit_id = u.id) AS "document_count!"
+            FROM submission_units u
+            JOIN applications a ON a.id = u.application_id
+            WHERE ($1::text IS NULL OR a.application_number = $1)
+              AND ($2::text IS NULL OR a.applicant_name ILIKE $2)
+              AND ($3::int IS NULL OR u.sequence_number >= $3)
+              AND ($4::int IS NULL OR u.sequence_number <= $4)
+              AND ($5::text IS NULL OR u.status_code = $5)
+              AND ($6::text IS NULL OR EXISTS (
+                    SELECT 1 FROM contexts_of_use c WHERE c.submission_unit_id = u.id AND c.code = $6))
+              AND ($7::timestamptz IS NULL OR u.created_at >= $7)
+              AND ($8::timestamptz IS NULL OR u.created_at < $8)
+              AND ($9::text[] IS NULL OR a.application_number = ANY($9))
+              AND ($11::uuid IS NULL OR CASE $10
+                    WHEN 'application' THEN (a.application_number, u.sequence_number, u.id) > ($12, $13, $11)
+                    WHEN 'oldest' THEN (u.created_at, u.id) > ($14, $11)
+                    ELSE (u.created_at, u.id) < ($14, $11)
+                  END)
+            ORDER BY
+                CASE WHEN $10 = 'application' THEN a.application_number END,
+                CASE WHEN $10 = 'application' THEN u.sequence_number END,
+                CASE WHEN $10 = 'oldest' THEN u.created_at END ASC,
+                CASE WHEN $10 = 'newest' THEN u.created_at END DESC,
+                CASE WHEN $10 = 'newest' THEN u.id END DESC,
+                u.id
+            LIMIT $15
+            "#,
+            filter.application_number,
+            filter.applicant_pattern(),
+            filter.sequence_from,
+            filter.sequence_to,
+            filter.status_code,
+            filter.context_code,
+            filter.created_after,
+            filter.created_before,
+            filter.applications.as_deref(),
+            sort.as_str(),
+            after.as_ref().map(|c| c.id),
+            after.as_ref().map(|c| c.application_number.clone()),
+            after.as_ref().map(|c| c.sequence_number),
+            after.as_ref().map(|c| c.created_at),
+            size + 1
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(into_page(rows, size, |u| UnitCursor {
+            sort,
+            application_number: u.application_number.clone(),
+            sequence_number: u.sequence_number,
+            created_at: u.created_at,
+            id: u.id,
+        }))
+    }
+
+    /// Documents, by application, sequence and path. `unit_id` narrows the
+    /// listing to one unit; `context_code` matches the document's own contexts.
+    pub async fn list_documents(
+        &self,
+        filter: &SubmissionFilter,
+        unit_id: Option<Uuid>,
+        page: &PageRequest,
+    ) -> Result<Page<DocumentSummary>, ListingError> {
+        let size = page.size();
+        let after: Option<DocumentCursor> = page.after()?;
+
+        let rows = sqlx::query_as!(
+            DocumentSummary,
+            r#"
+            SELECT d.id, u.id AS "submission_unit_id!", a.application_number, u.sequence_number,
+                   d.xlink_href AS href, d.title, d.media_type, d.checksum,
+                   ARRAY(SELECT c.code::text FROM contexts_of_use c
+                         WHERE c.document_reference_id = d.id AND c.submission_unit_id = u.id
+                         ORDER BY c.code) AS "context_codes!"
+            FROM documents d
+            JOIN submission_units u ON u.id = d.submission_unit_id
+            JOIN applications a ON a.id = u.application_id
+            WHERE ($1::uuid IS NULL OR u.id = $1)
+              AND ($2::text IS NULL OR a.application_number = $2)
+              AND ($3::text IS NULL OR a.applicant_name ILIKE $3)
+              AND ($4::int IS NULL OR u.sequence_number >= $4)
+              AND ($5::int IS NULL OR u.sequence_number <= $5)
+              AND ($6::text IS NULL OR u.status_code = $6)
+              AND ($7::text IS NULL OR EXISTS (
+                    SELECT 1 FROM contexts_of_use c
+                    WHERE c.document_reference_id = d.id AND c.submission_unit_id = u.id AND c.code = $7))
+              AND ($8::timestamptz IS NULL OR u.created_at >= $8)
+              AND ($9::timestamptz IS NULL OR u.created_at < $9)
+              AND ($10::text[] IS NULL OR a.application_number = ANY($10))
+              AND ($14::uuid IS NULL
+                   OR (a.application_number, u.sequence_number, d.xlink_href, d.id) > ($11, $12, $13, $14))
+            ORDER BY a.application_number, u.sequence_number, d.xlink_href, d.id
+            LIMIT $15
+            "#,
+            unit_id,
+            filter.application_number,
+            filter.applicant_pattern(),
+            filter.sequence_from,
+            filter.sequence_to,
+            filter.status_code,
+            filter.context_code,
+            filter.created_after,
+            filter.created_before,
+            filter.applications.as_deref(),
+            after.as_ref().map(|c| c.application_number.clone()),
+            after.as_ref().map(|c| c.sequence_number),
+            after.as_ref().map(|c| c.href.clone()),
+            after.as_ref().map(|c| c.id),
+            size + 1
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(into_page(rows, size, |d| DocumentCursor {
+            application_number: d.application_number.clone(),
+            sequence_number: d.sequence_number,
+            href: d.href.clone(),
+            id: d.id,
+        }))
+    }
+
+    /// Documents with this content that the unit may reference instead of
+    /// sending a copy: those of earlier sequences of its application, the
+    /// latest sequence first. A reference can't cross applications.
+    pub async fn identical_documents(
+        &self,
+        unit_id: Uuid,
+        sha256: &str,
+    ) -> Result<Vec<DocumentSummary>, sqlx::Error> {
+        sqlx::query_as!(
+            DocumentSummary,
+            r#"
+            SELECT d.id, u.id AS "submission_unit_id!", a.application_number, u.sequence_number,
+                   d.xlink_href AS href, d.title, d.media_type, d.checksum,
+                   ARRAY(SELECT c.code::text FROM contexts_of_use c
+                         WHERE c.document_reference_id = d.id AND c.submission_unit_id = u.id
+                         ORDER BY c.code) AS "context_codes!"
+            FROM submission_units this
+            JOIN documents d ON d.blob_sha256 = $2
+            JOIN submission_units u ON u.id = d.submission_unit_id
+            JOIN applications a ON a.id = u.application_id
+            WHERE this.id = $1
+              AND u.application_id = this.application_id AND u.sequence_number < this.sequence_number
+            ORDER BY u.sequence_number DESC, d.id
+            "#,
+            unit_id,
+            sha256.to_lowercase()
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip_and_rejects_garbage() {
+        let cursor = ApplicationCursor { application_number: "123456".to_string() };
+        let decoded: ApplicationCursor = decode_cursor(&encode_cursor(&cursor)).unwrap();
+        assert_eq!(decoded.application_number, "123456");
+
+        assert!(matches!(decode_cursor::<ApplicationCursor>("zz"), Err(ListingError::InvalidCursor)));
+        assert!(matches!(decode_cursor::<UnitCursor>(&encode_cursor(&cursor)), Err(ListingError::InvalidCursor)));
+    }
+
+    #[test]
+    fn test_into_page_sets_cursor_only_when_more_rows_exist() {
+        let page = into_page(vec![1, 2, 3], 2, |n| *n);
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(page.next_cursor, Some(encode_cursor(&2)));
+
+        let last = into_page(vec![1, 2], 2, |n| *n);
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[test]
+    fn test_applicant_pattern_escapes_wildcards() {
+        let filter = SubmissionFilter { applicant: Some("50%_off".to_string()), ..Default::default() };
+        assert_eq!(filter.applicant_pattern().unwrap(), "%50\\%\\_off%");
+    }
+
+    const SHA: &str = "ab54d286e2e3e8d1f1ebc4ce8fb4cf9a30e4a1d97a0d9b5e0d0e2e3fa1b2c3d4";
+
+    async fn application(pool: &PgPool, number: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO applications (id, application_number, application_type, applicant_name)
+             VALUES (gen_random_uuid(), $1, 'nda', 'Acme') RETURNING id",
+        )
+        .bind(number)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn unit(pool: &PgPool, application_id: Uuid, sequence: i32) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO submission_units (submission_id, sequence_number, code, code_system, status_code, application_id)
+             VALUES (gen_random_uuid(), $1, 'original-application', 'oid', 'active', $2) RETURNING id",
+        )
+        .bind(sequence)
+        .bind(application_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn document(pool: &PgPool, unit_id: Uuid) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO documents (submission_unit_id, xlink_href, checksum, title, media_type, blob_sha256)
+             VALUES ($1, 'm1/us/letter.pdf', $2, 'Letter', 'application/pdf', $2) RETURNING id",
+        )
+        .bind(unit_id)
+        .bind(SHA)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_identical_documents_are_those_of_earlier_sequences(pool: PgPool) {
+        crate::schema::migrate(&pool).await.unwrap();
+        sqlx::query("INSERT INTO blobs (sha256, size_bytes) VALUES ($1, 5)").bind(SHA).execute(&pool).await.unwrap();
+        let app = application(&pool, "100001").await;
+        let other_app = application(&pool, "100002").await;
+        let (first, second, third) = (unit(&pool, app, 1).await, unit(&pool, app, 2).await, unit(&pool, app, 3).await);
+        let in_first = document(&pool, first).await;
+        let in_second = document(&pool, second).await;
+        document(&pool, unit(&pool, other_app, 1).await).await;
+
+        let repo = ListingRepository::new(pool);
+        let ids = |docs: Vec<DocumentSummary>| docs.into_iter().map(|d| d.id).collect::<Vec<_>>();
+
+        // Latest first; never the unit's own, a later sequence's or another application's
+        assert_eq!(ids(repo.identical_documents(third, SHA).await.unwrap()), [in_second, in_first]);
+        assert_eq!(ids(repo.identical_documents(second, &SHA.to_uppercase()).await.unwrap()), [in_first]);
+        assert!(repo.identical_documents(first, SHA).await.unwrap().is_empty());
+        assert!(repo.identical_documents(third, &"0".repeat(64)).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use time::{Date, OffsetDateTime};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// Due dates travel as "YYYY-MM-DD"
+time::serde::format_description!(pub iso_date, Date, "[year]-[month]-[day]");
+
+/// A context of use planned for a unit before its document exists.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Placeholder {
+    pub id: Uuid,
+    pub submission_unit_id: Uuid,
+    /// The context of use code the document will be attached under
+    pub code: String,
+    /// Expected document title
+    pub title: Option<String>,
+    /// Who delivers the document
+    pub owner: Option<String>,
+    #[serde(with = "iso_date::option")]
+    #[schema(value_type = Option<String>, format = Date)]
+    pub due_date: Option<Date>,
+    pub note: Option<String>,
+    /// Optional placeholders don't hold up the export
+    pub required: bool,
+    /// Priority of the context of use, unless the attach gives one
+    pub priority: Option<i32>,
+    /// Package folder for the document, instead of the default for its code
+    pub folder: Option<String>,
+    /// The context of use that fulfilled it; absent while open
+    pub context_of_use_id: Option<Uuid>,
+    /// The document attached for it
+    pub document_id: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub fulfilled_at: Option<OffsetDateTime>,
+    /// Open and past its due date
+    pub overdue: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl Placeholder {
+    pub fn is_open(&self) -> bool {
+        self.context_of_use_id.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewPlaceholder {
+    pub code: String,
+    pub title: Option<String>,
+    pub owner: Option<String>,
+    pub due_date: Option<Date>,
+    pub note: Option<String>,
+    pub required: bool,
+    pub priority: Option<i32>,
+    pub folder: Option<String>,
+}
+
+/// Editable placeholder fields; `None` leaves a field as is.
+#[derive(Debug, Clone, Default)]
+pub struct PlaceholderUpdate {
+    pub code: Option<String>,
+    pub title: Option<String>,
+    pub owner: Option<String>,
+    pub due_date: Option<Date>,
+    pub note: Option<String>,
+    pub required: Option<bool>,
+    pub priority: Option<i32>,
+    pub folder: Option<String>,
+}
+
+pub struct PlanningRepository {
+    pool: PgPool,
+}
+
+impl PlanningRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        placeholder: &NewPlaceholder,
+    ) -> Result<Placeholder, sqlx::Error> {
+        let mut created = self.create_all(tx, unit_id, std::slice::from_ref(placeholder)).await?;
+        Ok(created.remove(0))
+    }
+
+    /// Adds several placeholders in the caller's transaction, in order.
+    pub async fn create_all(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        placeholders: &[NewPlaceholder],
+    ) -> Result<Vec<Placeholder>, sqlx::Error> {
+        let mut created = Vec::with_capacity(placeholders.len());
+
+        for placeholder in placeholders {
+            let row = sqlx::query_as!(
+                Placeholder,
+                r#"
+                INSERT INTO context_placeholders
+                (id, submission_unit_id, code, title, owner, due_date, note, required, priority, folder)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                RETURNING id, submission_unit_id, code, title, owner, due_date, note,
+                          required, priority, folder,
+                          context_of_use_id, NULL::uuid AS "document_id?", fulfilled_at,
+                          COALESCE(due_date < CURRENT_DATE, FALSE) AS "overdue!",
+                          created_at
+                "#,
+                Uuid::new_v4(),
+                unit_id,
+                placeholder.code,
+                placeholder.title,
+                placeholder.owner,
+                placeholder.due_date,
+                placeholder.note,
+                placeholder.required,
+                placeholder.priority,
+                placeholder.folder
+            )
+            .fetch_one(&mut **tx)
+            .await?;
+            created.push(row);
+        }
+
+        Ok(created)
+    }
+
+    /// The unit's placeholders: open before fulfilled, required before optional,
+    /// then soonest due first.
+    pub async fn list(&self, unit_id: Uuid) -> Result<Vec<Placeholder>, sqlx::Error> {
+        sqlx::query_as!(
+            Placeholder,
+            r#"
+            SELECT p.id, p.submission_unit_id, p.code, p.title, p.owner, p.due_date, p.note,
+                   p.required, p.priority, p.folder,
+                   p.context_of_use_id, c.document_reference_id AS "document_id?", p.fulfilled_at,
+                   COALESCE(p.context_of_use_id IS NULL AND p.due_date < CURRENT_DATE, FALSE) AS "overdue!",
+                   p.created_at
+            FROM context_placeholders p
+            LEFT JOIN contexts_of_use c ON c.id = p.context_of_use_id
+            WHERE p.submission_unit_id = $1
+            ORDER BY p.context_of_use_id IS NOT NULL, NOT p.required, p.due_date NULLS LAST, p.code,
+                     p.priority NULLS LAST, p.created_at, p.id
+            "#,
+            unit_id
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get(&self, unit_id: Uuid, id: Uuid) -> Result<Option<Placeholder>, sqlx::Error> {
+        Ok(self.list(unit_id).await?.into_iter().find(|p| p.id == id))
+    }
+
+    /// The placeholder as changed, or None when the unit has no such placeholder.
+    pub async fn update(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        id: Uuid,
+        update: &PlaceholderUpdate,
+    ) -> Result<Option<Placeholder>, sqlx::Error> {
+        sqlx::query_as!(
+            Placeholder,
+            r#"
+            WITH p AS (
+                UPDATE context_placeholders SET
+                    code = COALESCE($3, code),
+                    title = COALESCE($4, title),
+                    owner = COALESCE($5, owner),
+                    due_date = COALESCE($6, due_date),
+                    note = COALESCE($7, note),
+                    required = COALESCE($8, required),
+                    priority = COALESCE($9, priority),
+                    folder = COALESCE($10, folder)
+                WHERE submission_unit_id = $1 AND id = $2
+                RETURNING *
+            )
+            SELECT p.id, p.submission_unit_id, p.code, p.title, p.owner, p.due_date, p.note,
+                   p.required, p.priority, p.folder,
+                   p.context_of_use_id, c.document_reference_id AS "document_id?", p.fulfilled_at,
+                   COALESCE(p.context_of_use_id IS NULL AND p.due_date < CURRENT_DATE, FALSE) AS "overdue!",
+                   p.created_at
+            FROM p
+            LEFT JOIN contexts_of_use c ON c.id = p.context_of_use_id
+            "#,
+            unit_id,
+            id,
+            update.code,
+            update.title,
+            update.owner,
+            update.due_date,
+            update.note,
+            update.required,
+            update.priority,
+            update.folder
+        )
+        .fetch_optional(&mut **tx)
+        .await
+    }
+
+    /// Returns false when the unit has no such placeholder.
+    pub async fn delete(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM context_placeholders WHERE submission_unit_id = $1 AND id = $2",
+            unit_id,
+            id
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Marks a placeholder as fulfilled by a new context of use, in the caller's
+/// transaction. With `placeholder` that one must be open and have the same
+/// code (`RowNotFound` otherwise); without it the open placeholder of the same
+/// code that comes first (see [`PlanningRepository::list`]) is taken, if there is one.
+pub(crate) async fn fulfil(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    unit_id: Uuid,
+    code: &str,
+    placeholder: Option<Uuid>,
+    context_id: Uuid,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let fulfilled = sqlx::query_scalar!(
+        r#"
+        UPDATE context_placeholders SET context_of_use_id = $4, fulfilled_at = NOW()
+        WHERE id = (
+            SELECT id FROM context_placeholders
+            WHERE submission_unit_id = $1 AND code = $2 AND context_of_use_id IS NULL
+              AND ($3::uuid IS NULL OR id = $3)
+            ORDER BY NOT required, due_date NULLS LAST, priority NULLS LAST, created_at, id
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id
+        "#,
+        unit_id,
+        code,
+        placeholder,
+        context_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    match (placeholder, fulfilled) {
+        (Some(_), None) => Err(sqlx::Error::RowNotFound),
+        (_, fulfilled) => Ok(fulfilled),
+    }
+}
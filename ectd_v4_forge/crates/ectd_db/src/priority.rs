@@ -0,0 +1,96 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A context of use in force for a unit: active and not replaced by the
+/// unit or an earlier sequence of its application.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewContext {
+    pub context_of_use_id: Uuid,
+    pub submission_unit_id: Uuid,
+    pub sequence_number: i32,
+    pub code: String,
+    pub priority: i32,
+    pub document_id: Option<Uuid>,
+    pub title: Option<String>,
+}
+
+pub struct PriorityRepository {
+    pool: PgPool,
+}
+
+impl PriorityRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// The current view as of the unit: its own contexts and those of the
+    /// earlier sequences still in force, by code and priority.
+    pub async fn current_view(&self, unit_id: Uuid) -> Result<Vec<ViewContext>, sqlx::Error> {
+        self.contexts(unit_id, true).await
+    }
+
+    /// Like `current_view`, without the unit's own contexts.
+    pub async fn inherited(&self, unit_id: Uuid) -> Result<Vec<ViewContext>, sqlx::Error> {
+        self.contexts(unit_id, false).await
+    }
+
+    async fn contexts(&self, unit_id: Uuid, include_unit: bool) -> Result<Vec<ViewContext>, sqlx::Error> {
+        sqlx::query_as!(
+            ViewContext,
+            r#"
+            SELECT c.id AS context_of_use_id, u.id AS submission_unit_id, u.sequence_number,
+                   c.code, c.priority_number AS priority,
+                   d.id AS "document_id?", d.title AS "title?"
+            FROM submission_units this
+            JOIN submission_units u
+              ON u.application_id = this.application_id
+             AND (u.sequence_number < this.sequence_number OR u.id = this.id)
+            JOIN contexts_of_use c ON c.submission_unit_id = u.id
+            LEFT JOIN documents d ON d.id = c.document_reference_id
+            WHERE this.id = $1
+              AND c.status_code = 'active'
+              AND ($2 OR u.id <> this.id)
+              AND NOT EXISTS (
+                  SELECT 1
+                  FROM contexts_of_use r
+                  JOIN submission_units ru ON ru.id = r.submission_unit_id
+                  WHERE r.replaces_context_id = c.id
+                    AND ru.application_id = this.application_id
+                    AND (ru.sequence_number < this.sequence_number OR ru.id = this.id)
+              )
+            ORDER BY c.code, c.priority_number, u.sequence_number, c.id
+            "#,
+            unit_id,
+            include_unit
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Sets the priorities of contexts of the unit in the caller's transaction.
+    /// Fails with `RowNotFound` when one is not the unit's.
+    pub async fn renumber(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        priorities: &[(Uuid, i32)],
+    ) -> Result<(), sqlx::Error> {
+        for (context_id, priority) in priorities {
+            let result = sqlx::query!(
+                "UPDATE contexts_of_use SET priority_number = $3 WHERE id = $1 AND submission_unit_id = $2",
+                context_id,
+                unit_id,
+                priority
+            )
+            .execute(&mut **tx)
+            .await?;
+            if result.rows_affected() == 0 {
+                return Err(sqlx::Error::RowNotFound);
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,52 @@
+use serde::Serialize;
 use sqlx::{PgPool, FromRow};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use ectd_core::models::submission_unit::SubmissionUnit;
+use crate::{keyword, planning};
 // Import other models for cleaner casting
 use ectd_core::models::{
-    context_of_use::{ContextOfUse, PriorityNumber, DocumentReference, DocumentIdRef},
+    context_of_use::{ContextOfUse, PriorityNumber, DocumentReference, DocumentIdRef, RelatedContextOfUse, Keyword},
     document::{Document, DocumentTitle, DocumentText, DocumentReferencePath},
     keyword_definition::{KeywordDefinition, KeywordDefinitionValue, KeywordDefinitionItem, DisplayName},
 };
 
+/// One row of the submission listing: the unit header without its children.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmissionSummary {
+    pub id: Uuid,
+    pub application_number: String,
+    pub application_code: String,
+    pub applicant_name: String,
+    pub sequence_number: i32,
+    pub code: String,
+    pub submission_code: String,
+    pub status_code: String,
+    /// Documents the unit's contexts of use reference, its own or reused
+    pub document_count: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Header fields that may change after creation; `None` leaves a field as is.
+/// Application type and applicant belong to the application (see `ApplicationRepository`).
+#[derive(Debug, Clone, Default)]
+pub struct SubmissionUpdate {
+    pub code: Option<String>,
+    pub status_code: Option<String>,
+    pub submission_code: Option<String>,
+}
+
+/// Editable Context of Use fields; `None` leaves a field as is.
+#[derive(Debug, Clone, Default)]
+pub struct ContextUpdate {
+    pub code: Option<String>,
+    pub status_code: Option<String>,
+    pub priority_number: Option<u32>,
+}
+
 pub struct SubmissionRepository {
     pool: PgPool,
 }
@@ -17,198 +56,244 @@ impl SubmissionRepository {
         Self { pool }
     }
 
-    /// The "Big Bang": Takes a full SubmissionUnit struct and persists it transactionally.
-    /// Surgically attaches a single document and context to an existing submission unit
-    /// This is the "Hands" of the system.
+    /// Attaches one document and its context of use to an existing unit, in the
+    /// caller's transaction. The context fulfils `placeholder` (`RowNotFound`
+    /// unless it is open and has the same code), or else the unit's first open
+    /// placeholder of the same code in planning order; returns the placeholder
+    /// fulfilled, if any.
     pub async fn add_document_to_submission(
         &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
         unit_id: Uuid,
         doc: &Document,
         cou: &ContextOfUse,
-    ) -> Result<(), sqlx::Error> {
-        let mut tx = self.pool.begin().await?;
+        placeholder: Option<Uuid>,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let fulfilled = self.add_documents_to_submission(tx, unit_id, &[(Some(doc), cou, placeholder)]).await?;
+        Ok(fulfilled[0])
+    }
 
-        // 1. Insert Document Metadata
-        // We use the ID from the struct, ensuring strict UUID compliance
-        sqlx::query!(
+    /// `add_document_to_submission` for several documents in the caller's
+    /// transaction; returns the placeholder each fulfilled, in order. A context
+    /// without a document references one already stored (e.g. of an earlier sequence).
+    pub async fn add_documents_to_submission(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        items: &[(Option<&Document>, &ContextOfUse, Option<Uuid>)],
+    ) -> Result<Vec<Option<Uuid>>, sqlx::Error> {
+        let mut fulfilled = Vec::with_capacity(items.len());
+        for &(doc, cou, placeholder) in items {
+            fulfilled.push(insert_document(tx, unit_id, doc, cou, placeholder).await?);
+        }
+        Ok(fulfilled)
+    }
+
+    /// Persists a full SubmissionUnit in the caller's transaction. The
+    /// application is created from the unit's header if its number is new; an
+    /// existing application keeps its own type and applicant.
+    pub async fn create_submission(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit: &SubmissionUnit,
+    ) -> Result<Uuid, sqlx::Error> {
+        let application_id = upsert_application(tx, unit).await?;
+        insert_unit(tx, application_id, unit).await
+    }
+
+    /// Like `create_submission`, numbering the unit as the application's next
+    /// sequence (1 for a new application). The application row stays locked
+    /// until the transaction ends, so concurrent callers get distinct numbers
+    /// without gaps. An empty submission code becomes `seq-NNNN`.
+    pub async fn create_next_submission(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit: &mut SubmissionUnit,
+    ) -> Result<Uuid, sqlx::Error> {
+        let application_id = upsert_application(tx, unit).await?;
+
+        let next = sqlx::query_scalar!(
             r#"
-            INSERT INTO documents
-            (id, submission_unit_id, xlink_href, checksum, checksum_algorithm, title)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            SELECT COALESCE(MAX(sequence_number), 0) + 1 AS "next!"
+            FROM submission_units
+            WHERE application_id = $1
             "#,
-            Uuid::parse_str(&doc.id).unwrap(), // Safe unwrap because we generated it
-            unit_id,
-            doc.text.reference.value,
-            doc.text.checksum,
-            doc.text.checksum_algorithm,
-            doc.title.value
+            application_id
         )
-        .execute(&mut *tx)
+        .fetch_one(&mut **tx)
         .await?;
 
-        // 2. Insert Context of Use (The Link)
-        // This connects the logic (Context) to the file (Document)
-        let doc_ref_id = cou
-            .document_reference
-            .as_ref()
-            .map(|d| Uuid::parse_str(&d.id.root).unwrap());
+        unit.submission.sequence_number.value = next as u32;
+        if unit.submission.code.is_empty() {
+            unit.submission.code = format!("seq-{:04}", next);
+        }
 
-        sqlx::query!(
+        insert_unit(tx, application_id, unit).await
+    }
+
+    pub async fn submission_exists(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM submission_units WHERE id = $1) AS "exists!""#,
+            id
+        )
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// The application a unit belongs to, or None for an unknown unit.
+    pub async fn application_number(&self, id: Uuid) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
             r#"
-            INSERT INTO contexts_of_use
-            (id, submission_unit_id, code, code_system, status_code, priority_number, document_reference_id)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            SELECT a.application_number
+            FROM submission_units u
+            JOIN applications a ON a.id = u.application_id
+            WHERE u.id = $1
             "#,
-            Uuid::parse_str(&cou.id).unwrap(),
-            unit_id,
-            cou.code,
-            cou.code_system,
-            cou.status_code,
-            cou.priority_number.value as i32,
-            doc_ref_id
+            id
         )
-        .execute(&mut *tx)
-        .await?;
-
-        tx.commit().await?;
-        Ok(())
+            .fetch_optional(&self.pool)
+            .await
     }
 
-    pub async fn create_submission(&self, unit: &SubmissionUnit) -> Result<Uuid, sqlx::Error> {
-        // 1. START THE TRANSACTION
-        let mut tx = self.pool.begin().await?;
+    /// Ids of the documents sent in the earlier sequences of the unit's
+    /// application, which its contexts may reference instead of a new copy.
+    pub async fn inherited_document_ids(&self, id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT d.id
+            FROM submission_units this
+            JOIN submission_units u
+              ON u.application_id = this.application_id AND u.sequence_number < this.sequence_number
+            JOIN documents d ON d.submission_unit_id = u.id
+            WHERE this.id = $1
+            "#,
+            id
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
 
-        // ---------------------------------------------------------
-        // LEVEL 1: Insert the Submission Unit Container (Updated)
-        // ---------------------------------------------------------
-        let unit_id = Uuid::parse_str(&unit.id).unwrap_or_else(|_| Uuid::new_v4());
-        let submission_id = Uuid::parse_str(&unit.submission.id).unwrap_or_else(|_| Uuid::new_v4());
-        let sequence_number = unit.submission.sequence_number.value;
+    /// Returns false when no unit has this id.
+    pub async fn update_submission(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        id: Uuid,
+        update: &SubmissionUpdate,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE submission_units SET
+                code = COALESCE($2, code),
+                status_code = COALESCE($3, status_code),
+                submission_code = COALESCE($4, submission_code)
+            WHERE id = $1
+            "#,
+            id,
+            update.code,
+            update.status_code,
+            update.submission_code
+        )
+        .execute(&mut **tx)
+        .await?;
 
-        // Extract new metadata fields
-        let app_uuid = Uuid::parse_str(&unit.application.id).unwrap_or_else(|_| Uuid::new_v4());
-        let app_code = &unit.application.code;
-        let app_num = &unit.application.application_number.code;
-        let applicant_name = &unit.applicant.sponsoring_organization.name;
-        let sub_code = &unit.submission.code;
+        Ok(result.rows_affected() > 0)
+    }
 
-        sqlx::query!(
+    /// Returns false when the context doesn't belong to this unit.
+    pub async fn update_context(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        context_id: Uuid,
+        update: &ContextUpdate,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
             r#"
-            INSERT INTO submission_units
-            (id, submission_id, sequence_number, code, code_system, status_code,
-             application_id_uuid, application_code, application_number, applicant_name, submission_code)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            UPDATE contexts_of_use SET
+                code = COALESCE($3, code),
+                status_code = COALESCE($4, status_code),
+                priority_number = COALESCE($5, priority_number)
+            WHERE id = $1 AND submission_unit_id = $2
             "#,
+            context_id,
             unit_id,
-            submission_id,
-            sequence_number as i32,
-            unit.code,
-            unit.code_system,
-            unit.status_code,
-            app_uuid,
-            app_code,
-            app_num,
-            applicant_name,
-            sub_code
+            update.code,
+            update.status_code,
+            update.priority_number.map(|p| p as i32)
         )
-        .execute(&mut *tx)
+        .execute(&mut **tx)
         .await?;
 
-        // ---------------------------------------------------------
-        // LEVEL 2: Insert Documents (Unchanged)
-        // ---------------------------------------------------------
-        for doc in &unit.documents {
-            let doc_id = Uuid::parse_str(&doc.id).unwrap_or_else(|_| Uuid::new_v4());
-            let href = &doc.text.reference.value;
-            let checksum = &doc.text.checksum;
-            let alg = &doc.text.checksum_algorithm;
-            let title = &doc.title.value;
-
-            sqlx::query!(
-                r#"
-                INSERT INTO documents
-                (id, submission_unit_id, xlink_href, checksum, checksum_algorithm, title)
-                VALUES ($1, $2, $3, $4, $5, $6)
-                "#,
-                doc_id,
-                unit_id,
-                href,
-                checksum,
-                alg,
-                title
-            )
-            .execute(&mut *tx)
-            .await?;
-        }
+        Ok(result.rows_affected() > 0)
+    }
 
-        // ---------------------------------------------------------
-        // LEVEL 3: Insert Keyword Definitions (Unchanged)
-        // ---------------------------------------------------------
-        if let Some(definitions) = &unit.keyword_definitions {
-            for def in definitions {
-                let val = &def.value.item;
-                sqlx::query!(
-                    r#"
-                    INSERT INTO keyword_definitions
-                    (submission_unit_id, code, code_system, display_name)
-                    VALUES ($1, $2, $3, $4)
-                    "#,
-                    unit_id,
-                    def.code,
-                    "urn:oid:2.16.840.1.113883.3.989.2.1.1.1",
-                    val.display_name.value
-                )
-                .execute(&mut *tx)
-                .await?;
-            }
-        }
+    /// Inserts a keyword definition, or renames or re-states it when the unit
+    /// already defines the code in that code system.
+    pub async fn upsert_keyword_definition(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        def: &KeywordDefinition,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO keyword_definitions
+            (submission_unit_id, code, code_system, display_name, status_code)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (submission_unit_id, code_system, code)
+            DO UPDATE SET display_name = EXCLUDED.display_name, status_code = EXCLUDED.status_code
+            "#,
+            unit_id,
+            def.code,
+            def.code_system,
+            def.value.item.display_name.value,
+            def.status_code.as_deref().unwrap_or("active")
+        )
+        .execute(&mut **tx)
+        .await?;
 
-        // ---------------------------------------------------------
-        // LEVEL 4: Insert Context of Use (Unchanged)
-        // ---------------------------------------------------------
-        for cou in &unit.context_of_use {
-            let cou_id = Uuid::parse_str(&cou.id).unwrap_or_else(|_| Uuid::new_v4());
-            let doc_ref_id = cou.document_reference.as_ref()
-                .map(|d| Uuid::parse_str(&d.id.root).unwrap_or(Uuid::nil()));
+        Ok(())
+    }
 
-            sqlx::query!(
-                r#"
-                INSERT INTO contexts_of_use
-                (id, submission_unit_id, code, code_system, status_code, priority_number, document_reference_id)
-                VALUES ($1, $2, $3, $4, $5, $6, $7)
-                "#,
-                cou_id,
-                unit_id,
-                cou.code,
-                cou.code_system,
-                cou.status_code,
-                cou.priority_number.value as i32,
-                doc_ref_id
-            )
-            .execute(&mut *tx)
-            .await?;
-        }
+    /// Returns false when the unit has no definition with this code.
+    pub async fn delete_keyword_definition(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        code_system: &str,
+        code: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM keyword_definitions WHERE submission_unit_id = $1 AND code_system = $2 AND code = $3",
+            unit_id,
+            code_system,
+            code
+        )
+        .execute(&mut **tx)
+        .await?;
 
-        tx.commit().await?;
-        Ok(unit_id)
+        Ok(result.rows_affected() > 0)
     }
 
-    /// Reconstructs a full SubmissionUnit from the relational database
+    /// Reconstructs a full SubmissionUnit from the relational database.
+    /// Child rows are ordered so the same data always yields the same XML.
     pub async fn get_submission(&self, id: Uuid) -> Result<SubmissionUnit, sqlx::Error> {
         // 1. Fetch Root (Updated Select)
         let unit_rec = sqlx::query!(
             r#"
-            SELECT id, submission_id, sequence_number, code, code_system, status_code, created_at,
-                   application_id_uuid, application_code, application_number, applicant_name, submission_code
-            FROM submission_units
-            WHERE id = $1
+            SELECT u.id, u.submission_id, u.sequence_number, u.code, u.code_system, u.status_code,
+                   u.created_at, u.submission_code,
+                   a.id AS application_id, a.application_type, a.application_number, a.applicant_name
+            FROM submission_units u
+            JOIN applications a ON a.id = u.application_id
+            WHERE u.id = $1
             "#,
             id
         ).fetch_one(&self.pool).await?;
 
         // 2. Fetch Documents (Using centralized map)
         let documents: Vec<Document> = sqlx::query_as!(DocumentRow,
-            r#"SELECT id, xlink_href, checksum, checksum_algorithm, title, media_type FROM documents WHERE submission_unit_id = $1"#,
+            r#"SELECT id, xlink_href, checksum, checksum_algorithm, title, media_type FROM documents WHERE submission_unit_id = $1 ORDER BY xlink_href, id"#,
             id
         )
         .fetch_all(&self.pool)
@@ -217,9 +302,9 @@ impl SubmissionRepository {
         .map(Into::into)
         .collect();
 
-        // 3. Fetch Contexts
-        let context_of_use: Vec<ContextOfUse> = sqlx::query_as!(ContextRow,
-            r#"SELECT id, code, code_system, status_code, priority_number, document_reference_id FROM contexts_of_use WHERE submission_unit_id = $1"#,
+        // 3. Fetch Contexts and the keywords they are tagged with
+        let mut context_of_use: Vec<ContextOfUse> = sqlx::query_as!(ContextRow,
+            r#"SELECT id, code, code_system, status_code, priority_number, document_reference_id, replaces_context_id FROM contexts_of_use WHERE submission_unit_id = $1 ORDER BY code, priority_number, id"#,
             id
         )
         .fetch_all(&self.pool)
@@ -228,9 +313,28 @@ impl SubmissionRepository {
         .map(Into::into)
         .collect();
 
+        let context_keywords = sqlx::query!(
+            r#"
+            SELECT ck.context_of_use_id, ck.code, ck.code_system
+            FROM context_keywords ck
+            JOIN contexts_of_use c ON c.id = ck.context_of_use_id
+            WHERE c.submission_unit_id = $1
+            ORDER BY ck.code_system, ck.code
+            "#,
+            id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        for row in context_keywords {
+            let context_id = row.context_of_use_id.to_string();
+            if let Some(cou) = context_of_use.iter_mut().find(|c| c.id == context_id) {
+                cou.keywords.push(Keyword { code: row.code, code_system: row.code_system });
+            }
+        }
+
         // 4. Fetch Keywords
         let keywords_raw = sqlx::query_as!(KeywordRow,
-            r#"SELECT code, code_system, display_name FROM keyword_definitions WHERE submission_unit_id = $1"#,
+            r#"SELECT code, code_system, display_name, status_code FROM keyword_definitions WHERE submission_unit_id = $1 ORDER BY code, code_system"#,
             id
         )
         .fetch_all(&self.pool)
@@ -264,8 +368,8 @@ impl SubmissionRepository {
 
             // Real Application Metadata
             application: ectd_core::models::submission_unit::Application {
-                id: unit_rec.application_id_uuid.to_string(),
-                code: unit_rec.application_code,
+                id: unit_rec.application_id.to_string(),
+                code: unit_rec.application_type,
                 code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
                 application_number: ectd_core::models::submission_unit::ApplicationNumber {
                     code: unit_rec.application_number,
@@ -287,6 +391,209 @@ impl SubmissionRepository {
     }
 }
 
+/// Inserts one document (if given) with its context of use into an open
+/// transaction; returns the placeholder it fulfilled, if any.
+async fn insert_document(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    unit_id: Uuid,
+    doc: Option<&Document>,
+    cou: &ContextOfUse,
+    placeholder: Option<Uuid>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    // 1. Insert Document Metadata
+    // We use the ID from the struct, ensuring strict UUID compliance.
+    // The SHA-256 checksum doubles as the content address; the blob must already be registered.
+    if let Some(doc) = doc {
+        sqlx::query!(
+            r#"
+            INSERT INTO documents
+            (id, submission_unit_id, xlink_href, checksum, checksum_algorithm, title, blob_sha256, media_type)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            Uuid::parse_str(&doc.id).unwrap(), // Safe unwrap because we generated it
+            unit_id,
+            doc.text.reference.value,
+            doc.text.checksum,
+            doc.text.checksum_algorithm,
+            doc.title.value,
+            doc.text.checksum.to_lowercase(),
+            doc.text.media_type
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    // 2. Insert Context of Use (The Link)
+    // This connects the logic (Context) to the file (Document)
+    let doc_ref_id = cou
+        .document_reference
+        .as_ref()
+        .map(|d| Uuid::parse_str(&d.id.root).unwrap());
+
+    sqlx::query!(
+        r#"
+        INSERT INTO contexts_of_use
+        (id, submission_unit_id, code, code_system, status_code, priority_number, document_reference_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        Uuid::parse_str(&cou.id).unwrap(),
+        unit_id,
+        cou.code,
+        cou.code_system,
+        cou.status_code,
+        cou.priority_number.value as i32,
+        doc_ref_id
+    )
+    .execute(&mut **tx)
+    .await?;
+    keyword::insert_context_keywords(tx, Uuid::parse_str(&cou.id).unwrap(), &cou.keywords).await?;
+
+    // 3. Planned content it delivers
+    planning::fulfil(tx, unit_id, &cou.code, placeholder, Uuid::parse_str(&cou.id).unwrap()).await
+}
+
+/// The id of the unit's application, creating it from the unit header when
+/// the number is new. Locks the application row for the rest of the transaction.
+async fn upsert_application(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    unit: &SubmissionUnit,
+) -> Result<Uuid, sqlx::Error> {
+    let app_uuid = Uuid::parse_str(&unit.application.id).unwrap_or_else(|_| Uuid::new_v4());
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO applications (id, application_number, application_type, applicant_name)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (application_number) DO UPDATE SET application_number = EXCLUDED.application_number
+        RETURNING id
+        "#,
+        app_uuid,
+        unit.application.application_number.code,
+        unit.application.code,
+        unit.applicant.sponsoring_organization.name
+    )
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// Inserts the unit and its children (levels 1-4) into an open transaction.
+async fn insert_unit(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    application_id: Uuid,
+    unit: &SubmissionUnit,
+) -> Result<Uuid, sqlx::Error> {
+    // ---------------------------------------------------------
+    // LEVEL 1: Insert the Submission Unit Container (Updated)
+    // ---------------------------------------------------------
+    let unit_id = Uuid::parse_str(&unit.id).unwrap_or_else(|_| Uuid::new_v4());
+    let submission_id = Uuid::parse_str(&unit.submission.id).unwrap_or_else(|_| Uuid::new_v4());
+    let sequence_number = unit.submission.sequence_number.value;
+    let sub_code = &unit.submission.code;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO submission_units
+        (id, submission_id, sequence_number, code, code_system, status_code,
+         application_id, submission_code)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+        unit_id,
+        submission_id,
+        sequence_number as i32,
+        unit.code,
+        unit.code_system,
+        unit.status_code,
+        application_id,
+        sub_code
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    // ---------------------------------------------------------
+    // LEVEL 2: Insert Documents (content-addressed by checksum)
+    // ---------------------------------------------------------
+    for doc in &unit.documents {
+        let doc_id = Uuid::parse_str(&doc.id).unwrap_or_else(|_| Uuid::new_v4());
+        let href = &doc.text.reference.value;
+        let checksum = &doc.text.checksum;
+        let alg = &doc.text.checksum_algorithm;
+        let title = &doc.title.value;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO documents
+            (id, submission_unit_id, xlink_href, checksum, checksum_algorithm, title, blob_sha256, media_type)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            doc_id,
+            unit_id,
+            href,
+            checksum,
+            alg,
+            title,
+            checksum.to_lowercase(),
+            doc.text.media_type
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    // ---------------------------------------------------------
+    // LEVEL 3: Insert Keyword Definitions
+    // ---------------------------------------------------------
+    if let Some(definitions) = &unit.keyword_definitions {
+        for def in definitions {
+            let val = &def.value.item;
+            sqlx::query!(
+                r#"
+                INSERT INTO keyword_definitions
+                (submission_unit_id, code, code_system, display_name, status_code)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+                unit_id,
+                def.code,
+                def.code_system,
+                val.display_name.value,
+                def.status_code.as_deref().unwrap_or("active")
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+    }
+
+    // ---------------------------------------------------------
+    // LEVEL 4: Insert Context of Use and its keywords
+    // ---------------------------------------------------------
+    for cou in &unit.context_of_use {
+        let cou_id = Uuid::parse_str(&cou.id).unwrap_or_else(|_| Uuid::new_v4());
+        let doc_ref_id = cou.document_reference.as_ref()
+            .map(|d| Uuid::parse_str(&d.id.root).unwrap_or(Uuid::nil()));
+        // Lifecycle: the context of use this one replaces (from an earlier unit)
+        let replaces_id = cou.related_context_of_use.as_ref()
+            .and_then(|r| Uuid::parse_str(&r.id.root).ok());
+
+        sqlx::query!(
+            r#"
+            INSERT INTO contexts_of_use
+            (id, submission_unit_id, code, code_system, status_code, priority_number, document_reference_id, replaces_context_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            cou_id,
+            unit_id,
+            cou.code,
+            cou.code_system,
+            cou.status_code,
+            cou.priority_number.value as i32,
+            doc_ref_id,
+            replaces_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        keyword::insert_context_keywords(tx, cou_id, &cou.keywords).await?;
+    }
+
+    Ok(unit_id)
+}
+
 // =================================================================
 // THE MAP (Internal Structs & Converters)
 // =================================================================
@@ -298,7 +605,7 @@ struct DocumentRow {
     checksum: String,
     checksum_algorithm: Option<String>,
     title: String,
-    media_type: Option<String>,
+    media_type: String,
 }
 
 impl Into<Document> for DocumentRow {
@@ -310,7 +617,7 @@ impl Into<Document> for DocumentRow {
                 reference: DocumentReferencePath { value: self.xlink_href },
                 checksum: self.checksum,
                 checksum_algorithm: self.checksum_algorithm.unwrap_or_else(|| "SHA256".to_string()),
-                media_type: self.media_type.unwrap_or_else(|| "application/pdf".to_string()),
+                media_type: self.media_type,
             },
         }
     }
@@ -324,6 +631,7 @@ struct ContextRow {
     status_code: String,
     priority_number: i32,
     document_reference_id: Option<Uuid>,
+    replaces_context_id: Option<Uuid>,
 }
 
 impl Into<ContextOfUse> for ContextRow {
@@ -337,7 +645,10 @@ impl Into<ContextOfUse> for ContextRow {
             document_reference: self.document_reference_id.map(|id| DocumentReference {
                 id: DocumentIdRef { root: id.to_string() }
             }),
-            related_context_of_use: None,
+            related_context_of_use: self.replaces_context_id.map(|id| RelatedContextOfUse {
+                id: DocumentIdRef { root: id.to_string() },
+                relationship_name: "replaces".to_string(),
+            }),
             keywords: vec![],
         }
     }
@@ -348,6 +659,7 @@ struct KeywordRow {
     code: String,
     code_system: String,
     display_name: String,
+    status_code: String,
 }
 
 impl Into<KeywordDefinition> for KeywordRow {
@@ -361,6 +673,7 @@ impl Into<KeywordDefinition> for KeywordRow {
                     display_name: DisplayName { value: self.display_name },
                 },
             },
+            status_code: Some(self.status_code),
         }
     }
 }
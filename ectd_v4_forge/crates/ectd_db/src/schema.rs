@@ -1,44 +1,262 @@
 use rust_embed::RustEmbed;
-use sqlx::{PgPool, Executor};
+use sha2::{Digest, Sha256};
+use sqlx::{Acquire, Executor, PgConnection, PgPool};
 use std::str;
+use thiserror::Error;
+use time::OffsetDateTime;
 
 #[derive(RustEmbed)]
 #[folder = "schema/"]
 struct SchemaAssets;
 
-/// Reads the build order and applies all SQL files in a single transaction.
-pub async fn rebuild_database(pool: &PgPool) -> Result<(), sqlx::Error> {
-    let mut tx = pool.begin().await?;
+/// Arbitrary but stable key for `pg_advisory_lock`, so two processes never migrate at once.
+const MIGRATION_LOCK_KEY: i64 = 0x6563_7464_0004;
 
-    // 1. Read the Manifest
-    let manifest = get_file_content("00_build_order.sql")
-        .expect("Missing 00_build_order.sql");
+const LEDGER_DDL: &str = r#"
+CREATE TABLE IF NOT EXISTS schema_migrations (
+    version BIGINT PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    checksum CHAR(64) NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+"#;
+
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("invalid migration asset '{0}': expected migrations/NNNN_name.sql")]
+    InvalidAsset(String),
+
+    #[error("missing schema file '{0}'")]
+    MissingFile(String),
+
+    #[error("schema drift detected: {}", describe_drift(.0))]
+    Drift(Vec<MigrationStatus>),
+
+    #[error("{0} pending migration(s); run `ectd_cli db migrate`")]
+    Pending(usize),
+}
+
+/// A single forward-only migration compiled into the binary.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: i64,
+    pub name: String,
+    /// SHA-256 of the fully expanded script (after `@include` resolution).
+    pub checksum: String,
+    sql: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationState {
+    /// Applied and the checksum still matches the embedded script.
+    Applied,
+    /// Embedded in the binary but not yet applied.
+    Pending,
+    /// Applied, but the embedded script has since been edited.
+    Modified,
+    /// Recorded in the ledger but unknown to this binary (database is ahead).
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+    pub applied_at: Option<OffsetDateTime>,
+}
+
+impl MigrationStatus {
+    pub fn is_drift(&self) -> bool {
+        matches!(self.state, MigrationState::Modified | MigrationState::Unknown)
+    }
+}
+
+/// Loads every `migrations/NNNN_name.sql` asset, sorted by version.
+pub fn migrations() -> Result<Vec<Migration>, MigrationError> {
+    let mut migrations = Vec::new();
+
+    for path in SchemaAssets::iter() {
+        let Some(file) = path.strip_prefix("migrations/") else { continue };
+        let (version, name) = parse_migration_name(file)
+            .ok_or_else(|| MigrationError::InvalidAsset(path.to_string()))?;
+
+        let sql = expand_script(&path)?;
+        let checksum = hex::encode(Sha256::digest(sql.as_bytes()));
+
+        migrations.push(Migration { version, name, checksum, sql });
+    }
+
+    migrations.sort_by_key(|m| m.version);
+    Ok(migrations)
+}
+
+/// Compares the embedded migrations against the `schema_migrations` ledger.
+pub async fn status(pool: &PgPool) -> Result<Vec<MigrationStatus>, MigrationError> {
+    let mut conn = pool.acquire().await?;
+    ledger_status(&mut conn, &migrations()?).await
+}
+
+/// Applies all pending migrations, each in its own transaction.
+/// Refuses to touch the database if an applied script has drifted.
+/// Returns the migrations that were applied by this call.
+pub async fn migrate(pool: &PgPool) -> Result<Vec<Migration>, MigrationError> {
+    let migrations = migrations()?;
+    let mut conn = pool.acquire().await?;
+
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut *conn)
+        .await?;
+
+    let result = apply_pending(&mut conn, &migrations).await;
+
+    sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut *conn)
+        .await?;
+
+    result
+}
+
+/// Startup guard for long-running processes: errors on drift or pending migrations.
+pub async fn ensure_current(pool: &PgPool) -> Result<(), MigrationError> {
+    let statuses = status(pool).await?;
+
+    let drifted: Vec<_> = statuses.iter().filter(|s| s.is_drift()).cloned().collect();
+    if !drifted.is_empty() {
+        return Err(MigrationError::Drift(drifted));
+    }
+
+    let pending = statuses.iter().filter(|s| s.state == MigrationState::Pending).count();
+    if pending > 0 {
+        return Err(MigrationError::Pending(pending));
+    }
+
+    Ok(())
+}
+
+async fn apply_pending(
+    conn: &mut PgConnection,
+    migrations: &[Migration],
+) -> Result<Vec<Migration>, MigrationError> {
+    let statuses = ledger_status(conn, migrations).await?;
+
+    let drifted: Vec<_> = statuses.iter().filter(|s| s.is_drift()).cloned().collect();
+    if !drifted.is_empty() {
+        return Err(MigrationError::Drift(drifted));
+    }
+
+    let mut applied = Vec::new();
+    for migration in migrations {
+        let pending = statuses
+            .iter()
+            .any(|s| s.version == migration.version && s.state == MigrationState::Pending);
+        if !pending {
+            continue;
+        }
+
+        println!("   📄 Applying: {:04}_{}", migration.version, migration.name);
+
+        let mut tx = conn.begin().await?;
+        tx.execute(migration.sql.as_str()).await?;
+        sqlx::query("INSERT INTO schema_migrations (version, name, checksum) VALUES ($1, $2, $3)")
+            .bind(migration.version)
+            .bind(&migration.name)
+            .bind(&migration.checksum)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        applied.push(migration.clone());
+    }
+
+    Ok(applied)
+}
+
+async fn ledger_status(
+    conn: &mut PgConnection,
+    migrations: &[Migration],
+) -> Result<Vec<MigrationStatus>, MigrationError> {
+    conn.execute(LEDGER_DDL).await?;
+
+    let recorded: Vec<(i64, String, String, OffsetDateTime)> = sqlx::query_as(
+        "SELECT version, name, checksum, applied_at FROM schema_migrations ORDER BY version",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut statuses: Vec<MigrationStatus> = migrations
+        .iter()
+        .map(|m| match recorded.iter().find(|(v, ..)| *v == m.version) {
+            Some((_, _, checksum, applied_at)) => MigrationStatus {
+                version: m.version,
+                name: m.name.clone(),
+                state: if *checksum == m.checksum {
+                    MigrationState::Applied
+                } else {
+                    MigrationState::Modified
+                },
+                applied_at: Some(*applied_at),
+            },
+            None => MigrationStatus {
+                version: m.version,
+                name: m.name.clone(),
+                state: MigrationState::Pending,
+                applied_at: None,
+            },
+        })
+        .collect();
+
+    for (version, name, _, applied_at) in recorded {
+        if !migrations.iter().any(|m| m.version == version) {
+            statuses.push(MigrationStatus {
+                version,
+                name,
+                state: MigrationState::Unknown,
+                applied_at: Some(applied_at),
+            });
+        }
+    }
+
+    statuses.sort_by_key(|s| s.version);
+    Ok(statuses)
+}
+
+/// Resolves `-- @include folder/file.sql` directives; other comment lines, in
+/// the migration and in the files it includes alike, are dropped so that
+/// editing commentary never changes a migration's checksum.
+fn expand_script(path: &str) -> Result<String, MigrationError> {
+    let manifest = get_file_content(path)
+        .ok_or_else(|| MigrationError::MissingFile(path.to_string()))?;
 
-    // 2. Parse and Aggregate SQL
     let mut full_script = String::new();
 
     for line in manifest.lines() {
-        let trimmed = line.trim();
-
-        // Parse: -- @include folder/file.sql
-        if let Some(path) = parse_include_directive(trimmed) {
-            println!("   📄 Including: {}", path);
-            let content = get_file_content(path)
-                .expect(&format!("Missing included file: {}", path));
-            full_script.push_str(&content);
+        if let Some(include) = parse_include_directive(line.trim()) {
+            let content = get_file_content(include)
+                .ok_or_else(|| MigrationError::MissingFile(include.to_string()))?;
+            full_script.push_str(&strip_comments(&content));
             full_script.push('\n');
-        } else if !trimmed.starts_with("--") {
-            // Keep normal lines (if any), ignore comments
+        } else if !is_comment(line) {
             full_script.push_str(line);
             full_script.push('\n');
         }
     }
 
-    // 3. Execute
-    tx.execute(full_script.as_str()).await?;
-    tx.commit().await?;
+    Ok(full_script)
+}
 
-    Ok(())
+/// `sql` without its `--` comment lines; everything else is kept byte for byte.
+fn strip_comments(sql: &str) -> String {
+    sql.split_inclusive('\n').filter(|line| !is_comment(line)).collect()
+}
+
+fn is_comment(line: &str) -> bool {
+    line.trim().starts_with("--")
 }
 
 fn get_file_content(path: &str) -> Option<String> {
@@ -53,3 +271,100 @@ fn parse_include_directive(line: &str) -> Option<&str> {
         None
     }
 }
+
+/// "0002_submission_unit_metadata.sql" -> (2, "submission_unit_metadata")
+fn parse_migration_name(file: &str) -> Option<(i64, String)> {
+    let stem = file.strip_suffix(".sql")?;
+    let (version, name) = stem.split_once('_')?;
+    Some((version.parse().ok()?, name.to_string()))
+}
+
+fn describe_drift(statuses: &[MigrationStatus]) -> String {
+    statuses
+        .iter()
+        .map(|s| format!("{:04}_{} ({:?})", s.version, s.name, s.state))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_migration_name() {
+        assert_eq!(
+            parse_migration_name("0002_submission_unit_metadata.sql"),
+            Some((2, "submission_unit_metadata".to_string()))
+        );
+        assert_eq!(parse_migration_name("baseline.sql"), None);
+        assert_eq!(parse_migration_name("0001_baseline.txt"), None);
+    }
+
+    #[test]
+    fn test_embedded_migrations_are_contiguous() {
+        // Forward-only: versions must start at 1 and never skip or repeat.
+        let migrations = migrations().expect("Embedded migrations must load");
+        assert!(!migrations.is_empty());
+
+        for (i, m) in migrations.iter().enumerate() {
+            assert_eq!(m.version, i as i64 + 1, "Gap or duplicate at {:04}_{}", m.version, m.name);
+            assert_eq!(m.checksum.len(), 64);
+            assert!(!m.sql.contains("@include"), "Unresolved include in {:04}_{}", m.version, m.name);
+        }
+    }
+
+    #[test]
+    fn test_comment_lines_are_dropped_wherever_they_come_from() {
+        let sql = "-- a table\nCREATE TABLE t (\n    -- the key\n    id INT\n);\n";
+        assert_eq!(strip_comments(sql), "CREATE TABLE t (\n    id INT\n);\n");
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_applications_migration_backfills_existing_units(pool: PgPool) {
+        let before_applications: Vec<_> = migrations().unwrap().into_iter().filter(|m| m.version < 11).collect();
+        let mut conn = pool.acquire().await.unwrap();
+        apply_pending(&mut conn, &before_applications).await.unwrap();
+
+        let first_id = uuid::Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO submission_units
+                (submission_id, sequence_number, code, code_system, status_code, created_at,
+                 application_id_uuid, application_code, application_number, applicant_name)
+            VALUES
+                (gen_random_uuid(), 1, 'original-application', 'oid', 'active', NOW() - INTERVAL '1 day',
+                 $1, 'ind', '123456', 'Acme'),
+                (gen_random_uuid(), 2, 'amendment', 'oid', 'active', NOW(),
+                 gen_random_uuid(), 'nda', '123456', 'Acme Pharma'),
+                (gen_random_uuid(), 1, 'original-application', 'oid', 'active', NOW(),
+                 gen_random_uuid(), 'bla', '654321', 'Beta Bio')
+            "#,
+        )
+        .bind(first_id)
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        drop(conn);
+
+        migrate(&pool).await.unwrap();
+
+        let applications: Vec<(String, uuid::Uuid, String, String, i64)> = sqlx::query_as(
+            r#"
+            SELECT a.application_number, a.id, a.application_type, a.applicant_name, COUNT(u.id)
+            FROM applications a
+            JOIN submission_units u ON u.application_id = a.id
+            GROUP BY a.id
+            ORDER BY a.application_number
+            "#,
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(applications.len(), 2);
+        // The id of the earliest sequence; type and applicant of the latest
+        assert_eq!(applications[0], ("123456".to_string(), first_id, "nda".to_string(), "Acme Pharma".to_string(), 2));
+        assert_eq!((applications[1].0.as_str(), applications[1].2.as_str(), applications[1].4), ("654321", "bla", 1));
+    }
+}
@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use ectd_core::text::ExtractedText;
+use crate::listing::{into_page, ListingError, Page, PageRequest, SubmissionFilter};
+
+/// Marks the matched terms in [`SearchHit::snippet`].
+pub const HIGHLIGHT_START: &str = "**";
+pub const HIGHLIGHT_STOP: &str = "**";
+
+/// A document matching a search, with the sequence and contexts of use it
+/// appears in. Best matches first.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub id: Uuid,
+    pub submission_unit_id: Uuid,
+    pub application_number: String,
+    pub sequence_number: i32,
+    pub href: String,
+    pub title: String,
+    pub media_type: String,
+    /// Codes of the document's unit's contexts of use that reference it
+    pub context_codes: Vec<String>,
+    /// Relevance; title matches weigh most, then keywords, then body text
+    pub rank: f32,
+    /// Passage around the matches (from the text, or the title when the
+    /// document has none), matches wrapped in `**`
+    pub snippet: String,
+}
+
+/// A stored document whose text has not been extracted yet.
+#[derive(Debug, Clone, FromRow)]
+pub struct UnindexedDocument {
+    pub sha256: String,
+    /// Object key in the document store
+    pub storage_key: String,
+    pub href: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SearchCursor {
+    #[serde(rename = "r")]
+    rank: f32,
+    #[serde(rename = "i")]
+    id: Uuid,
+}
+
+pub struct SearchRepository {
+    pool: PgPool,
+}
+
+impl SearchRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Whether text for this content was already extracted.
+    pub async fn has_text(&self, sha256: &str) -> Result<bool, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"SELECT EXISTS(SELECT 1 FROM document_texts WHERE sha256 = $1) AS "exists!""#,
+            sha256.to_lowercase()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.exists)
+    }
+
+    /// Stores extracted text; every document with this checksum becomes searchable by it.
+    pub async fn save_text(&self, sha256: &str, text: &ExtractedText) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO document_texts (sha256, content, page_count, truncated)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (sha256) DO UPDATE
+            SET content = EXCLUDED.content, page_count = EXCLUDED.page_count,
+                truncated = EXCLUDED.truncated, extracted_at = NOW()
+            "#,
+            sha256.to_lowercase(),
+            text.content,
+            text.page_count as i32,
+            text.truncated
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Documents of `media_type` without extracted text, one per distinct content.
+    pub async fn unindexed(&self, media_type: &str) -> Result<Vec<UnindexedDocument>, sqlx::Error> {
+        sqlx::query_as!(
+            UnindexedDocument,
+            r#"
+            SELECT DISTINCT ON (LOWER(d.checksum))
+                   LOWER(d.checksum) AS "sha256!",
+                   COALESCE(d.blob_sha256, d.id::text) AS "storage_key!",
+                   d.xlink_href AS href
+            FROM documents d
+            WHERE d.media_type = $1
+              AND NOT EXISTS (SELECT 1 FROM document_texts t WHERE t.sha256 = LOWER(d.checksum))
+            ORDER BY LOWER(d.checksum), d.blob_sha256 NULLS LAST
+            "#,
+            media_type
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Full-text search over titles, keywords and extracted text, in web
+    /// search syntax (`"exact phrase"`, `or`, `-excluded`). Best matches first.
+    pub async fn search(
+        &self,
+        query: &str,
+        filter: &SubmissionFilter,
+        page: &PageRequest,
+    ) -> Result<Page<SearchHit>, ListingError> {
+        let size = page.size();
+        let after: Option<SearchCursor> = page.after()?;
+        let options = format!(
+            "StartSel=\"{}\", StopSel=\"{}\", MaxWords=30, MinWords=10, MaxFragments=2",
+            HIGHLIGHT_START, HIGHLIGHT_STOP
+        );
+
+        // Rank and filter first, then build snippets for the page only
+        let rows = sqlx::query_as!(
+            SearchHit,
+            r#"
+            WITH q AS (SELECT websearch_to_tsquery('english', $1) AS query),
+            hits AS (
+                SELECT d.id, u.id AS unit_id, a.application_number, u.sequence_number,
+                       d.xlink_href, d.title, d.media_type, d.checksum,
+                       ts_rank_cd(d.search_vector, q.query) AS rank
+                FROM documents d
+                JOIN submission_units u ON u.id = d.submission_unit_id
+                JOIN applications a ON a.id = u.application_id
+                CROSS JOIN q
+                WHERE d.search_vector @@ q.query
+                  AND ($2::text IS NULL OR a.application_number = $2)
+                  AND ($3::text IS NULL OR a.applicant_name ILIKE $3)
+                  AND ($4::int IS NULL OR u.sequence_number >= $4)
+                  AND ($5::int IS NULL OR u.sequence_number <= $5)
+                  AND ($6::text IS NULL OR u.status_code = $6)
+                  AND ($7::text IS NULL OR EXISTS (
+                        SELECT 1 FROM contexts_of_use c
+                        WHERE c.document_reference_id = d.id AND c.submission_unit_id = u.id AND c.code = $7))
+                  AND ($8::timestamptz IS NULL OR u.created_at >= $8)
+                  AND ($9::timestamptz IS NULL OR u.created_at < $9)
+                  AND ($10::text[] IS NULL OR a.application_number = ANY($10))
+            ),
+            page AS (
+                SELECT * FROM hits
+                WHERE $12::uuid IS NULL OR hits.rank < $11 OR (hits.rank = $11 AND hits.id > $12)
+                ORDER BY hits.rank DESC, hits.id
+                LIMIT $13
+            )
+            SELECT p.id AS "id!", p.unit_id AS "submission_unit_id!",
+                   p.application_number AS "application_number!", p.sequence_number AS "sequence_number!",
+                   p.xlink_href AS "href!", p.title AS "title!", p.media_type AS "media_type!",
+                   ARRAY(SELECT c.code::text FROM contexts_of_use c
+                         WHERE c.document_reference_id = p.id AND c.submission_unit_id = p.unit_id
+                         ORDER BY c.code) AS "context_codes!",
+                   p.rank AS "rank!",
+                   ts_headline('english', COALESCE(NULLIF(t.content, ''), p.title), q.query, $14) AS "snippet!"
+            FROM page p
+            CROSS JOIN q
+            LEFT JOIN document_texts t ON t.sha256 = LOWER(p.checksum)
+            ORDER BY p.rank DESC, p.id
+            "#,
+            query,
+            filter.application_number,
+            filter.applicant_pattern(),
+            filter.sequence_from,
+            filter.sequence_to,
+            filter.status_code,
+            filter.context_code,
+            filter.created_after,
+            filter.created_before,
+            filter.applications.as_deref(),
+            after.as_ref().map(|c| c.rank),
+            after.as_ref().map(|c| c.id),
+            size + 1,
+            options
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(into_page(rows, size, |h| SearchCursor { rank: h.rank, id: h.id }))
+    }
+}
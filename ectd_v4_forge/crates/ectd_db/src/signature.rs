@@ -0,0 +1,111 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use std::fmt;
+use std::str::FromStr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// The meaning of a signature, as required by 21 CFR 11.50.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureMeaning {
+    Author,
+    Review,
+    Approve,
+}
+
+impl SignatureMeaning {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignatureMeaning::Author => "author",
+            SignatureMeaning::Review => "review",
+            SignatureMeaning::Approve => "approve",
+        }
+    }
+}
+
+impl fmt::Display for SignatureMeaning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SignatureMeaning {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "author" => Ok(SignatureMeaning::Author),
+            "review" => Ok(SignatureMeaning::Review),
+            "approve" => Ok(SignatureMeaning::Approve),
+            other => Err(format!("Unknown signature meaning '{}' (expected author, review or approve)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureRecord {
+    pub id: Uuid,
+    pub submission_unit_id: Uuid,
+    pub signer: String,
+    pub meaning: String,
+    pub content_digest: String,
+    pub comment: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub signed_at: OffsetDateTime,
+}
+
+pub struct SignatureRepository {
+    pool: PgPool,
+}
+
+impl SignatureRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn add_signature(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        unit_id: Uuid,
+        signer: &str,
+        meaning: SignatureMeaning,
+        content_digest: &str,
+        comment: Option<&str>,
+    ) -> Result<SignatureRecord, sqlx::Error> {
+        sqlx::query_as!(
+            SignatureRecord,
+            r#"
+            INSERT INTO submission_signatures
+            (id, submission_unit_id, signer, meaning, content_digest, comment)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, submission_unit_id, signer, meaning, content_digest, comment, signed_at
+            "#,
+            Uuid::new_v4(),
+            unit_id,
+            signer,
+            meaning.as_str(),
+            content_digest,
+            comment
+        )
+        .fetch_one(&mut **tx)
+        .await
+    }
+
+    /// All signatures ever applied to the unit, oldest first (including stale ones).
+    pub async fn list_signatures(&self, unit_id: Uuid) -> Result<Vec<SignatureRecord>, sqlx::Error> {
+        sqlx::query_as!(
+            SignatureRecord,
+            r#"
+            SELECT id, submission_unit_id, signer, meaning, content_digest, comment, signed_at
+            FROM submission_signatures
+            WHERE submission_unit_id = $1
+            ORDER BY signed_at ASC
+            "#,
+            unit_id
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+}
@@ -0,0 +1,183 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A nonclinical or clinical study of an application. Its documents are the
+/// contexts of use tagged with its study-id keyword.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Study {
+    pub id: Uuid,
+    /// The study-id keyword code, e.g. "ABC-101"
+    pub study_id: String,
+    pub title: String,
+    /// e.g. "pk", "efficacy"
+    pub categories: Vec<String>,
+    /// Context of use code of its report, protocol and appendices
+    pub report_code: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewStudy {
+    pub study_id: String,
+    pub title: String,
+    pub categories: Vec<String>,
+    pub report_code: String,
+}
+
+/// Editable study fields; `None` leaves a field as is.
+#[derive(Debug, Clone, Default)]
+pub struct StudyUpdate {
+    pub title: Option<String>,
+    pub categories: Option<Vec<String>>,
+    pub report_code: Option<String>,
+}
+
+/// A context of use tagged with a study's keyword, in whichever sequence.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StudyDocument {
+    pub sequence_number: i32,
+    pub submission_unit_id: Uuid,
+    pub context_of_use_id: Uuid,
+    pub code: String,
+    pub priority: i32,
+    pub status_code: String,
+    pub document_id: Option<Uuid>,
+    pub title: Option<String>,
+    pub href: Option<String>,
+}
+
+pub struct StudyRepository {
+    pool: PgPool,
+}
+
+impl StudyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns None when the application already has a study with this id,
+    /// or when no application has this number.
+    pub async fn create(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        application_number: &str,
+        study: &NewStudy,
+    ) -> Result<Option<Study>, sqlx::Error> {
+        sqlx::query_as!(
+            Study,
+            r#"
+            INSERT INTO studies (id, application_id, study_id, title, categories, report_code)
+            SELECT $1, a.id, $3, $4, $5, $6
+            FROM applications a
+            WHERE a.application_number = $2
+            ON CONFLICT (application_id, study_id) DO NOTHING
+            RETURNING id, study_id, title, categories, report_code, created_at
+            "#,
+            Uuid::new_v4(),
+            application_number,
+            study.study_id,
+            study.title,
+            &study.categories,
+            study.report_code
+        )
+        .fetch_optional(&mut **tx)
+        .await
+    }
+
+    pub async fn list(&self, application_number: &str) -> Result<Vec<Study>, sqlx::Error> {
+        sqlx::query_as!(
+            Study,
+            r#"
+            SELECT s.id, s.study_id, s.title, s.categories, s.report_code, s.created_at
+            FROM studies s
+            JOIN applications a ON a.id = s.application_id
+            WHERE a.application_number = $1
+            ORDER BY s.study_id
+            "#,
+            application_number
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get(&self, application_number: &str, study_id: &str) -> Result<Option<Study>, sqlx::Error> {
+        sqlx::query_as!(
+            Study,
+            r#"
+            SELECT s.id, s.study_id, s.title, s.categories, s.report_code, s.created_at
+            FROM studies s
+            JOIN applications a ON a.id = s.application_id
+            WHERE a.application_number = $1 AND s.study_id = $2
+            "#,
+            application_number,
+            study_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Returns None when there is no such study.
+    pub async fn update(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        application_number: &str,
+        study_id: &str,
+        update: &StudyUpdate,
+    ) -> Result<Option<Study>, sqlx::Error> {
+        sqlx::query_as!(
+            Study,
+            r#"
+            UPDATE studies s SET
+                title = COALESCE($3, s.title),
+                categories = COALESCE($4, s.categories),
+                report_code = COALESCE($5, s.report_code)
+            FROM applications a
+            WHERE a.id = s.application_id AND a.application_number = $1 AND s.study_id = $2
+            RETURNING s.id, s.study_id, s.title, s.categories, s.report_code, s.created_at
+            "#,
+            application_number,
+            study_id,
+            update.title,
+            update.categories.as_deref(),
+            update.report_code
+        )
+        .fetch_optional(&mut **tx)
+        .await
+    }
+
+    /// The contexts of use of the application tagged with `keyword`, by
+    /// sequence, code and priority.
+    pub async fn documents(
+        &self,
+        application_number: &str,
+        code_system: &str,
+        keyword: &str,
+    ) -> Result<Vec<StudyDocument>, sqlx::Error> {
+        sqlx::query_as!(
+            StudyDocument,
+            r#"
+            SELECT u.sequence_number, u.id AS submission_unit_id,
+                   c.id AS context_of_use_id, c.code, c.priority_number AS priority, c.status_code,
+                   d.id AS "document_id?", d.title AS "title?", d.xlink_href AS "href?"
+            FROM context_keywords ck
+            JOIN contexts_of_use c ON c.id = ck.context_of_use_id
+            JOIN submission_units u ON u.id = c.submission_unit_id
+            JOIN applications a ON a.id = u.application_id
+            LEFT JOIN documents d ON d.id = c.document_reference_id
+            WHERE a.application_number = $1 AND ck.code_system = $2 AND ck.code = $3
+            ORDER BY u.sequence_number, c.code, c.priority_number, c.id
+            "#,
+            application_number,
+            code_system,
+            keyword
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use ectd_core::models::keyword_definition::KeywordType;
+
+/// A kind of sequence (e.g. "NDA original"): what a unit created from it is
+/// called and which contexts of use and keywords it starts with.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SubmissionTemplate {
+    /// Lowercase letters, digits and dashes, e.g. "nda-original"
+    pub name: String,
+    pub description: Option<String>,
+    /// Only applications of this type may use it (nda, ind, ...); any when absent
+    pub application_type: Option<String>,
+    /// Code of the units created from it, e.g. "original-application"
+    pub unit_code: String,
+    /// Contexts of use planned as placeholders, in order
+    #[serde(default)]
+    pub contexts: Vec<TemplateContext>,
+    /// Keyword definitions every unit starts with
+    #[serde(default)]
+    pub keywords: Vec<TemplateKeyword>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TemplateContext {
+    pub code: String,
+    /// Expected document title
+    pub title: Option<String>,
+    pub priority: Option<u32>,
+    /// Optional contexts don't hold up the export; defaults to true
+    #[serde(default = "required_by_default")]
+    pub required: bool,
+    /// Package folder for the document, instead of the default for its code
+    pub folder: Option<String>,
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TemplateKeyword {
+    /// Defaults to "sponsor"
+    #[serde(rename = "type", default)]
+    pub keyword_type: KeywordType,
+    pub code: String,
+    pub display_name: String,
+}
+
+/// What the `definition` column holds: the template minus its name and description.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Definition {
+    application_type: Option<String>,
+    unit_code: String,
+    #[serde(default)]
+    contexts: Vec<TemplateContext>,
+    #[serde(default)]
+    keywords: Vec<TemplateKeyword>,
+}
+
+struct TemplateRow {
+    name: String,
+    description: Option<String>,
+    definition: Json<Definition>,
+}
+
+impl From<TemplateRow> for SubmissionTemplate {
+    fn from(row: TemplateRow) -> Self {
+        let Json(d) = row.definition;
+        SubmissionTemplate {
+            name: row.name,
+            description: row.description,
+            application_type: d.application_type,
+            unit_code: d.unit_code,
+            contexts: d.contexts,
+            keywords: d.keywords,
+        }
+    }
+}
+
+pub struct TemplateRepository {
+    pool: PgPool,
+}
+
+impl TemplateRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list(&self) -> Result<Vec<SubmissionTemplate>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            TemplateRow,
+            r#"SELECT name, description, definition AS "definition: Json<Definition>" FROM submission_templates ORDER BY name"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get(&self, name: &str) -> Result<Option<SubmissionTemplate>, sqlx::Error> {
+        let row = sqlx::query_as!(
+            TemplateRow,
+            r#"SELECT name, description, definition AS "definition: Json<Definition>" FROM submission_templates WHERE name = $1"#,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// Creates or replaces the template; returns true when it is new.
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        template: &SubmissionTemplate,
+    ) -> Result<bool, sqlx::Error> {
+        let definition = Json(Definition {
+            application_type: template.application_type.clone(),
+            unit_code: template.unit_code.clone(),
+            contexts: template.contexts.clone(),
+            keywords: template.keywords.clone(),
+        });
+        let created = sqlx::query_scalar!(
+            r#"
+            INSERT INTO submission_templates (name, description, definition)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (name) DO UPDATE
+            SET description = EXCLUDED.description, definition = EXCLUDED.definition, updated_at = NOW()
+            RETURNING (xmax = 0) AS "created!"
+            "#,
+            template.name,
+            template.description,
+            definition as _
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        Ok(created)
+    }
+
+    /// Returns false when there is no such template.
+    pub async fn delete(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        name: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM submission_templates WHERE name = $1", name)
+            .execute(&mut **tx)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{EctdService, ServiceError};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use utoipa::ToSchema;
+use serde_json::{json, Value};
+use ectd_db::application::{
+    ApplicationRecord, ApplicationRepository, ApplicationUpdate, NewApplication, RelatedApplication,
+};
+
+/// Region used when none is given.
+pub const DEFAULT_REGION: &str = "us";
+
+/// A new application. Type and region are stored in lowercase.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NewApplicationParams {
+    pub application_number: String,
+    /// nda, ind, bla, anda, dmf, …
+    pub application_type: String,
+    /// Defaults to "us"
+    pub region: Option<String>,
+    /// Sponsoring organization named in every unit
+    pub applicant_name: String,
+    pub product_name: Option<String>,
+    #[serde(default)]
+    pub related_applications: Vec<RelatedApplication>,
+}
+
+/// Changes to an application; absent fields are left untouched.
+/// `relatedApplications` replaces the whole list.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ApplicationPatch {
+    pub application_type: Option<String>,
+    pub region: Option<String>,
+    pub applicant_name: Option<String>,
+    pub product_name: Option<String>,
+    pub related_applications: Option<Vec<RelatedApplication>>,
+}
+
+impl EctdService {
+    pub async fn create_application(&self, params: NewApplicationParams) -> Result<ApplicationRecord> {
+        let app = NewApplication {
+            application_number: params.application_number.trim().to_string(),
+            application_type: params.application_type.trim().to_lowercase(),
+            region: params.region.as_deref().unwrap_or(DEFAULT_REGION).trim().to_lowercase(),
+            applicant_name: params.applicant_name.trim().to_string(),
+            product_name: params.product_name,
+            related_applications: params.related_applications,
+        };
+        for (field, value) in [
+            ("applicationNumber", &app.application_number),
+            ("applicationType", &app.application_type),
+            ("region", &app.region),
+            ("applicantName", &app.applicant_name),
+        ] {
+            if value.is_empty() {
+                return Err(ServiceError::Invalid(format!("{} must not be empty", field)).into());
+            }
+        }
+        check_related(&app.application_number, &app.related_applications)?;
+
+        let repo = ApplicationRepository::new(self.pool.clone());
+        let mut tx = self.pool.begin().await?;
+        let Some(record) = repo.create(&mut tx, &app).await.context("Failed to create application")? else {
+            return Err(ServiceError::Conflict(format!("Application {} already exists", app.application_number)).into());
+        };
+        self.record_audit(
+            &mut tx,
+            "application.create",
+            "application",
+            Some(record.id),
+            None,
+            Some(application_state(&record)),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(record)
+    }
+
+    pub async fn get_application(&self, application_number: &str) -> Result<ApplicationRecord> {
+        let repo = ApplicationRepository::new(self.pool.clone());
+        repo.get(application_number).await
+            .context("Failed to fetch application")?
+            .ok_or_else(|| ServiceError::application_not_found(application_number).into())
+    }
+
+    /// Changes the application; every unit of it (including those already
+    /// created) carries the new type and applicant from then on.
+    pub async fn update_application(&self, application_number: &str, patch: ApplicationPatch) -> Result<ApplicationRecord> {
+        for (field, value) in [
+            ("applicationType", &patch.application_type),
+            ("region", &patch.region),
+            ("applicantName", &patch.applicant_name),
+        ] {
+            if value.as_deref().is_some_and(|v| v.trim().is_empty()) {
+                return Err(ServiceError::Invalid(format!("{} must not be empty", field)).into());
+            }
+        }
+        if let Some(related) = &patch.related_applications {
+            check_related(application_number, related)?;
+        }
+
+        let before = self.get_application(application_number).await?;
+
+        let repo = ApplicationRepository::new(self.pool.clone());
+        let update = ApplicationUpdate {
+            application_type: patch.application_type.map(|t| t.trim().to_lowercase()),
+            region: patch.region.map(|r| r.trim().to_lowercase()),
+            applicant_name: patch.applicant_name.map(|a| a.trim().to_string()),
+            product_name: patch.product_name,
+            related_applications: patch.related_applications,
+        };
+        let mut tx = self.pool.begin().await?;
+        let Some(after) = repo.update(&mut tx, application_number, &update).await.context("Failed to update application")? else {
+            return Err(ServiceError::application_not_found(application_number).into());
+        };
+        self.record_audit(
+            &mut tx,
+            "application.update",
+            "application",
+            Some(after.id),
+            Some(application_state(&before)),
+            Some(application_state(&after)),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(after)
+    }
+}
+
+fn check_related(application_number: &str, related: &[RelatedApplication]) -> Result<()> {
+    for (i, r) in related.iter().enumerate() {
+        if r.application_number.trim().is_empty() || r.relationship.trim().is_empty() {
+            return Err(ServiceError::Invalid(
+                "related applications need an applicationNumber and a relationship".to_string()
+            ).into());
+        }
+        if r.application_number == application_number {
+            return Err(ServiceError::Invalid("an application can't be related to itself".to_string()).into());
+        }
+        if related[..i].iter().any(|other| other.application_number == r.application_number) {
+            return Err(ServiceError::Invalid(format!(
+                "related application {} is listed twice", r.application_number
+            )).into());
+        }
+    }
+    Ok(())
+}
+
+fn application_state(app: &ApplicationRecord) -> Value {
+    json!({
+        "applicationNumber": app.application_number,
+        "applicationType": app.application_type,
+        "region": app.region,
+        "applicant": app.applicant_name,
+        "productName": app.product_name,
+        "relatedApplications": app.related_applications,
+    })
+}
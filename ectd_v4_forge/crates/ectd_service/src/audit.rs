@@ -0,0 +1,86 @@
+use crate::EctdService;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use uuid::Uuid;
+use ectd_db::audit::{self, AuditQuery, AuditRecord, AuditRepository, ChainVerification, NewAuditEntry};
+
+impl EctdService {
+    /// Appends a record to the audit trail on behalf of the service's current
+    /// actor, in the transaction that makes the change it describes.
+    pub(crate) async fn record_audit(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        action: &str,
+        entity_type: &str,
+        entity_id: Option<Uuid>,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) -> Result<()> {
+        audit::append(tx, NewAuditEntry {
+            actor: self.actor.clone(),
+            action: action.to_string(),
+            entity_type: entity_type.to_string(),
+            entity_id,
+            before,
+            after,
+            reason: self.reason.clone(),
+        })
+        .await
+        .context("Failed to write audit record")?;
+
+        Ok(())
+    }
+
+    /// Like `record_audit`, for an event that changes nothing in the database
+    /// (an export, objects removed from the store).
+    pub(crate) async fn record_event(
+        &self,
+        action: &str,
+        entity_type: &str,
+        entity_id: Option<Uuid>,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        self.record_audit(&mut tx, action, entity_type, entity_id, before, after).await?;
+        tx.commit().await.context("Failed to write audit record")
+    }
+
+    pub async fn audit_trail(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>> {
+        let repo = AuditRepository::new(self.pool.clone());
+        repo.list(query).await.context("Failed to read audit trail")
+    }
+
+    pub async fn verify_audit_chain(&self) -> Result<ChainVerification> {
+        let repo = AuditRepository::new(self.pool.clone());
+        repo.verify_chain().await.context("Failed to verify audit chain")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::DocumentReuse;
+
+    #[sqlx::test(migrations = false)]
+    async fn test_unit_history_includes_its_documents(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let unit = testing::new_unit(&service, "100001").await;
+        let other = testing::new_unit(&service, "100002").await;
+
+        let cover = testing::file("cover.txt", "cover letter");
+        let attached = testing::attach(&service, unit, &cover, "cover-letter", DocumentReuse::Ask).await.unwrap();
+        let form = testing::file("form.txt", "form 356h");
+        testing::attach(&service, other, &form, "form", DocumentReuse::Ask).await.unwrap();
+
+        let history = service.audit_trail(&AuditQuery { submission_unit_id: Some(unit), ..Default::default() }).await.unwrap();
+        let entries: Vec<(&str, Option<Uuid>)> = history.iter()
+            .map(|r| (r.action.as_str(), r.entity_id))
+            .collect();
+        assert_eq!(entries, [
+            ("submission.create", Some(unit)),
+            ("document.attach", Some(attached.document_id)),
+        ]);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::EctdService;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+use ectd_core::media_type;
+use ectd_db::blob::BlobRepository;
+
+/// A file stored by content. `deduplicated` means the bytes were already in the store.
+#[derive(Debug, Clone)]
+pub struct StoredBlob {
+    pub sha256: String,
+    pub size_bytes: u64,
+    pub deduplicated: bool,
+}
+
+/// What `collect_garbage` removed (or would remove, on a dry run).
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    /// Registered blobs no document references any more.
+    pub unreferenced_blobs: Vec<String>,
+    /// Objects in the store the database has no record of (e.g. a crashed upload).
+    pub orphaned_objects: Vec<String>,
+    pub bytes_reclaimed: u64,
+    pub dry_run: bool,
+}
+
+/// Streams the file through SHA-256 (8KB chunks). Returns the lowercase hex digest and size.
+pub async fn hash_file(path: &Path) -> Result<(String, u64)> {
+    let mut file = File::open(path).await
+        .context(format!("Failed to open file: {:?}", path))?;
+
+    let mut hasher = Sha256::new();
+    let mut buffer = [0; 8192];
+    let mut size = 0;
+    loop {
+        let n = file.read(&mut buffer).await?;
+        if n == 0 { break; }
+        hasher.update(&buffer[..n]);
+        size += n as u64;
+    }
+
+    Ok((hex::encode(hasher.finalize()), size))
+}
+
+/// Detects the media type from the file's leading bytes (see `ectd_core::media_type`).
+pub async fn sniff_media_type(path: &Path) -> Result<Option<&'static str>> {
+    let mut file = File::open(path).await
+        .context(format!("Failed to open file: {:?}", path))?;
+
+    let mut head = Vec::with_capacity(media_type::SNIFF_LEN);
+    (&mut file).take(media_type::SNIFF_LEN as u64).read_to_end(&mut head).await?;
+
+    Ok(media_type::detect(&head))
+}
+
+impl EctdService {
+    /// Stores a file under its SHA-256 and registers the blob.
+    /// The upload is skipped when the store already holds the same content.
+    pub async fn store_blob(&self, path: &Path, sha256: &str, size_bytes: u64, content_type: &str) -> Result<StoredBlob> {
+        let sha256 = sha256.to_lowercase();
+
+        // The store, not the blobs table, is the truth for "are the bytes there":
+        // a registered blob whose upload was lost must be uploaded again.
+        let deduplicated = self.store.exists(&sha256).await?;
+        if !deduplicated {
+            self.store.put_file(&sha256, path, content_type).await
+                .context("Upload to document store failed")?;
+        }
+
+        BlobRepository::new(self.pool.clone())
+            .register(&sha256, size_bytes as i64)
+            .await
+            .context("Failed to register blob")?;
+
+        Ok(StoredBlob { sha256, size_bytes, deduplicated })
+    }
+
+    /// Saga compensation: undoes uploads staged by an operation whose metadata
+    /// never committed. Objects that were already stored (deduplicated) or that
+    /// another document has claimed since are left alone.
+    /// Best-effort: anything left behind is picked up by `collect_garbage`.
+    pub(crate) async fn discard_staged(&self, staged: &[StoredBlob]) {
+        let repo = BlobRepository::new(self.pool.clone());
+
+        for blob in staged {
+            match repo.delete_if_unreferenced(&blob.sha256).await {
+                Ok(true) if !blob.deduplicated => {
+                    if let Err(e) = self.store.delete(&blob.sha256).await {
+                        eprintln!("⚠️  Compensation failed for {}: {} (run gc)", blob.sha256, e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("⚠️  Compensation failed for {}: {} (run gc)", blob.sha256, e),
+            }
+        }
+    }
+
+    /// Removes unreferenced blobs and orphaned objects older than `min_age`.
+    /// The grace period protects uploads that are still on their way to a commit.
+    pub async fn collect_garbage(&self, min_age: Duration, dry_run: bool) -> Result<GcReport> {
+        let repo = BlobRepository::new(self.pool.clone());
+        let mut report = GcReport { dry_run, ..GcReport::default() };
+
+        // 1. Blobs whose reference count dropped to zero
+        for blob in repo.unreferenced(min_age).await? {
+            if !dry_run {
+                if !repo.delete_if_unreferenced(&blob.sha256).await? {
+                    continue; // Re-attached since we looked
+                }
+                self.store.delete(&blob.sha256).await?;
+            }
+            report.bytes_reclaimed += blob.size_bytes as u64;
+            report.unreferenced_blobs.push(blob.sha256);
+        }
+
+        // 2. Objects the database has never heard of
+        let known = repo.known_keys().await?;
+        let now = SystemTime::now();
+        for obj in self.store.list().await? {
+            if known.contains(&obj.key) || report.unreferenced_blobs.contains(&obj.key) {
+                continue;
+            }
+            let old_enough = obj.modified
+                .and_then(|m| now.duration_since(m).ok())
+                .is_none_or(|age| age >= min_age);
+            if !old_enough {
+                continue;
+            }
+            if !dry_run {
+                self.store.delete(&obj.key).await?;
+            }
+            report.bytes_reclaimed += obj.size_bytes;
+            report.orphaned_objects.push(obj.key);
+        }
+
+        let removed = report.unreferenced_blobs.len() + report.orphaned_objects.len();
+        if !dry_run && removed > 0 {
+            self.record_event(
+                "storage.gc",
+                "blob",
+                None,
+                None,
+                Some(json!({
+                    "store": self.store.describe(),
+                    "unreferencedBlobs": report.unreferenced_blobs,
+                    "orphanedObjects": report.orphaned_objects,
+                    "bytesReclaimed": report.bytes_reclaimed,
+                })),
+            ).await?;
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::{AddDocumentParams, DocumentReuse};
+
+    async fn stage(service: &EctdService, path: &Path) -> StoredBlob {
+        let (sha256, size) = hash_file(path).await.unwrap();
+        service.store_blob(path, &sha256, size, "text/plain").await.unwrap()
+    }
+
+    async fn stored_keys(service: &EctdService) -> Vec<String> {
+        let mut keys: Vec<String> = service.store.list().await.unwrap().into_iter().map(|o| o.key).collect();
+        keys.sort();
+        keys
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_identical_content_is_stored_once(pool: sqlx::PgPool) {
+        let service = testing::service(pool.clone()).await;
+        let unit = testing::new_unit(&service, "100001").await;
+
+        let first = testing::file("a.txt", "same bytes");
+        let second = testing::file("b.txt", "same bytes");
+        testing::attach(&service, unit, &first, "cover-letter", DocumentReuse::Ask).await.unwrap();
+        testing::attach(&service, unit, &second, "form", DocumentReuse::Ask).await.unwrap();
+
+        let (sha256, _) = hash_file(&first).await.unwrap();
+        let blob = BlobRepository::new(pool).get(&sha256).await.unwrap().unwrap();
+        assert_eq!(blob.ref_count, 2);
+        assert_eq!(stored_keys(&service).await, [sha256]);
+        assert!(stage(&service, &second).await.deduplicated);
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_discard_removes_only_uploads_it_made(pool: sqlx::PgPool) {
+        let service = testing::service(pool.clone()).await;
+        let kept = testing::file("kept.txt", "already stored");
+        let fresh = testing::file("fresh.txt", "uploaded by the failed operation");
+
+        let earlier = stage(&service, &kept).await;
+        assert!(!earlier.deduplicated);
+        let deduplicated = stage(&service, &kept).await;
+        assert!(deduplicated.deduplicated);
+        let uploaded = stage(&service, &fresh).await;
+
+        service.discard_staged(&[deduplicated, uploaded.clone()]).await;
+
+        assert_eq!(stored_keys(&service).await, [earlier.sha256]);
+        assert!(BlobRepository::new(pool).get(&uploaded.sha256).await.unwrap().is_none());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_failed_batch_leaves_no_orphans(pool: sqlx::PgPool) {
+        let service = testing::service(pool.clone()).await;
+        let unit = testing::new_unit(&service, "100001").await;
+
+        let good = testing::file("cover.txt", "cover letter");
+        let broken = testing::file("form.pdf", "%PDF-1.7 not really a PDF");
+        let batch = [(good, "cover-letter"), (broken, "form")].map(|(path, code)| AddDocumentParams {
+            submission_id: unit,
+            file_path: path,
+            context_code: code.to_string(),
+            title: code.to_string(),
+            priority: None,
+            placeholder_id: None,
+            keywords: vec![],
+            folder: None,
+            reuse: DocumentReuse::Ask,
+        });
+        assert!(service.attach_documents(unit, batch.into()).await.is_err());
+
+        assert!(stored_keys(&service).await.is_empty());
+        assert!(BlobRepository::new(pool.clone()).known_keys().await.unwrap().is_empty());
+        assert!(service.get_submission(unit).await.unwrap().documents.is_empty());
+    }
+
+    const GRACE: Duration = Duration::from_secs(3600);
+
+    /// Makes every registered blob look `age` old to garbage collection.
+    async fn age_blobs(pool: &sqlx::PgPool, age: Duration) {
+        sqlx::query("UPDATE blobs SET touched_at = NOW() - make_interval(secs => $1)")
+            .bind(age.as_secs_f64())
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_gc_keeps_referenced_and_recent_blobs(pool: sqlx::PgPool) {
+        let service = testing::service(pool.clone()).await;
+        let unit = testing::new_unit(&service, "100001").await;
+
+        let cover = testing::file("cover.txt", "cover letter");
+        testing::attach(&service, unit, &cover, "cover-letter", DocumentReuse::Ask).await.unwrap();
+        age_blobs(&pool, GRACE * 2).await;
+        let recent = stage(&service, &testing::file("upload.txt", "still on its way")).await;
+
+        let report = service.collect_garbage(GRACE, false).await.unwrap();
+
+        assert!(report.unreferenced_blobs.is_empty());
+        assert!(report.orphaned_objects.is_empty());
+        assert_eq!(stored_keys(&service).await.len(), 2);
+        assert!(BlobRepository::new(pool).get(&recent.sha256).await.unwrap().is_some());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_gc_deletes_old_unreferenced_blobs(pool: sqlx::PgPool) {
+        let service = testing::service(pool.clone()).await;
+        let abandoned = stage(&service, &testing::file("upload.txt", "never committed")).await;
+        age_blobs(&pool, GRACE * 2).await;
+
+        let dry_run = service.collect_garbage(GRACE, true).await.unwrap();
+        assert_eq!(dry_run.unreferenced_blobs, [abandoned.sha256.as_str()]);
+        assert_eq!(stored_keys(&service).await, [abandoned.sha256.as_str()]);
+
+        let report = service.collect_garbage(GRACE, false).await.unwrap();
+        assert_eq!(report.unreferenced_blobs, [abandoned.sha256.as_str()]);
+        assert_eq!(report.bytes_reclaimed, abandoned.size_bytes);
+        assert!(stored_keys(&service).await.is_empty());
+        assert!(BlobRepository::new(pool).get(&abandoned.sha256).await.unwrap().is_none());
+    }
+}
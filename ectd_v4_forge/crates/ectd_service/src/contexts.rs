@@ -0,0 +1,106 @@
+use crate::keywords::KeywordRef;
+use crate::submission::check_status;
+use crate::{EctdService, ServiceError};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use utoipa::ToSchema;
+use serde_json::json;
+use uuid::Uuid;
+
+use ectd_core::models::context_of_use::ContextOfUse;
+use ectd_db::keyword::KeywordRepository;
+use ectd_db::repository::{ContextUpdate, SubmissionRepository};
+
+/// Changes to a Context of Use; absent fields are left untouched.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ContextPatch {
+    pub code: Option<String>,
+    pub status_code: Option<String>,
+    pub priority: Option<u32>,
+    /// Replaces the keywords the context is tagged with
+    pub keywords: Option<Vec<KeywordRef>>,
+}
+
+impl EctdService {
+    pub async fn list_contexts(&self, submission_id: Uuid) -> Result<Vec<ContextOfUse>> {
+        Ok(self.get_submission(submission_id).await?.context_of_use)
+    }
+
+    pub async fn update_context(&self, submission_id: Uuid, context_id: Uuid, patch: ContextPatch) -> Result<ContextOfUse> {
+        if let Some(status) = &patch.status_code {
+            check_status(status)?;
+        }
+        if patch.priority == Some(0) {
+            return Err(ServiceError::Invalid("priority must be 1 or greater".to_string()).into());
+        }
+        if patch.code.as_deref().is_some_and(|c| c.trim().is_empty()) {
+            return Err(ServiceError::Invalid("code must not be empty".to_string()).into());
+        }
+
+        let before = self.find_context(submission_id, context_id).await?;
+        let keywords = match &patch.keywords {
+            Some(refs) => Some(self.resolve_keywords(submission_id, refs).await?),
+            None => None,
+        };
+
+        let repo = SubmissionRepository::new(self.pool.clone());
+        let update = ContextUpdate {
+            code: patch.code,
+            status_code: patch.status_code,
+            priority_number: patch.priority,
+        };
+        let before_state = context_state(submission_id, &before);
+        let mut after = before;
+        if let Some(code) = &update.code {
+            after.code = code.clone();
+        }
+        if let Some(status) = &update.status_code {
+            after.status_code = status.clone();
+        }
+        if let Some(priority) = update.priority_number {
+            after.priority_number.value = priority;
+        }
+
+        let mut tx = self.pool.begin().await?;
+        repo.update_context(&mut tx, submission_id, context_id, &update).await
+            .context("Failed to update context of use")?;
+        if let Some(keywords) = keywords {
+            KeywordRepository::new(self.pool.clone())
+                .set_context_keywords(&mut tx, submission_id, context_id, &keywords).await
+                .context("Failed to update context keywords")?;
+            after.keywords = keywords;
+        }
+        self.record_audit(
+            &mut tx,
+            "context_of_use.update",
+            "context_of_use",
+            Some(context_id),
+            Some(before_state),
+            Some(context_state(submission_id, &after)),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(after)
+    }
+
+    pub(crate) async fn find_context(&self, submission_id: Uuid, context_id: Uuid) -> Result<ContextOfUse> {
+        let id = context_id.to_string();
+        self.list_contexts(submission_id).await?
+            .into_iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| ServiceError::NotFound(format!("Context of use {}", context_id)).into())
+    }
+}
+
+fn context_state(submission_id: Uuid, cou: &ContextOfUse) -> serde_json::Value {
+    json!({
+        "submissionUnitId": submission_id,
+        "code": cou.code,
+        "statusCode": cou.status_code,
+        "priority": cou.priority_number.value,
+        "keywords": cou.keywords.iter()
+            .map(|k| json!({ "code": k.code, "codeSystem": k.code_system }))
+            .collect::<Vec<_>>(),
+    })
+}
@@ -1,167 +1,581 @@
-use crate::EctdService;
+use crate::planning::check_folder;
+use crate::{EctdService, KeywordRef, ServiceError};
+use crate::blobs::{hash_file, sniff_media_type, StoredBlob};
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use futures::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use utoipa::ToSchema;
 use uuid::Uuid;
-use tokio::fs::File;
-use tokio::io::AsyncReadExt;
-use sha2::{Sha256, Digest};
-use aws_sdk_s3::primitives::ByteStream;
 
 use ectd_core::models::{
     document::{Document, DocumentTitle, DocumentText, DocumentReferencePath},
-    context_of_use::{ContextOfUse, PriorityNumber, DocumentReference, DocumentIdRef},
+    context_of_use::{ContextOfUse, PriorityNumber, DocumentReference, DocumentIdRef, Keyword},
     submission_unit::{SubmissionUnit, Submission, Application, Applicant, SequenceNumber, ApplicationNumber, SponsoringOrganization},
 };
 // Import the new helper
-use ectd_core::resolve_folder_path;
+use ectd_core::{media_type, resolve_folder_path};
 use ectd_core::validation::{ValidationEngine, rules_pdf::RuleEctd4_533};
+use ectd_db::listing::{DocumentSummary, ListingRepository};
 use ectd_db::repository::SubmissionRepository;
 
+/// Documents of a batch checked and uploaded at a time.
+const STAGE_CONCURRENCY: usize = 8;
+
+/// What to do when a file is identical to a document already submitted in an
+/// earlier sequence of the application.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum DocumentReuse {
+    /// Refuse, naming the earlier document, so the caller can choose
+    #[default]
+    Ask,
+    /// Reference the earlier document instead of sending the file again
+    Reference,
+    /// Send a new copy anyway
+    Copy,
+}
+
+impl DocumentReuse {
+    pub const ALL: [DocumentReuse; 3] = [DocumentReuse::Ask, DocumentReuse::Reference, DocumentReuse::Copy];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            DocumentReuse::Ask => "ask",
+            DocumentReuse::Reference => "reference",
+            DocumentReuse::Copy => "copy",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.code() == code)
+    }
+}
+
+impl std::str::FromStr for DocumentReuse {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_code(s.trim()).ok_or_else(|| format!(
+            "unknown reuse '{}' (one of: {})", s, Self::ALL.map(|r| r.code()).join(", ")
+        ))
+    }
+}
+
+/// A document as attached: a new one, or an earlier one its context references.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachedDocument {
+    pub document_id: Uuid,
+    pub context_of_use_id: Uuid,
+    /// The earlier document referenced instead of sending a new copy
+    pub reused: Option<DocumentSummary>,
+}
+
 #[derive(Debug)]
 pub struct AddDocumentParams {
     pub submission_id: Uuid,
-    pub file_path: std::path::PathBuf,
+    pub file_path: PathBuf,
+    pub context_code: String,
+    pub title: String,
+    /// None: the placeholder's priority, else the next after the code's
+    /// highest in the unit's current view
+    pub priority: Option<u32>,
+    /// The placeholder this document delivers; by default the unit's first open
+    /// placeholder of the same context code, if it has one
+    pub placeholder_id: Option<Uuid>,
+    /// Keywords to tag the context of use with; each must be defined for the application
+    pub keywords: Vec<KeywordRef>,
+    /// Package folder, instead of the placeholder's or the default for the context code
+    pub folder: Option<String>,
+    /// When the file was already submitted: ask (refuse), reference it, or copy it
+    pub reuse: DocumentReuse,
+}
+
+/// Where a document of a batch goes, resolved before its file is read.
+#[derive(Debug)]
+pub struct PlannedDocument {
+    pub file_path: PathBuf,
     pub context_code: String,
     pub title: String,
     pub priority: u32,
+    /// Package path of the file, unless it ends up referencing an earlier document
+    pub href: String,
+    /// The placeholder it delivers
+    pub placeholder_id: Option<Uuid>,
+    pub keywords: Vec<KeywordRef>,
+    submission_id: Uuid,
+    reuse: DocumentReuse,
+    resolved: Vec<Keyword>,
+}
+
+/// A document checked and uploaded (or an earlier one to reference), not
+/// yet recorded in its unit.
+struct StagedDocument {
+    content: StagedContent,
+    cou: ContextOfUse,
+    placeholder_id: Option<Uuid>,
+    keywords: Vec<KeywordRef>,
+}
+
+enum StagedContent {
+    New { doc: Document, blob: StoredBlob },
+    Reused(DocumentSummary),
+}
+
+impl StagedDocument {
+    fn into_blob(self) -> Option<StoredBlob> {
+        match self.content {
+            StagedContent::New { blob, .. } => Some(blob),
+            StagedContent::Reused(_) => None,
+        }
+    }
 }
 
 impl EctdService {
-    pub async fn attach_document(&self, params: AddDocumentParams) -> Result<Uuid> {
+    pub async fn attach_document(&self, params: AddDocumentParams) -> Result<AttachedDocument> {
+        let mut attached = self.attach_documents(params.submission_id, vec![params]).await?;
+        Ok(attached.remove(0))
+    }
+
+    /// Attaches several documents to one unit. They are planned in turn, then
+    /// checked and uploaded in parallel, then all are recorded in one
+    /// transaction, so the unit gets either all of them or none. Returns
+    /// them in order.
+    pub async fn attach_documents(&self, submission_id: Uuid, batch: Vec<AddDocumentParams>) -> Result<Vec<AttachedDocument>> {
+        let repo = SubmissionRepository::new(self.pool.clone());
+        let plans = self.plan_documents(submission_id, batch).await?;
+
         // 0. SELF-HEALING: Ensure Vault is ready
-        self.ensure_bucket().await
+        self.store.ensure_ready().await
             .context("Failed to initialize storage backend")?;
 
-        // 1. Checksum (Streaming from disk)
-        let mut file = File::open(&params.file_path).await
-            .context(format!("Failed to open file: {:?}", params.file_path))?;
+        // 1-4. Check and upload, several at a time
+        let results: Vec<Result<StagedDocument>> = stream::iter(plans)
+            .map(|plan| self.stage_document(plan))
+            .buffered(STAGE_CONCURRENCY)
+            .collect()
+            .await;
+        let mut staged: Vec<StagedDocument> = Vec::with_capacity(results.len());
+        let mut failure = None;
+        for result in results {
+            match result {
+                Ok(document) => staged.push(document),
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+        if let Some(e) = failure {
+            self.discard_staged(&staged.into_iter().filter_map(StagedDocument::into_blob).collect::<Vec<_>>()).await;
+            return Err(e);
+        }
 
-        let mut hasher = Sha256::new();
-        let mut buffer = [0; 8192]; // 8KB chunks
-        loop {
-            let n = file.read(&mut buffer).await?;
-            if n == 0 { break; }
-            hasher.update(&buffer[..n]);
+        // 5. Persist with the audit records (compensate the uploads if they don't land)
+        let items: Vec<_> = staged.iter()
+            .map(|s| match &s.content {
+                StagedContent::New { doc, .. } => (Some(doc), &s.cou, s.placeholder_id),
+                StagedContent::Reused(_) => (None, &s.cou, s.placeholder_id),
+            })
+            .collect();
+        let mut tx = self.pool.begin().await?;
+        let fulfilled = match repo.add_documents_to_submission(&mut tx, submission_id, &items).await {
+            Ok(fulfilled) => fulfilled,
+            Err(e) => {
+                let placeholders: Vec<String> = items.iter()
+                    .filter_map(|(_, _, id)| id.map(|id| id.to_string()))
+                    .collect();
+                self.discard_staged(&staged.into_iter().filter_map(StagedDocument::into_blob).collect::<Vec<_>>()).await;
+                if let (false, sqlx::Error::RowNotFound) = (placeholders.is_empty(), &e) {
+                    return Err(ServiceError::Conflict(format!(
+                        "Placeholder {} was fulfilled meanwhile", placeholders.join(", ")
+                    )).into());
+                }
+                return Err(e).context("Failed to persist document metadata");
+            }
+        };
+        let committed = async {
+            let mut attached = Vec::with_capacity(staged.len());
+            for (s, placeholder_id) in staged.iter().zip(fulfilled) {
+                let keywords: Vec<String> = s.keywords.iter().map(ToString::to_string).collect();
+                let (action, document_id, state) = match &s.content {
+                    StagedContent::New { doc, blob } => ("document.attach", Uuid::parse_str(&doc.id)?, json!({
+                        "submissionUnitId": submission_id,
+                        "contextOfUseId": s.cou.id,
+                        "contextCode": s.cou.code,
+                        "priority": s.cou.priority_number.value,
+                        "title": doc.title.value,
+                        "href": doc.text.reference.value,
+                        "checksum": doc.text.checksum,
+                        "mediaType": doc.text.media_type,
+                        "deduplicated": blob.deduplicated,
+                        "placeholderId": placeholder_id,
+                        "keywords": keywords,
+                    })),
+                    StagedContent::Reused(earlier) => ("document.reuse", earlier.id, json!({
+                        "submissionUnitId": submission_id,
+                        "contextOfUseId": s.cou.id,
+                        "contextCode": s.cou.code,
+                        "priority": s.cou.priority_number.value,
+                        "reusedFrom": {
+                            "submissionUnitId": earlier.submission_unit_id,
+                            "applicationNumber": earlier.application_number,
+                            "sequenceNumber": earlier.sequence_number,
+                        },
+                        "href": earlier.href,
+                        "checksum": earlier.checksum,
+                        "placeholderId": placeholder_id,
+                        "keywords": keywords,
+                    })),
+                };
+                self.record_audit(&mut tx, action, "document", Some(document_id), None, Some(state)).await?;
+                attached.push(AttachedDocument {
+                    document_id,
+                    context_of_use_id: Uuid::parse_str(&s.cou.id)?,
+                    reused: match &s.content {
+                        StagedContent::New { .. } => None,
+                        StagedContent::Reused(earlier) => Some(earlier.clone()),
+                    },
+                });
+            }
+            tx.commit().await?;
+            Ok::<_, anyhow::Error>(attached)
+        }.await;
+        if committed.is_err() {
+            self.discard_staged(&staged.into_iter().filter_map(StagedDocument::into_blob).collect::<Vec<_>>()).await;
         }
-        let hash = hex::encode(hasher.finalize());
+        committed
+    }
+
+    /// Documents with this content that a context of the unit may reference
+    /// instead of a new copy, latest first; see `DocumentReuse`.
+    pub async fn identical_documents(&self, submission_id: Uuid, sha256: &str) -> Result<Vec<DocumentSummary>> {
+        ListingRepository::new(self.pool.clone())
+            .identical_documents(submission_id, sha256).await
+            .context("Failed to look up identical documents")
+    }
+
+    /// Resolves where each document of a batch goes (its placeholder,
+    /// priority, keywords and package path) without reading or uploading
+    /// any file; `attach_documents` starts with this, and it serves as a
+    /// dry run. No two documents deliver the same placeholder, and those
+    /// without a priority are numbered after the batch's earlier ones.
+    pub async fn plan_documents(&self, submission_id: Uuid, batch: Vec<AddDocumentParams>) -> Result<Vec<PlannedDocument>> {
+        let repo = SubmissionRepository::new(self.pool.clone());
+        if !repo.submission_exists(submission_id).await? {
+            return Err(ServiceError::submission_not_found(submission_id).into());
+        }
+        if let Some(other) = batch.iter().find(|p| p.submission_id != submission_id) {
+            return Err(ServiceError::Invalid(format!(
+                "{:?} is for unit {}, not {}", other.file_path, other.submission_id, submission_id
+            )).into());
+        }
+
+        let mut plans: Vec<PlannedDocument> = Vec::with_capacity(batch.len());
+        let mut claimed = HashSet::new();
+        let mut last_priorities = self.last_priorities(submission_id).await?;
+        for params in batch {
+            let plan = self.plan_document(params, &claimed, &mut last_priorities).await?;
+            claimed.extend(plan.placeholder_id);
+            plans.push(plan);
+        }
+        Ok(plans)
+    }
+
+    /// Plans one document. `claimed` are placeholders other documents of the
+    /// batch deliver; `last_priorities` the highest priority per code so far,
+    /// counting the batch.
+    async fn plan_document(
+        &self,
+        params: AddDocumentParams,
+        claimed: &HashSet<Uuid>,
+        last_priorities: &mut HashMap<String, u32>,
+    ) -> Result<PlannedDocument> {
+        // The planned content it delivers, which may fix its priority and folder
+        let placeholder = match params.placeholder_id {
+            Some(placeholder_id) if claimed.contains(&placeholder_id) => {
+                return Err(ServiceError::Invalid(format!(
+                    "Placeholder {} is delivered by another document of the batch", placeholder_id
+                )).into());
+            }
+            Some(placeholder_id) => {
+                let placeholder = self.find_open_placeholder(params.submission_id, placeholder_id).await?;
+                if placeholder.code != params.context_code {
+                    return Err(ServiceError::Invalid(format!(
+                        "Placeholder {} is for '{}', not '{}'", placeholder_id, placeholder.code, params.context_code
+                    )).into());
+                }
+                Some(placeholder)
+            }
+            None => self.list_placeholders(params.submission_id).await?
+                .into_iter()
+                .find(|p| p.is_open() && p.code == params.context_code && !claimed.contains(&p.id)),
+        };
+        let priority = params.priority
+            .or_else(|| placeholder.as_ref().and_then(|p| p.priority).map(|p| p as u32))
+            .unwrap_or_else(|| last_priorities.get(&params.context_code).copied().unwrap_or_default() + 1);
+        let last = last_priorities.entry(params.context_code.clone()).or_default();
+        *last = (*last).max(priority);
+        let resolved = self.resolve_keywords(params.submission_id, &params.keywords).await?;
+        if let Some(folder) = &params.folder {
+            check_folder(folder)?;
+        }
+        let is_file = tokio::fs::metadata(&params.file_path).await.is_ok_and(|m| m.is_file());
+        if !is_file {
+            return Err(ServiceError::Invalid(format!("{:?} is not a readable file", params.file_path)).into());
+        }
+
+        // Use the resolver to determine the correct eCTD folder, unless the caller or the plan names one
+        let filename = params.file_path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let folder = params.folder.as_deref()
+            .map(|f| f.trim_matches('/'))
+            .or_else(|| placeholder.as_ref().and_then(|p| p.folder.as_deref()))
+            .unwrap_or_else(|| resolve_folder_path(&params.context_code));
+        let href = format!("{}/{}", folder, filename);
+
+        Ok(PlannedDocument {
+            file_path: params.file_path,
+            context_code: params.context_code,
+            title: params.title,
+            priority,
+            href,
+            placeholder_id: placeholder.map(|p| p.id),
+            keywords: params.keywords,
+            submission_id: params.submission_id,
+            reuse: params.reuse,
+            resolved,
+        })
+    }
+
+    /// Checks and uploads one planned document.
+    async fn stage_document(&self, plan: PlannedDocument) -> Result<StagedDocument> {
+        // 1. Checksum (Streaming from disk) - also the content address
+        let (hash, size) = hash_file(&plan.file_path).await?;
+
+        // 1.1 Already submitted? Then reference it, unless the caller wants a copy
+        if plan.reuse != DocumentReuse::Copy
+            && let Some(earlier) = self.identical_documents(plan.submission_id, &hash).await?.into_iter().next()
+        {
+            if plan.reuse == DocumentReuse::Ask {
+                return Err(ServiceError::Conflict(format!(
+                    "{:?} is identical to document {} ({}) of application {} sequence {}; \
+                     attach it with reuse 'reference' to reference that document, or 'copy' to send a new copy",
+                    plan.file_path.file_name().unwrap_or_default(),
+                    earlier.id,
+                    earlier.href,
+                    earlier.application_number,
+                    earlier.sequence_number
+                )).into());
+            }
+            return Ok(StagedDocument {
+                cou: context_of_use(plan.context_code, plan.priority, earlier.id, plan.resolved),
+                content: StagedContent::Reused(earlier),
+                placeholder_id: plan.placeholder_id,
+                keywords: plan.keywords,
+            });
+        }
+
+        // 1.2 Media type from content, not from the file extension
+        let content_type = sniff_media_type(&plan.file_path).await?
+            .unwrap_or("application/octet-stream");
 
         // 1.5 VALIDATION (The Shield)
         // Check PDF integrity before uploading.
-        // We assume "application/pdf" for now, but in reality we should check extension.
-        if let Some(ext) = params.file_path.extension() {
-            if ext.to_string_lossy().to_lowercase() == "pdf" {
-                // Construct a minimal dummy unit to satisfy the Validator signature
-                let validation_doc = Document {
-                    id: "temp-validation-id".to_string(),
-                    title: DocumentTitle { value: params.title.clone() },
-                    text: DocumentText {
-                        // Crucial: Use LOCAL path for validation so lopdf can find it
-                        reference: DocumentReferencePath { value: params.file_path.to_string_lossy().to_string() },
-                        checksum: hash.clone(),
-                        checksum_algorithm: "SHA256".to_string(),
-                        media_type: "application/pdf".to_string(),
-                    },
-                };
+        if content_type == media_type::PDF {
+            // Construct a minimal dummy unit to satisfy the Validator signature
+            let validation_doc = Document {
+                id: "temp-validation-id".to_string(),
+                title: DocumentTitle { value: plan.title.clone() },
+                text: DocumentText {
+                    // Crucial: Use LOCAL path for validation so lopdf can find it
+                    reference: DocumentReferencePath { value: plan.file_path.to_string_lossy().to_string() },
+                    checksum: hash.clone(),
+                    checksum_algorithm: "SHA256".to_string(),
+                    media_type: content_type.to_string(),
+                },
+            };
 
-                let dummy_unit = SubmissionUnit {
+            let dummy_unit = SubmissionUnit {
+                id: Uuid::new_v4().to_string(),
+                code: "validation-wrapper".to_string(),
+                code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
+                status_code: "active".to_string(),
+                xmlns: "urn:hl7-org:v3".to_string(),
+                xmlns_xsi: None,
+                schema_location: None,
+                submission: Submission {
                     id: Uuid::new_v4().to_string(),
-                    code: "validation-wrapper".to_string(),
+                    code: "seq-0000".to_string(),
                     code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
-                    status_code: "active".to_string(),
-                    xmlns: "urn:hl7-org:v3".to_string(),
-                    xmlns_xsi: None,
-                    schema_location: None,
-                    submission: Submission {
-                        id: Uuid::new_v4().to_string(),
-                        code: "seq-0000".to_string(),
-                        code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
-                        sequence_number: SequenceNumber { value: 0 },
-                    },
-                    application: Application {
-                        id: Uuid::new_v4().to_string(),
-                        code: "nda".to_string(),
+                    sequence_number: SequenceNumber { value: 0 },
+                },
+                application: Application {
+                    id: Uuid::new_v4().to_string(),
+                    code: "nda".to_string(),
+                    code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
+                    application_number: ApplicationNumber {
+                        code: "000000".to_string(),
                         code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
-                        application_number: ApplicationNumber {
-                            code: "000000".to_string(),
-                            code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
-                        }
-                    },
-                    applicant: Applicant {
-                        sponsoring_organization: SponsoringOrganization { name: "Validation".to_string() }
-                    },
-                    context_of_use: vec![],
-                    keyword_definitions: None,
-                    documents: vec![validation_doc],
-                };
-
-                let engine = ValidationEngine::new()
-                    .add_rule(RuleEctd4_533);
+                    }
+                },
+                applicant: Applicant {
+                    sponsoring_organization: SponsoringOrganization { name: "Validation".to_string() }
+                },
+                context_of_use: vec![],
+                keyword_definitions: None,
+                documents: vec![validation_doc],
+            };
 
-                let errors = engine.run(&dummy_unit);
+            // Parsing is CPU-bound; keep it off the runtime so a batch's checks run side by side
+            let errors = tokio::task::spawn_blocking(move || {
+                ValidationEngine::new()
+                    .add_rule(RuleEctd4_533)
+                    .run(&dummy_unit)
+            }).await.context("PDF check failed to run")?;
 
-                // Block on High Errors (Severity "High Error")
-                for err in errors {
-                    if err.severity.contains("High Error") {
-                         anyhow::bail!("PDF Validation Failed: {} (Code: {})", err.message, err.code);
-                    }
-                    // We could log warnings here
+            // Block on High Errors (Severity "High Error")
+            for err in errors {
+                if err.severity.contains("High Error") {
+                     return Err(ServiceError::Invalid(format!("PDF Validation Failed: {} (Code: {})", err.message, err.code)).into());
                 }
+                // We could log warnings here
             }
         }
 
-        // 2. Identities
+        // 2. Identity
         let doc_id = Uuid::new_v4();
-        let cou_id = Uuid::new_v4();
 
-        // 3. Upload (Streaming again)
-        let body = ByteStream::from_path(&params.file_path).await?;
+        // 3. Upload (Streaming again), skipped if the same content is already stored
+        let blob = self.store_blob(&plan.file_path, &hash, size, content_type).await?;
 
-        self.s3.put_object()
-            .bucket(&self.bucket)
-            .key(doc_id.to_string())
-            .body(body)
-            .content_type("application/pdf")
-            .send()
-            .await
-            .context("S3 Upload Failed")?;
+        // 3.5 Text for the search index (before the insert, whose trigger picks it up)
+        self.index_text(&plan.file_path, &blob.sha256, content_type).await;
 
         // 4. Construct
-        let filename = params.file_path.file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
-
-        // Use the resolver to determine the correct eCTD folder
-        let folder = resolve_folder_path(&params.context_code);
-        let ref_path = format!("{}/{}", folder, filename);
-
         let doc = Document {
             id: doc_id.to_string(),
-            title: DocumentTitle { value: params.title },
+            title: DocumentTitle { value: plan.title },
             text: DocumentText {
-                reference: DocumentReferencePath { value: ref_path },
+                reference: DocumentReferencePath { value: plan.href },
                 checksum: hash,
                 checksum_algorithm: "SHA256".to_string(),
-                media_type: "application/pdf".to_string(),
+                media_type: content_type.to_string(),
             },
         };
 
-        let cou = ContextOfUse {
-            id: cou_id.to_string(),
-            code: params.context_code,
-            code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
-            status_code: "active".to_string(),
-            priority_number: PriorityNumber { value: params.priority },
-            document_reference: Some(DocumentReference {
-                id: DocumentIdRef { root: doc_id.to_string() }
-            }),
-            related_context_of_use: None,
-            keywords: vec![],
-        };
+        Ok(StagedDocument {
+            content: StagedContent::New { doc, blob },
+            cou: context_of_use(plan.context_code, plan.priority, doc_id, plan.resolved),
+            placeholder_id: plan.placeholder_id,
+            keywords: plan.keywords,
+        })
+    }
+}
 
-        // 5. Persist
-        let repo = SubmissionRepository::new(self.pool.clone());
-        repo.add_document_to_submission(params.submission_id, &doc, &cou).await?;
+/// A new active context of use for the document.
+fn context_of_use(code: String, priority: u32, document_id: Uuid, keywords: Vec<Keyword>) -> ContextOfUse {
+    ContextOfUse {
+        id: Uuid::new_v4().to_string(),
+        code,
+        code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
+        status_code: "active".to_string(),
+        priority_number: PriorityNumber { value: priority },
+        document_reference: Some(DocumentReference {
+            id: DocumentIdRef { root: document_id.to_string() }
+        }),
+        related_context_of_use: None,
+        keywords,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    /// Sequence 0001 with a cover letter, and sequence 0002 to attach the same file to.
+    async fn submitted_twice(service: &EctdService) -> (Uuid, Uuid, PathBuf) {
+        let first = testing::new_unit(service, "100001").await;
+        let letter = testing::file("letter.txt", "letter of authorization");
+        let sent = testing::attach(service, first, &letter, "cover-letter", DocumentReuse::Ask).await.unwrap();
+        let second = testing::new_unit(service, "100001").await;
+        (sent.document_id, second, letter)
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_ask_names_the_earlier_document(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let (earlier, second, letter) = submitted_twice(&service).await;
+
+        let err = testing::attach(&service, second, &letter, "cover-letter", DocumentReuse::Ask).await.unwrap_err();
+
+        assert!(matches!(err.downcast_ref(), Some(ServiceError::Conflict(m)) if m.contains(&earlier.to_string())), "{err:#}");
+        assert!(service.get_submission(second).await.unwrap().context_of_use.is_empty());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_reference_points_at_the_earlier_document(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let (earlier, second, letter) = submitted_twice(&service).await;
+
+        let attached = testing::attach(&service, second, &letter, "cover-letter", DocumentReuse::Reference).await.unwrap();
+
+        assert_eq!(attached.document_id, earlier);
+        assert_eq!(attached.reused.map(|r| r.sequence_number), Some(1));
+        let unit = service.get_submission(second).await.unwrap();
+        assert!(unit.documents.is_empty());
+        let reference = unit.context_of_use[0].document_reference.as_ref().unwrap();
+        assert_eq!(reference.id.root, earlier.to_string());
+        let findings = service.validate_submission(second).await.unwrap();
+        assert!(!findings.iter().any(|f| f.code == "DOC-001"), "{findings:?}");
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_listing_counts_references_in_the_unit_that_makes_them(pool: sqlx::PgPool) {
+        use ectd_db::listing::{PageRequest, SubmissionFilter, SubmissionSort};
+        let service = testing::service(pool).await;
+        let (earlier, second, letter) = submitted_twice(&service).await;
+        testing::attach(&service, second, &letter, "cover-letter", DocumentReuse::Reference).await.unwrap();
+
+        let filter = SubmissionFilter::default();
+        let units = service.list_submissions(&filter, SubmissionSort::Application, &PageRequest::default()).await.unwrap();
+        let counts: Vec<_> = units.items.iter().map(|u| (u.sequence_number, u.document_count)).collect();
+        assert_eq!(counts, [(1, 1), (2, 1)]);
+
+        let documents = service.list_documents(&filter, None, &PageRequest::default()).await.unwrap();
+        assert_eq!(documents.items.len(), 1);
+        assert_eq!(documents.items[0].id, earlier);
+        assert_eq!(documents.items[0].context_codes, ["cover-letter"]);
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_copy_sends_a_new_document(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let (earlier, second, letter) = submitted_twice(&service).await;
+
+        let attached = testing::attach(&service, second, &letter, "cover-letter", DocumentReuse::Copy).await.unwrap();
+
+        assert_ne!(attached.document_id, earlier);
+        assert!(attached.reused.is_none());
+        let unit = service.get_submission(second).await.unwrap();
+        assert_eq!(unit.documents[0].id, attached.document_id.to_string());
+        assert_eq!(service.store.list().await.unwrap().len(), 1);
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_documents_of_other_applications_are_not_reused(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let (_, _, letter) = submitted_twice(&service).await;
+        let other = testing::new_unit(&service, "100002").await;
+
+        let attached = testing::attach(&service, other, &letter, "cover-letter", DocumentReuse::Ask).await.unwrap();
 
-        Ok(doc_id)
+        assert!(attached.reused.is_none());
+        assert_eq!(service.get_submission(other).await.unwrap().documents.len(), 1);
     }
 }
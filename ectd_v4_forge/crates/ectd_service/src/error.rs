@@ -0,0 +1,27 @@
+use thiserror::Error;
+
+/// Failures a caller should be able to tell apart from infrastructure errors.
+/// Service methods return them inside `anyhow::Error`; front ends recover
+/// them with `err.downcast_ref::<ServiceError>()`.
+#[derive(Debug, Error)]
+pub enum ServiceError {
+    /// The named entity doesn't exist (e.g. "Submission unit <id>").
+    #[error("{0} not found")]
+    NotFound(String),
+    /// The request can't be carried out as given.
+    #[error("{0}")]
+    Invalid(String),
+    /// The request clashes with existing data (e.g. a sequence number already in use).
+    #[error("{0}")]
+    Conflict(String),
+}
+
+impl ServiceError {
+    pub fn submission_not_found(id: uuid::Uuid) -> Self {
+        ServiceError::NotFound(format!("Submission unit {}", id))
+    }
+
+    pub fn application_not_found(number: &str) -> Self {
+        ServiceError::NotFound(format!("Application {}", number))
+    }
+}
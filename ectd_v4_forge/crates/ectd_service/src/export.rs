@@ -1,18 +1,25 @@
 use crate::EctdService;
+use crate::signatures::has_valid_approval;
+use crate::package::{write_archive, ArchiveFormat};
+use crate::blobs::hash_file;
+use crate::storage::DocumentStore;
 use anyhow::{Context, Result};
 use uuid::Uuid;
 use std::path::{Path, PathBuf};
 use std::fs::{self, File};
 use std::io::{Write, BufWriter};
 use futures::stream::{self, Stream, StreamExt};
-use tokio::io::AsyncWriteExt;
 use sha2::{Sha256, Digest};
 use ectd_db::repository::SubmissionRepository;
+use ectd_db::blob::BlobRepository;
 use async_stream::stream;
 use serde::{Serialize, Deserialize};
+use serde_json::json;
+use utoipa::ToSchema;
 use std::pin::Pin;
+use std::time::Duration;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportProgress {
     pub file_name: String,      // "m1/us/cover.pdf"
@@ -22,11 +29,58 @@ pub struct ExportProgress {
     pub status: String,         // "Downloading", "Hashing", "Complete"
 }
 
+/// A document that could not be exported, after retries.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportFailure {
+    pub file_name: String,
+    pub error: String,
+}
+
+/// Returned at the end of an export in which some documents failed.
+/// Every other document was still fetched, so re-running resumes from here.
+#[derive(Debug, thiserror::Error)]
+#[error("{} document(s) could not be exported: {}", .failures.len(), describe_failures(.failures))]
+pub struct ExportIncomplete {
+    pub failures: Vec<ExportFailure>,
+}
+
+fn describe_failures(failures: &[ExportFailure]) -> String {
+    failures
+        .iter()
+        .map(|f| format!("{} ({})", f.file_name, f.error))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// How the exported sequence is laid out and packaged.
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions {
+    /// Nest the sequence as `<application number>/<sequence>/` (ESG / CESP convention)
+    pub transmission_layout: bool,
+    /// Regional util files (DTDs, stylesheets) copied into `<sequence>/util/`
+    pub util_dir: Option<PathBuf>,
+    /// Also write a reproducible archive of the export next to it
+    pub archive: Option<ArchiveFormat>,
+}
+
 impl EctdService {
+    /// Exports the sequence as a plain directory tree with `sha256.txt`.
+    /// Re-running into the same directory resumes: files already there with
+    /// the right checksum are not downloaded again.
     pub fn export_submission_stream(
         &self,
         id: Uuid,
         output_dir: PathBuf,
+    ) -> Pin<Box<dyn Stream<Item = Result<ExportProgress, anyhow::Error>> + Send + '_>> {
+        self.export_package_stream(id, output_dir, ExportOptions::default())
+    }
+
+    pub fn export_package_stream(
+        &self,
+        id: Uuid,
+        output_dir: PathBuf,
+        options: ExportOptions,
     ) -> Pin<Box<dyn Stream<Item = Result<ExportProgress, anyhow::Error>> + Send + '_>> {
         Box::pin(stream! {
             // 1. Fetch Data
@@ -39,11 +93,55 @@ impl EctdService {
                 }
             };
 
+            // 1.4 Readiness Gate (every planned document must be attached)
+            if let Err(e) = self.ensure_ready(id).await {
+                yield Err(e);
+                return;
+            }
+
+            // 1.5 Approval Gate (SOP: no unsigned sequence leaves the building)
+            if self.require_approval {
+                match self.signature_status_for(id, &unit).await {
+                    Ok(statuses) if has_valid_approval(&statuses) => {},
+                    Ok(_) => {
+                        yield Err(anyhow::anyhow!(
+                            "Submission unit {} has no valid approval signature. Sign it with meaning 'approve' before exporting.",
+                            id
+                        ));
+                        return;
+                    }
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                }
+            }
+
+            // 1.6 Storage keys (content hash, or UUID for pre-content-addressed documents)
+            let storage_keys = match BlobRepository::new(self.pool.clone()).storage_keys(id).await {
+                Ok(keys) => keys,
+                Err(e) => {
+                    yield Err(anyhow::anyhow!("Failed to resolve storage keys: {}", e));
+                    return;
+                }
+            };
+
             let total_docs = unit.documents.len();
             let mut processed_count = 0;
 
-            // 2. Prepare Output Directory
-            if let Err(e) = fs::create_dir_all(&output_dir) {
+            // 2. Prepare Output Directory (<app>/<seq>/ for transmission)
+            let sequence_prefix = if options.transmission_layout {
+                format!(
+                    "{}/{:04}/",
+                    unit.application.application_number.code,
+                    unit.submission.sequence_number.value
+                )
+            } else {
+                String::new()
+            };
+            let content_root = output_dir.join(&sequence_prefix);
+
+            if let Err(e) = fs::create_dir_all(&content_root) {
                 yield Err(anyhow::anyhow!("Failed to create output dir: {}", e));
                 return;
             }
@@ -57,80 +155,107 @@ impl EctdService {
             });
 
             // 3. Download Files (Parallel)
+            // Files left by an earlier run are kept when their checksum still matches,
+            // so re-running into the same directory only fetches what is missing.
             // Use clone of documents to own the data in the stream, avoiding complex lifetime issues with async closures
             let docs_to_download = unit.documents.clone();
             let mut downloads = stream::iter(docs_to_download.into_iter())
                 .map(|doc| {
-                    let client = self.s3.clone();
-                    let bucket = self.bucket.clone();
-                    let out_dir = output_dir.clone();
-                    let doc_id = doc.id;
+                    let store = self.store.clone();
+                    let out_dir = content_root.clone();
+                    let key = Uuid::parse_str(&doc.id).ok()
+                        .and_then(|u| storage_keys.get(&u).cloned())
+                        .unwrap_or(doc.id);
                     let ref_path = doc.text.reference.value;
+                    let expected = doc.text.checksum;
 
                     async move {
-                        let rel_path = Path::new(&ref_path);
-                        let full_out_path = out_dir.join(rel_path);
+                        let full_out_path = out_dir.join(Path::new(&ref_path));
 
                         if let Some(parent) = full_out_path.parent() {
                              let _ = fs::create_dir_all(parent);
                         }
 
-                        let mut stream = client.get_object()
-                            .bucket(bucket)
-                            .key(&doc_id.to_string())
-                            .send()
-                            .await?
-                            .body;
-
-                        let mut file = tokio::fs::File::create(&full_out_path).await?;
-                        let mut bytes_written = 0;
-                        while let Some(bytes) = stream.try_next().await? {
-                            file.write_all(&bytes).await?;
-                            bytes_written += bytes.len() as u64;
-                        }
-
-                        Ok::<(PathBuf, String, u64), anyhow::Error>((full_out_path, ref_path, bytes_written))
+                        let fetched = fetch_document(store.as_ref(), &key, &full_out_path, &expected).await;
+                        (full_out_path, ref_path, fetched)
                     }
                 })
                 .buffer_unordered(10);
 
             let mut manifest_entries = Vec::new();
+            let mut failures = Vec::new();
             let mut total_bytes = 0;
+            let mut skipped = 0;
+            let mut retried = 0;
 
-            while let Some(result) = downloads.next().await {
-                match result {
-                    Ok((path, ref_path, bytes)) => {
-                        processed_count += 1;
-                        total_bytes += bytes;
-
-                        let hash = calculate_file_hash(&path);
-                        let hash = match hash {
-                            Ok(h) => h,
-                            Err(e) => {
-                                 yield Err(anyhow::anyhow!("Hashing failed: {}", e));
-                                 return;
-                            }
-                        };
-
-                        let rel_str = match path.strip_prefix(&output_dir) {
-                            Ok(p) => p.to_string_lossy().replace("\\", "/"),
-                            Err(e) => {
-                                yield Err(anyhow::anyhow!("Path strip prefix failed: {}", e));
-                                return;
-                            }
-                        };
-                        manifest_entries.push((hash, rel_str));
+            while let Some((path, ref_path, fetched)) = downloads.next().await {
+                processed_count += 1;
 
+                let fetched = match fetched {
+                    Ok(f) => f,
+                    Err(e) => {
+                        // Keep going: one bad object shouldn't cost the rest of the package
+                        failures.push(ExportFailure { file_name: ref_path.clone(), error: format!("{:#}", e) });
                         yield Ok(ExportProgress {
                             file_name: ref_path,
                             processed_files: processed_count,
                             total_files: total_docs,
                             bytes_processed: total_bytes,
-                            status: "Downloading".to_string(),
+                            status: "Failed".to_string(),
                         });
-                    },
+                        continue;
+                    }
+                };
+
+                total_bytes += fetched.bytes;
+                if fetched.attempts == 0 {
+                    skipped += 1;
+                } else if fetched.attempts > 1 {
+                    retried += 1;
+                }
+
+                let rel_str = match path.strip_prefix(&content_root) {
+                    Ok(p) => p.to_string_lossy().replace("\\", "/"),
+                    Err(e) => {
+                        yield Err(anyhow::anyhow!("Path strip prefix failed: {}", e));
+                        return;
+                    }
+                };
+                manifest_entries.push((fetched.sha256, rel_str));
+
+                yield Ok(ExportProgress {
+                    file_name: ref_path,
+                    processed_files: processed_count,
+                    total_files: total_docs,
+                    bytes_processed: total_bytes,
+                    status: if fetched.attempts == 0 { "Up to date" } else { "Downloading" }.to_string(),
+                });
+            }
+
+            // 3.5 Never write the XML or manifest for an incomplete package.
+            // Whatever did arrive stays on disk for the next run.
+            if !failures.is_empty() {
+                failures.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+                yield Err(ExportIncomplete { failures }.into());
+                return;
+            }
+
+            // 4. Regional util folder
+            if let Some(util_dir) = &options.util_dir {
+                match copy_util_dir(util_dir, &content_root.join("util")) {
+                    Ok(files) => {
+                        for rel in files {
+                            match calculate_file_hash(&content_root.join(&rel)) {
+                                Ok(hash) => manifest_entries.push((hash, rel)),
+                                Err(e) => {
+                                    yield Err(anyhow::anyhow!("Hashing failed: {}", e));
+                                    return;
+                                }
+                            }
+                        }
+                    }
                     Err(e) => {
-                        yield Err(anyhow::anyhow!("File download failed: {}", e));
+                        yield Err(e);
                         return;
                     }
                 }
@@ -153,7 +278,7 @@ impl EctdService {
                 }
             };
 
-            let xml_path = output_dir.join("submissionunit.xml");
+            let xml_path = content_root.join("submissionunit.xml");
             if let Err(e) = fs::write(&xml_path, final_xml) {
                  yield Err(anyhow::anyhow!("Failed to write XML: {}", e));
                  return;
@@ -177,7 +302,10 @@ impl EctdService {
                 status: "Finalizing Manifest".to_string(),
             });
 
-            let manifest_path = output_dir.join("sha256.txt");
+            // Sorted, so the manifest doesn't depend on download completion order
+            manifest_entries.sort_by(|a, b| a.1.cmp(&b.1));
+
+            let manifest_path = content_root.join("sha256.txt");
             let manifest_file = match File::create(manifest_path) {
                 Ok(f) => f,
                 Err(e) => {
@@ -186,13 +314,89 @@ impl EctdService {
                 }
             };
             let mut manifest_file = BufWriter::new(manifest_file);
-            for (hash, filename) in manifest_entries {
+            for (hash, filename) in &manifest_entries {
                 let line = format!("{}  {}\n", hash, filename);
                 if let Err(e) = manifest_file.write_all(line.as_bytes()) {
                     yield Err(anyhow::anyhow!("Failed to write to manifest: {}", e));
                     return;
                 }
             }
+            if let Err(e) = manifest_file.flush() {
+                yield Err(anyhow::anyhow!("Failed to write to manifest: {}", e));
+                return;
+            }
+
+            // 7. Archive (reproducible: same submission, same bytes)
+            let mut archive_record = serde_json::Value::Null;
+            if let Some(format) = options.archive {
+                yield Ok(ExportProgress {
+                    file_name: format!("archive.{}", format),
+                    processed_files: processed_count,
+                    total_files: total_docs,
+                    bytes_processed: total_bytes,
+                    status: "Packaging".to_string(),
+                });
+
+                let archive_name = format!(
+                    "{}-{:04}.{}",
+                    unit.application.application_number.code,
+                    unit.submission.sequence_number.value,
+                    format.extension()
+                );
+                let archive_path = output_dir.join(&archive_name);
+                let entries: Vec<String> = manifest_entries
+                    .iter()
+                    .map(|(_, rel)| rel.as_str())
+                    .chain(std::iter::once("sha256.txt"))
+                    .map(|rel| format!("{}{}", sequence_prefix, rel))
+                    .collect();
+
+                let root = output_dir.clone();
+                let dest = archive_path.clone();
+                let packaged = tokio::task::spawn_blocking(move || {
+                    write_archive(format, &root, &entries, &dest)?;
+                    Ok::<String, anyhow::Error>(calculate_file_hash(&dest)?)
+                }).await;
+
+                match packaged {
+                    Ok(Ok(hash)) => {
+                        archive_record = json!({ "path": archive_path.to_string_lossy(), "sha256": hash });
+                    }
+                    Ok(Err(e)) => {
+                        yield Err(anyhow::anyhow!("Packaging failed: {}", e));
+                        return;
+                    }
+                    Err(e) => {
+                        yield Err(anyhow::anyhow!("Packaging task failed: {}", e));
+                        return;
+                    }
+                }
+            }
+
+            // 8. Audit: record what left the system and where it went
+            let audit = self.record_event(
+                "submission.export",
+                "submission_unit",
+                Some(id),
+                None,
+                Some(json!({
+                    "outputDir": output_dir.to_string_lossy(),
+                    "sequenceDir": sequence_prefix,
+                    "archive": archive_record,
+                    "files": manifest_entries.len(),
+                    "bytes": total_bytes,
+                    "skipped": skipped,
+                    "retried": retried,
+                    "manifest": manifest_entries
+                        .iter()
+                        .map(|(hash, filename)| json!({ "path": filename, "sha256": hash }))
+                        .collect::<Vec<_>>(),
+                })),
+            ).await;
+            if let Err(e) = audit {
+                yield Err(e);
+                return;
+            }
 
             yield Ok(ExportProgress {
                 file_name: "Done".to_string(),
@@ -205,6 +409,62 @@ impl EctdService {
     }
 }
 
+/// Attempts per document before it is reported as failed.
+const MAX_ATTEMPTS: u32 = 4;
+/// First retry delay; doubled on each further attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
+struct FetchedDocument {
+    sha256: String,
+    bytes: u64,
+    /// 0 when an earlier run had already left the right file in place.
+    attempts: u32,
+}
+
+/// Puts the document at `dest`, verified against `expected`.
+/// Transient failures are retried with exponential backoff; a missing object
+/// or content that doesn't match its checksum is not going to get better.
+async fn fetch_document(
+    store: &dyn DocumentStore,
+    key: &str,
+    dest: &Path,
+    expected: &str,
+) -> Result<FetchedDocument> {
+    // Resume: keep what a previous run already downloaded
+    if dest.is_file()
+        && let Ok((hash, size)) = hash_file(dest).await
+        && hash.eq_ignore_ascii_case(expected)
+    {
+        return Ok(FetchedDocument { sha256: hash, bytes: size, attempts: 0 });
+    }
+
+    let mut attempt = 1;
+    loop {
+        let error = match store.get_to_file(key, dest).await {
+            Ok(_) => {
+                let (hash, size) = hash_file(dest).await?;
+                // Integrity: what came back must be what was attached
+                if hash.eq_ignore_ascii_case(expected) {
+                    return Ok(FetchedDocument { sha256: hash, bytes: size, attempts: attempt });
+                }
+                let _ = fs::remove_file(dest);
+                anyhow::bail!("Integrity check failed: expected {}, got {}", expected, hash);
+            }
+            Err(e) => e,
+        };
+
+        let _ = fs::remove_file(dest);
+        let missing = matches!(store.exists(key).await, Ok(false));
+        if missing || attempt >= MAX_ATTEMPTS {
+            return Err(error.context(format!("Download failed after {} attempt(s)", attempt)));
+        }
+
+        tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+        attempt += 1;
+    }
+}
+
 // Synchronous Hashing Helper
 fn calculate_file_hash(path: &Path) -> std::io::Result<String> {
     let mut file = File::open(path)?;
@@ -212,3 +472,108 @@ fn calculate_file_hash(path: &Path) -> std::io::Result<String> {
     std::io::copy(&mut file, &mut hasher)?;
     Ok(hex::encode(hasher.finalize()))
 }
+
+/// Copies a util tree, returning the copied files as '/'-separated paths
+/// relative to the sequence folder (e.g. "util/dtd/us-regional.dtd").
+fn copy_util_dir(src: &Path, dest: &Path) -> Result<Vec<String>> {
+    let mut copied = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+
+    while let Some(rel_dir) = pending.pop() {
+        let entries = fs::read_dir(src.join(&rel_dir))
+            .context(format!("Failed to read util dir: {:?}", src.join(&rel_dir)))?;
+        fs::create_dir_all(dest.join(&rel_dir))?;
+
+        for entry in entries {
+            let entry = entry?;
+            let rel = rel_dir.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                pending.push(rel);
+            } else {
+                fs::copy(entry.path(), dest.join(&rel))?;
+                copied.push(format!("util/{}", rel.to_string_lossy().replace('\\', "/")));
+            }
+        }
+    }
+
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{MemoryStore, StoredObject};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails the first `failures` downloads, then behaves.
+    struct FlakyStore {
+        inner: MemoryStore,
+        failures: AtomicU32,
+    }
+
+    #[async_trait]
+    impl DocumentStore for FlakyStore {
+        async fn ensure_ready(&self) -> Result<()> { self.inner.ensure_ready().await }
+        async fn put_file(&self, key: &str, path: &Path, content_type: &str) -> Result<()> {
+            self.inner.put_file(key, path, content_type).await
+        }
+        async fn exists(&self, key: &str) -> Result<bool> { self.inner.exists(key).await }
+        async fn get_to_file(&self, key: &str, dest: &Path) -> Result<u64> {
+            if self.failures.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok() {
+                anyhow::bail!("connection reset");
+            }
+            self.inner.get_to_file(key, dest).await
+        }
+        async fn delete(&self, key: &str) -> Result<()> { self.inner.delete(key).await }
+        async fn list(&self) -> Result<Vec<StoredObject>> { self.inner.list().await }
+        fn describe(&self) -> String { "flaky".to_string() }
+    }
+
+    async fn setup(failures: u32) -> (FlakyStore, PathBuf, String) {
+        let dir = std::env::temp_dir().join(format!("ectd-export-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let src = dir.join("source.pdf");
+        fs::write(&src, b"%PDF-1.7 export").unwrap();
+        let (sha, _) = hash_file(&src).await.unwrap();
+
+        let store = FlakyStore { inner: MemoryStore::new(), failures: AtomicU32::new(failures) };
+        store.put_file(&sha, &src, "application/pdf").await.unwrap();
+        (store, dir, sha)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_retries_transient_failures() {
+        let (store, dir, sha) = setup(1).await;
+        let dest = dir.join("out.pdf");
+
+        let fetched = fetch_document(&store, &sha, &dest, &sha).await.unwrap();
+        assert_eq!(fetched.attempts, 2);
+        assert_eq!(fetched.sha256, sha);
+
+        // Second run finds the file in place and doesn't touch the store
+        store.failures.store(MAX_ATTEMPTS, Ordering::SeqCst);
+        let resumed = fetch_document(&store, &sha, &dest, &sha).await.unwrap();
+        assert_eq!(resumed.attempts, 0);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_replaces_stale_file_and_gives_up_on_missing_object() {
+        let (store, dir, sha) = setup(0).await;
+        let dest = dir.join("out.pdf");
+
+        fs::write(&dest, b"left over from an older sequence").unwrap();
+        let fetched = fetch_document(&store, &sha, &dest, &sha).await.unwrap();
+        assert_eq!(fetched.attempts, 1);
+        assert_eq!(fs::read(&dest).unwrap(), b"%PDF-1.7 export");
+
+        let missing = dir.join("missing.pdf");
+        let err = fetch_document(&store, "nope", &missing, &sha).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("after 1 attempt(s)"), "{:#}", err);
+        assert!(!missing.exists());
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,383 @@
+use crate::EctdService;
+use crate::blobs::{hash_file, sniff_media_type, StoredBlob};
+use anyhow::{Context, Result};
+use md5::Md5;
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use ectd_core::models::{
+    context_of_use::{ContextOfUse, DocumentIdRef, DocumentReference, Keyword, PriorityNumber, RelatedContextOfUse},
+    document::{Document, DocumentReferencePath, DocumentText, DocumentTitle},
+    keyword_definition::{DisplayName, KeywordDefinition, KeywordDefinitionItem, KeywordDefinitionValue, KeywordType},
+    submission_unit::{
+        Applicant, Application, ApplicationNumber, SequenceNumber, SponsoringOrganization, Submission, SubmissionUnit,
+    },
+};
+use ectd_core::v3::{self, mapping::context_code_for, LeafOperation, LeafRef};
+use ectd_db::repository::SubmissionRepository;
+
+const CODE_SYSTEM: &str = "urn:oid:2.16.840.1.113883.3.989.2.2.1";
+const KEYWORD_CODE_SYSTEM: &str = KeywordType::Sponsor.code_system();
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedSequence {
+    /// v3.2.2 sequence folder, e.g. "0002"
+    pub sequence: String,
+    pub unit_id: Uuid,
+    pub documents: usize,
+    pub contexts_of_use: usize,
+    /// Found in the database from an earlier run and left untouched
+    pub already_imported: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct V3ImportOutcome {
+    pub application_number: String,
+    pub sequences: Vec<ImportedSequence>,
+    pub uploaded: usize,
+    pub deduplicated: usize,
+    /// v3 headings without a v4 mapping; their name was kept as the context code
+    pub unmapped_headings: Vec<String>,
+    /// Lifecycle operations whose target leaf couldn't be found
+    pub warnings: Vec<String>,
+}
+
+/// What an imported leaf became, so later sequences can replace or delete it.
+#[derive(Debug, Clone)]
+struct ImportedLeaf {
+    context_id: Uuid,
+    code: String,
+    priority: u32,
+}
+
+impl EctdService {
+    /// Migrates a v3.2.2 application folder (`0000/`, `0001/`, ... each with an
+    /// index.xml) into v4.0 submission units, one per sequence, oldest first.
+    ///
+    /// Leaf lifecycle is carried over:
+    /// - new: a new context of use and document
+    /// - replace: a new context of use that replaces the target's
+    /// - append: a new context of use next to the target's (v4.0 has no append)
+    /// - delete: a suspended context of use that replaces the target's
+    ///
+    /// Node-extension titles become keywords. IDs are derived from the application
+    /// number, sequence and leaf ID, so re-running skips sequences already imported
+    /// and continues after a failure.
+    pub async fn import_v3_application(&self, app_dir: &Path) -> Result<V3ImportOutcome> {
+        self.store.ensure_ready().await
+            .context("Failed to initialize storage backend")?;
+
+        let sequences = v3::read_application(app_dir)?;
+        let repo = SubmissionRepository::new(self.pool.clone());
+
+        let mut header = sequences[0].header.clone();
+        let mut history: HashMap<LeafRef, ImportedLeaf> = HashMap::new();
+        let mut unmapped = BTreeSet::new();
+        let mut outcome = V3ImportOutcome {
+            application_number: String::new(),
+            sequences: Vec::new(),
+            uploaded: 0,
+            deduplicated: 0,
+            unmapped_headings: Vec::new(),
+            warnings: Vec::new(),
+        };
+
+        for sequence in &sequences {
+            // Later sequences only repeat what changed; keep the rest from earlier ones
+            let h = &sequence.header;
+            header.applicant = h.applicant.clone().or(header.applicant);
+            header.application_number = h.application_number.clone().or(header.application_number);
+            header.application_type = h.application_type.clone().or(header.application_type);
+            let app_number = header.application_number.clone()
+                .context("No application number found in the regional XML")?;
+            outcome.application_number = app_number.clone();
+
+            let unit_id = derived_id(&["unit", &app_number, &sequence.folder]);
+            let already_imported = repo.submission_exists(unit_id).await?;
+
+            let mut unit = SubmissionUnit {
+                xmlns: "urn:hl7-org:v3".to_string(),
+                xmlns_xsi: None,
+                schema_location: None,
+                id: unit_id.to_string(),
+                code: h.submission_type.clone().unwrap_or_else(|| "original-application".to_string()),
+                code_system: CODE_SYSTEM.to_string(),
+                status_code: "active".to_string(),
+                submission: Submission {
+                    id: derived_id(&["submission", &app_number, &sequence.folder]).to_string(),
+                    code: format!("seq-{:04}", sequence.number),
+                    code_system: CODE_SYSTEM.to_string(),
+                    sequence_number: SequenceNumber { value: sequence.number },
+                },
+                application: Application {
+                    id: derived_id(&["application", &app_number]).to_string(),
+                    code: header.application_type.clone().unwrap_or_else(|| "nda".to_string()).to_lowercase(),
+                    code_system: CODE_SYSTEM.to_string(),
+                    application_number: ApplicationNumber {
+                        code: app_number.clone(),
+                        code_system: CODE_SYSTEM.to_string(),
+                    },
+                },
+                applicant: Applicant {
+                    sponsoring_organization: SponsoringOrganization {
+                        name: header.applicant.clone().unwrap_or_else(|| "Unknown".to_string()),
+                    },
+                },
+                context_of_use: vec![],
+                documents: vec![],
+                keyword_definitions: None,
+            };
+
+            // 1. Map leaves (always, so later sequences can resolve their lifecycle targets)
+            let mut files: Vec<PathBuf> = Vec::new();
+            let mut priorities: HashMap<String, u32> = HashMap::new();
+            let mut keywords: Vec<KeywordDefinition> = Vec::new();
+
+            for leaf in &sequence.leaves {
+                let key = LeafRef { sequence: sequence.folder.clone(), leaf_id: leaf.id.clone() };
+                let context_id = derived_id(&["context", &app_number, &sequence.folder, &leaf.id]);
+                let target = leaf.modified_file.as_ref().and_then(|r| history.get(r)).cloned();
+
+                if leaf.operation != LeafOperation::New && target.is_none() {
+                    outcome.warnings.push(format!(
+                        "{}/{}: {:?} target {:?} not found; {}",
+                        sequence.folder, leaf.id, leaf.operation, leaf.modified_file,
+                        if leaf.operation == LeafOperation::Delete { "skipped" } else { "imported as new" }
+                    ));
+                }
+
+                if leaf.operation == LeafOperation::Delete {
+                    if let Some(target) = target {
+                        unit.context_of_use.push(ContextOfUse {
+                            id: context_id.to_string(),
+                            code: target.code,
+                            code_system: CODE_SYSTEM.to_string(),
+                            status_code: "suspended".to_string(),
+                            priority_number: PriorityNumber { value: target.priority },
+                            document_reference: None,
+                            related_context_of_use: Some(replaces(target.context_id)),
+                            keywords: vec![],
+                        });
+                    }
+                    continue;
+                }
+
+                let (Some(href), Some(file)) = (&leaf.href, &leaf.file) else {
+                    anyhow::bail!("Leaf {} in sequence {} has no xlink:href", leaf.id, sequence.folder);
+                };
+
+                // A replacement stays in its predecessor's context
+                let code = match (&target, leaf.operation) {
+                    (Some(t), LeafOperation::Replace) => t.code.clone(),
+                    _ => match context_code_for(&leaf.heading, href) {
+                        Some(code) => code.to_string(),
+                        None => {
+                            unmapped.insert(leaf.heading.clone());
+                            leaf.heading.clone()
+                        }
+                    },
+                };
+                // Replacements take their predecessor's slot; appends go right after it
+                let next = priorities.entry(code.clone()).or_insert(0);
+                let priority = match (&target, leaf.operation) {
+                    (Some(t), LeafOperation::Replace) => t.priority,
+                    (Some(t), LeafOperation::Append) => (*next + 1).max(t.priority + 1),
+                    _ => *next + 1,
+                };
+                *next = (*next).max(priority);
+
+                let doc_id = derived_id(&["document", &app_number, &sequence.folder, &leaf.id]);
+                let path = app_dir.join(file);
+
+                let mut cou_keywords = Vec::new();
+                for title in &leaf.node_extensions {
+                    let kw_code = format!("v3-node-{}", &hex::encode(Sha256::digest(title.as_bytes()))[..12]);
+                    if !keywords.iter().any(|k| k.code == kw_code) {
+                        keywords.push(KeywordDefinition {
+                            code: kw_code.clone(),
+                            code_system: KEYWORD_CODE_SYSTEM.to_string(),
+                            value: KeywordDefinitionValue {
+                                item: KeywordDefinitionItem {
+                                    code: kw_code.clone(),
+                                    display_name: DisplayName { value: title.clone() },
+                                },
+                            },
+                            status_code: None,
+                        });
+                    }
+                    cou_keywords.push(Keyword { code: kw_code, code_system: KEYWORD_CODE_SYSTEM.to_string() });
+                }
+
+                unit.context_of_use.push(ContextOfUse {
+                    id: context_id.to_string(),
+                    code: code.clone(),
+                    code_system: CODE_SYSTEM.to_string(),
+                    status_code: "active".to_string(),
+                    priority_number: PriorityNumber { value: priority },
+                    document_reference: Some(DocumentReference { id: DocumentIdRef { root: doc_id.to_string() } }),
+                    related_context_of_use: match (&target, leaf.operation) {
+                        (Some(t), LeafOperation::Replace) => Some(replaces(t.context_id)),
+                        _ => None,
+                    },
+                    keywords: cou_keywords,
+                });
+                unit.documents.push(Document {
+                    id: doc_id.to_string(),
+                    title: DocumentTitle { value: leaf.title.trim().to_string() },
+                    text: DocumentText {
+                        reference: DocumentReferencePath { value: href.clone() },
+                        // Filled in from the file when the sequence is staged
+                        checksum: String::new(),
+                        checksum_algorithm: "SHA256".to_string(),
+                        media_type: String::new(),
+                    },
+                });
+                files.push(path);
+
+                history.insert(key, ImportedLeaf { context_id, code, priority });
+            }
+
+            if !keywords.is_empty() {
+                unit.keyword_definitions = Some(keywords);
+            }
+
+            if already_imported {
+                outcome.sequences.push(ImportedSequence {
+                    sequence: sequence.folder.clone(),
+                    unit_id,
+                    documents: unit.documents.len(),
+                    contexts_of_use: unit.context_of_use.len(),
+                    already_imported: true,
+                });
+                continue;
+            }
+
+            // 2. Stage: verify the v3 MD5, then store by SHA-256
+            let leaves_with_files = sequence.leaves.iter().filter(|l| l.operation != LeafOperation::Delete);
+            let mut staged: Vec<StoredBlob> = Vec::new();
+            let staging = async {
+                for ((doc, path), leaf) in unit.documents.iter_mut().zip(&files).zip(leaves_with_files) {
+                    verify_md5(path, leaf.checksum.as_deref(), leaf.checksum_type.as_deref())?;
+
+                    let (hash, size) = hash_file(path).await?;
+                    let content_type = sniff_media_type(path).await?
+                        .unwrap_or("application/octet-stream");
+                    doc.text.checksum = hash.clone();
+                    doc.text.media_type = content_type.to_string();
+
+                    let blob = self.store_blob(path, &hash, size, content_type).await
+                        .context(format!("Upload failed for {}/{}", sequence.folder, doc.text.reference.value))?;
+                    staged.push(blob);
+                }
+                Ok::<(), anyhow::Error>(())
+            }.await;
+            if let Err(e) = staging {
+                self.discard_staged(&staged).await;
+                return Err(e.context(format!("Import stopped at sequence {}; earlier sequences are kept", sequence.folder)));
+            }
+
+            // 3. Commit, with its audit record
+            let deduplicated = staged.iter().filter(|b| b.deduplicated).count();
+            let committed = async {
+                let mut tx = self.pool.begin().await?;
+                repo.create_submission(&mut tx, &unit).await?;
+                self.record_audit(
+                    &mut tx,
+                    "submission.import",
+                    "submission_unit",
+                    Some(unit_id),
+                    None,
+                    Some(json!({
+                        "source": app_dir.join(&sequence.folder).to_string_lossy(),
+                        "format": "eCTD v3.2.2",
+                        "applicationNumber": app_number,
+                        "sequenceNumber": sequence.number,
+                        "documents": unit.documents.len(),
+                        "contextsOfUse": unit.context_of_use.len(),
+                        "uploaded": staged.len() - deduplicated,
+                        "deduplicated": deduplicated,
+                    })),
+                ).await?;
+                tx.commit().await?;
+                Ok::<(), anyhow::Error>(())
+            }.await;
+            if let Err(e) = committed {
+                self.discard_staged(&staged).await;
+                return Err(e.context(format!("Failed to persist sequence {}; uploads rolled back", sequence.folder)));
+            }
+            outcome.uploaded += staged.len() - deduplicated;
+            outcome.deduplicated += deduplicated;
+
+            outcome.sequences.push(ImportedSequence {
+                sequence: sequence.folder.clone(),
+                unit_id,
+                documents: unit.documents.len(),
+                contexts_of_use: unit.context_of_use.len(),
+                already_imported: false,
+            });
+        }
+
+        outcome.unmapped_headings = unmapped.into_iter().collect();
+        Ok(outcome)
+    }
+}
+
+fn replaces(context_id: Uuid) -> RelatedContextOfUse {
+    RelatedContextOfUse {
+        id: DocumentIdRef { root: context_id.to_string() },
+        relationship_name: "replaces".to_string(),
+    }
+}
+
+/// Stable IDs: the same application, sequence and leaf always map to the same UUID.
+fn derived_id(parts: &[&str]) -> Uuid {
+    let mut hasher = Sha256::new();
+    hasher.update(b"ectd-v3-import");
+    for part in parts {
+        hasher.update([0x1f]);
+        hasher.update(part.as_bytes());
+    }
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Builder::from_custom_bytes(bytes).into_uuid()
+}
+
+/// v3.2.2 leaves carry an MD5; a mismatch means the legacy archive is damaged.
+fn verify_md5(path: &Path, checksum: Option<&str>, checksum_type: Option<&str>) -> Result<()> {
+    let Some(expected) = checksum else { return Ok(()) };
+    if !checksum_type.is_none_or(|t| t.eq_ignore_ascii_case("md5")) {
+        return Ok(());
+    }
+
+    let mut file = File::open(path).context(format!("Required file not found: {:?}", path))?;
+    let mut hasher = Md5::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    let actual = hex::encode(hasher.finalize());
+
+    if !actual.eq_ignore_ascii_case(expected) {
+        anyhow::bail!("MD5 mismatch for {:?}: backbone declares {}, file is {}", path, expected, actual);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_ids_are_stable_and_distinct() {
+        let a = derived_id(&["context", "123456", "0001", "leaf-1"]);
+        assert_eq!(a, derived_id(&["context", "123456", "0001", "leaf-1"]));
+        assert_ne!(a, derived_id(&["context", "123456", "0002", "leaf-1"]));
+        // Parts are delimited, so shifting characters between them changes the ID
+        assert_ne!(derived_id(&["ab", "c"]), derived_id(&["a", "bc"]));
+    }
+}
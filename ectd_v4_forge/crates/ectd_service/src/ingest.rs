@@ -0,0 +1,162 @@
+use crate::EctdService;
+use crate::blobs::{hash_file, sniff_media_type, StoredBlob};
+use anyhow::{Context, Result};
+use quick_xml::de::from_str;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use ectd_core::media_type;
+use ectd_core::models::submission_unit::SubmissionUnit;
+use ectd_db::repository::SubmissionRepository;
+
+/// Name of the backbone file inside a package directory.
+pub const SUBMISSION_UNIT_FILE: &str = "submissionunit.xml";
+
+#[derive(Debug)]
+pub struct IngestParams {
+    /// Package directory, or its submissionunit.xml; documents are resolved relative to it
+    pub path: PathBuf,
+    /// Overrides the submission ID in the XML
+    pub submission_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IngestOutcome {
+    pub unit_id: Uuid,
+    pub uploaded: usize,
+    pub deduplicated: usize,
+}
+
+impl EctdService {
+    /// Ingests a package directory (or its submissionunit.xml) exactly as declared.
+    pub async fn ingest_package(&self, path: impl AsRef<Path>) -> Result<IngestOutcome> {
+        self.ingest_package_with(IngestParams {
+            path: path.as_ref().to_path_buf(),
+            submission_id: None,
+        }).await
+    }
+
+    /// Saga-style ingest of a submissionunit.xml and its documents:
+    ///   1. stage: upload every document (content-addressed, deduplicated)
+    ///   2. commit: persist all metadata in one database transaction
+    ///   3. compensate: if any step fails, remove the objects this call uploaded
+    ///
+    /// Every document's checksum and declared mediaType are verified against the file.
+    pub async fn ingest_package_with(&self, params: IngestParams) -> Result<IngestOutcome> {
+        self.store.ensure_ready().await
+            .context("Failed to initialize storage backend")?;
+
+        // 1. Parse
+        let xml_path = if params.path.is_dir() {
+            params.path.join(SUBMISSION_UNIT_FILE)
+        } else {
+            params.path.clone()
+        };
+        let xml_content = tokio::fs::read_to_string(&xml_path).await
+            .context(format!("Failed to read file: {:?}", xml_path))?;
+        let mut unit: SubmissionUnit = from_str(&xml_content)
+            .context("XML Parsing Error")?;
+
+        if let Some(sub_id) = params.submission_id {
+            unit.submission.id = sub_id;
+        }
+
+        let base_dir = xml_path.parent().unwrap_or_else(|| Path::new("."));
+
+        // 2. Stage
+        let mut staged: Vec<StoredBlob> = Vec::new();
+        if let Err(e) = self.stage_documents(&mut unit, base_dir, &mut staged).await {
+            self.discard_staged(&staged).await;
+            return Err(e);
+        }
+
+        // 3. Commit, with its audit record (compensate the uploads if it doesn't land)
+        let repo = SubmissionRepository::new(self.pool.clone());
+        let deduplicated = staged.iter().filter(|b| b.deduplicated).count();
+        let committed = async {
+            let mut tx = self.pool.begin().await?;
+            let unit_id = repo.create_submission(&mut tx, &unit).await?;
+            let outcome = IngestOutcome {
+                unit_id,
+                uploaded: staged.len() - deduplicated,
+                deduplicated,
+            };
+            self.record_audit(
+                &mut tx,
+                "submission.ingest",
+                "submission_unit",
+                Some(unit_id),
+                None,
+                Some(json!({
+                    "source": xml_path.to_string_lossy(),
+                    "applicationNumber": unit.application.application_number.code,
+                    "sequenceNumber": unit.submission.sequence_number.value,
+                    "documents": unit.documents.len(),
+                    "contextsOfUse": unit.context_of_use.len(),
+                    "uploaded": outcome.uploaded,
+                    "deduplicated": outcome.deduplicated,
+                })),
+            ).await?;
+            tx.commit().await?;
+            Ok::<_, anyhow::Error>(outcome)
+        }.await;
+        match committed {
+            Ok(outcome) => Ok(outcome),
+            Err(e) => {
+                self.discard_staged(&staged).await;
+                Err(e.context("Failed to persist submission unit; uploads rolled back"))
+            }
+        }
+    }
+
+    /// Verifies and uploads each document, pushing onto `staged` as it goes so the
+    /// caller can compensate exactly what was stored before a failure.
+    /// Detected media types replace the declared ones (they agree, or we bail).
+    async fn stage_documents(
+        &self,
+        unit: &mut SubmissionUnit,
+        base_dir: &Path,
+        staged: &mut Vec<StoredBlob>,
+    ) -> Result<()> {
+        for doc in &mut unit.documents {
+            let rel_path = doc.text.reference.value.clone();
+            let file_path = base_dir.join(&rel_path);
+
+            if !file_path.exists() {
+                anyhow::bail!("Required file not found: {:?}", file_path);
+            }
+
+            // The declared checksum is the content address, so it has to be right
+            let (hash, size) = hash_file(&file_path).await?;
+            if !hash.eq_ignore_ascii_case(&doc.text.checksum) {
+                anyhow::bail!(
+                    "Checksum mismatch for {}: XML declares {}, file is {}",
+                    rel_path, doc.text.checksum, hash
+                );
+            }
+
+            // Content beats claims: the declared mediaType must match what the bytes are
+            let declared = media_type::normalize(&doc.text.media_type);
+            match sniff_media_type(&file_path).await? {
+                Some(detected) if !declared.is_empty() && declared != detected => {
+                    anyhow::bail!(
+                        "Media type mismatch for {}: XML declares {}, content is {}",
+                        rel_path, doc.text.media_type, detected
+                    );
+                }
+                Some(detected) => doc.text.media_type = detected.to_string(),
+                None if declared.is_empty() => {
+                    anyhow::bail!("Unrecognized content and no mediaType declared for {}", rel_path);
+                }
+                None => doc.text.media_type = declared, // Unsupported type: can't verify, keep the claim
+            }
+
+            let blob = self.store_blob(&file_path, &hash, size, &doc.text.media_type).await
+                .context(format!("Upload failed for {}", rel_path))?;
+            self.index_text(&file_path, &blob.sha256, &doc.text.media_type).await;
+            staged.push(blob);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{EctdService, ServiceError};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use uuid::Uuid;
+use ectd_db::job::{JobKind, JobRecord, JobRepository, JobStatus};
+use ectd_db::repository::SubmissionRepository;
+
+impl EctdService {
+    /// Records a queued job for a unit, requested by the current actor.
+    /// Running it is up to the caller (see the API's job runner).
+    pub async fn create_job(&self, kind: JobKind, unit_id: Uuid, parameters: Value) -> Result<JobRecord> {
+        if !self.unit_exists(unit_id).await? {
+            return Err(ServiceError::submission_not_found(unit_id).into());
+        }
+
+        let repo = JobRepository::new(self.pool.clone());
+        repo.create(Uuid::now_v7(), kind, unit_id, &self.actor, &parameters).await
+            .context("Failed to create job")
+    }
+
+    pub async fn get_job(&self, id: Uuid) -> Result<JobRecord> {
+        JobRepository::new(self.pool.clone()).get(id).await
+            .context("Failed to fetch job")?
+            .ok_or_else(|| ServiceError::NotFound(format!("Job {}", id)).into())
+    }
+
+    /// Jobs of one unit, newest first.
+    pub async fn list_jobs(&self, unit_id: Uuid) -> Result<Vec<JobRecord>> {
+        if !self.unit_exists(unit_id).await? {
+            return Err(ServiceError::submission_not_found(unit_id).into());
+        }
+        JobRepository::new(self.pool.clone()).list_for_unit(unit_id).await
+            .context("Failed to list jobs")
+    }
+
+    pub async fn start_job(&self, id: Uuid) -> Result<JobRecord> {
+        JobRepository::new(self.pool.clone()).mark_running(id).await
+            .context("Failed to mark job running")?;
+        self.get_job(id).await
+    }
+
+    pub async fn report_job_progress(&self, id: Uuid, progress: &Value) -> Result<()> {
+        JobRepository::new(self.pool.clone()).update_progress(id, progress).await
+            .context("Failed to record job progress")
+    }
+
+    pub async fn finish_job(&self, id: Uuid, outcome: Result<Value>) -> Result<JobRecord> {
+        let repo = JobRepository::new(self.pool.clone());
+        let finished = match outcome {
+            Ok(result) => repo.finish(id, JobStatus::Succeeded, Some(&result), None).await,
+            Err(e) => {
+                // Partial exports still say which files failed
+                let result = e.downcast_ref::<crate::ExportIncomplete>()
+                    .map(|incomplete| json!({ "failures": incomplete.failures }));
+                repo.finish(id, JobStatus::Failed, result.as_ref(), Some(&format!("{:#}", e))).await
+            }
+        };
+        finished.context("Failed to record job outcome")
+    }
+
+    /// Marks jobs orphaned by a previous process as failed. Returns how many there were.
+    pub async fn fail_interrupted_jobs(&self) -> Result<u64> {
+        JobRepository::new(self.pool.clone())
+            .fail_interrupted("Interrupted: the server stopped before the job finished")
+            .await
+            .context("Failed to clean up interrupted jobs")
+    }
+
+    async fn unit_exists(&self, unit_id: Uuid) -> Result<bool> {
+        SubmissionRepository::new(self.pool.clone()).submission_exists(unit_id).await
+            .context("Failed to look up submission unit")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::{ExportFailure, ExportIncomplete};
+
+    #[sqlx::test(migrations = false)]
+    async fn test_job_moves_from_queued_through_running_to_succeeded(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let unit = testing::new_unit(&service, "100001").await;
+
+        let job = service.create_job(JobKind::Validation, unit, json!({})).await.unwrap();
+        assert_eq!(job.status, "queued");
+        assert_eq!(job.requested_by, "tester");
+        assert!(job.started_at.is_none());
+
+        let running = service.start_job(job.id).await.unwrap();
+        assert_eq!(running.status, "running");
+        assert!(running.started_at.is_some() && !running.is_finished());
+
+        let done = service.finish_job(job.id, Ok(json!({ "valid": true }))).await.unwrap();
+        assert_eq!(done.status, "succeeded");
+        assert_eq!(done.result, Some(json!({ "valid": true })));
+        assert!(done.error.is_none() && done.finished_at.is_some() && done.is_finished());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_failed_job_records_the_error(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let unit = testing::new_unit(&service, "100001").await;
+        let job = service.create_job(JobKind::Export, unit, json!({})).await.unwrap();
+        service.start_job(job.id).await.unwrap();
+
+        let incomplete = ExportIncomplete {
+            failures: vec![ExportFailure { file_name: "m1/us/cover.pdf".to_string(), error: "not in store".to_string() }],
+        };
+        let outcome = Err(anyhow::Error::from(incomplete).context("Export of seq-0001 failed"));
+        let failed = service.finish_job(job.id, outcome).await.unwrap();
+
+        assert_eq!(failed.status, "failed");
+        assert_eq!(
+            failed.error.as_deref(),
+            Some("Export of seq-0001 failed: 1 document(s) could not be exported: m1/us/cover.pdf (not in store)")
+        );
+        assert_eq!(
+            failed.result,
+            Some(json!({ "failures": [{ "fileName": "m1/us/cover.pdf", "error": "not in store" }] }))
+        );
+        assert_eq!(service.get_job(job.id).await.unwrap().status, "failed");
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_only_queued_jobs_start(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let unit = testing::new_unit(&service, "100001").await;
+        let job = service.create_job(JobKind::Validation, unit, json!({})).await.unwrap();
+        service.start_job(job.id).await.unwrap();
+        service.finish_job(job.id, Err(anyhow::anyhow!("boom"))).await.unwrap();
+
+        let restarted = service.start_job(job.id).await.unwrap();
+        assert_eq!(restarted.status, "failed");
+        assert_eq!(restarted.error.as_deref(), Some("boom"));
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_job_for_unknown_unit_is_not_found(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let err = service.create_job(JobKind::Export, Uuid::new_v4(), json!({})).await.unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(ServiceError::NotFound(_))), "{err:#}");
+    }
+}
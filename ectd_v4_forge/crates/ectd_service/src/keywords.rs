@@ -0,0 +1,259 @@
+use crate::{EctdService, ServiceError};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use ectd_core::models::context_of_use::Keyword;
+use ectd_core::models::keyword_definition::{
+    DisplayName, KeywordDefinition, KeywordDefinitionItem, KeywordDefinitionValue, KeywordType,
+};
+use ectd_db::keyword::{KeywordEntry, KeywordRepository};
+use ectd_db::repository::SubmissionRepository;
+
+/// A keyword to tag a context of use with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct KeywordRef {
+    /// Defaults to "sponsor"
+    #[serde(rename = "type", default)]
+    pub keyword_type: KeywordType,
+    /// Code of a keyword defined in this unit or an earlier sequence
+    pub code: String,
+}
+
+impl KeywordRef {
+    pub fn to_keyword(&self) -> Keyword {
+        Keyword { code: self.code.clone(), code_system: self.keyword_type.code_system().to_string() }
+    }
+}
+
+/// "type:code", or a bare code for a sponsor keyword.
+impl FromStr for KeywordRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (keyword_type, code) = match s.split_once(':') {
+            Some((t, code)) => {
+                let keyword_type = KeywordType::from_code(t.trim()).ok_or_else(|| format!(
+                    "unknown keyword type '{}' (one of: {})",
+                    t,
+                    KeywordType::ALL.map(|t| t.code()).join(", ")
+                ))?;
+                (keyword_type, code)
+            }
+            None => (KeywordType::Sponsor, s),
+        };
+        let code = code.trim();
+        if code.is_empty() {
+            return Err(format!("'{}' has no keyword code", s));
+        }
+        Ok(KeywordRef { keyword_type, code: code.to_string() })
+    }
+}
+
+impl fmt::Display for KeywordRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.keyword_type.code(), self.code)
+    }
+}
+
+impl EctdService {
+    /// The keyword definitions this unit submits; see `keyword_vocabulary`
+    /// for everything its contexts may use.
+    pub async fn list_keywords(&self, submission_id: Uuid) -> Result<Vec<KeywordDefinition>> {
+        Ok(self.get_submission(submission_id).await?.keyword_definitions.unwrap_or_default())
+    }
+
+    /// The keywords in force for the unit: those defined by this or an earlier
+    /// sequence of the application, each as its latest sequence left it.
+    pub async fn keyword_vocabulary(&self, submission_id: Uuid) -> Result<Vec<KeywordEntry>> {
+        self.ensure_submission(submission_id).await?;
+        KeywordRepository::new(self.pool.clone()).vocabulary(submission_id).await
+            .context("Failed to fetch keywords")
+    }
+
+    /// Defines a keyword in this unit, or renames it. A keyword of an earlier
+    /// sequence is renamed (or reinstated) from this sequence on.
+    pub async fn put_keyword(
+        &self,
+        submission_id: Uuid,
+        keyword_type: KeywordType,
+        code: &str,
+        display_name: &str,
+    ) -> Result<KeywordDefinition> {
+        if code.trim().is_empty() || display_name.trim().is_empty() {
+            return Err(ServiceError::Invalid("keyword code and displayName must not be empty".to_string()).into());
+        }
+        if code.len() > 64 || code.contains(':') {
+            return Err(ServiceError::Invalid(format!("keyword code '{}' must be at most 64 characters, without ':'", code)).into());
+        }
+        self.ensure_submission(submission_id).await?;
+        self.save_keyword(submission_id, keyword_type, code, display_name, "active").await
+    }
+
+    /// Retires a keyword from this sequence on. Contexts of use of this unit
+    /// must be untagged first; later sequences can no longer use it.
+    pub async fn retire_keyword(&self, submission_id: Uuid, keyword_type: KeywordType, code: &str) -> Result<KeywordDefinition> {
+        let keyword = KeywordRef { keyword_type, code: code.to_string() };
+        let entry = self.keyword_vocabulary(submission_id).await?
+            .into_iter()
+            .find(|e| e.code_system == keyword_type.code_system() && e.code == code)
+            .ok_or_else(|| ServiceError::NotFound(format!("Keyword '{}'", keyword)))?;
+        if !entry.is_active() {
+            return Err(ServiceError::Conflict(format!("Keyword '{}' is already retired", keyword)).into());
+        }
+        if entry.usage > 0 {
+            return Err(ServiceError::Conflict(format!(
+                "Keyword '{}' tags {} context(s) of use of this unit", keyword, entry.usage
+            )).into());
+        }
+        self.save_keyword(submission_id, keyword_type, code, &entry.display_name, "inactive").await
+    }
+
+    /// Removes this unit's definition; an earlier sequence's definition, if
+    /// there is one, is in force again.
+    pub async fn delete_keyword(&self, submission_id: Uuid, keyword_type: KeywordType, code: &str) -> Result<()> {
+        let keyword = KeywordRef { keyword_type, code: code.to_string() };
+        let code_system = keyword_type.code_system();
+        let Some(before) = self.find_keyword(submission_id, code_system, code).await? else {
+            return Err(ServiceError::NotFound(format!("Keyword '{}'", keyword)).into());
+        };
+
+        let repo = KeywordRepository::new(self.pool.clone());
+        let usage = repo.vocabulary(submission_id).await?
+            .into_iter()
+            .find(|e| e.code_system == code_system && e.code == code)
+            .map_or(0, |e| e.usage);
+        let inherited = repo.inherited(submission_id).await?
+            .into_iter()
+            .any(|e| e.code_system == code_system && e.code == code && e.is_active());
+        if usage > 0 && !inherited {
+            return Err(ServiceError::Conflict(format!(
+                "Keyword '{}' tags {} context(s) of use of this unit", keyword, usage
+            )).into());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        SubmissionRepository::new(self.pool.clone())
+            .delete_keyword_definition(&mut tx, submission_id, code_system, code).await
+            .context("Failed to delete keyword definition")?;
+        self.record_audit(
+            &mut tx,
+            "keyword_definition.delete",
+            "keyword_definition",
+            Some(submission_id),
+            Some(keyword_state(&before)),
+            None,
+        ).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The keywords for a context of use; each must be in force for the unit.
+    pub(crate) async fn resolve_keywords(&self, submission_id: Uuid, refs: &[KeywordRef]) -> Result<Vec<Keyword>> {
+        if refs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let vocabulary = self.keyword_vocabulary(submission_id).await?;
+
+        let mut keywords: Vec<Keyword> = Vec::new();
+        for keyword in refs {
+            let code_system = keyword.keyword_type.code_system();
+            match vocabulary.iter().find(|e| e.code_system == code_system && e.code == keyword.code) {
+                Some(e) if e.is_active() => {}
+                Some(e) => return Err(ServiceError::Invalid(format!(
+                    "Keyword '{}' was retired in sequence {}", keyword, e.sequence_number
+                )).into()),
+                None => return Err(ServiceError::Invalid(format!(
+                    "Keyword '{}' is not defined for this application; define it first", keyword
+                )).into()),
+            }
+            if !keywords.iter().any(|k| k.code == keyword.code && k.code_system == code_system) {
+                keywords.push(keyword.to_keyword());
+            }
+        }
+        Ok(keywords)
+    }
+
+    async fn save_keyword(
+        &self,
+        submission_id: Uuid,
+        keyword_type: KeywordType,
+        code: &str,
+        display_name: &str,
+        status: &str,
+    ) -> Result<KeywordDefinition> {
+        let before = self.find_keyword(submission_id, keyword_type.code_system(), code).await?;
+        let def = KeywordDefinition {
+            code: code.to_string(),
+            code_system: keyword_type.code_system().to_string(),
+            value: KeywordDefinitionValue {
+                item: KeywordDefinitionItem {
+                    code: code.to_string(),
+                    display_name: DisplayName { value: display_name.to_string() },
+                },
+            },
+            status_code: Some(status.to_string()),
+        };
+
+        let repo = SubmissionRepository::new(self.pool.clone());
+        let mut tx = self.pool.begin().await?;
+        repo.upsert_keyword_definition(&mut tx, submission_id, &def).await
+            .context("Failed to save keyword definition")?;
+        self.record_audit(
+            &mut tx,
+            if status == "active" { "keyword_definition.put" } else { "keyword_definition.retire" },
+            "keyword_definition",
+            Some(submission_id),
+            before.as_ref().map(keyword_state),
+            Some(keyword_state(&def)),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(def)
+    }
+
+    async fn find_keyword(&self, submission_id: Uuid, code_system: &str, code: &str) -> Result<Option<KeywordDefinition>> {
+        Ok(self.list_keywords(submission_id).await?
+            .into_iter()
+            .find(|k| k.code == code && k.code_system == code_system))
+    }
+}
+
+fn keyword_state(def: &KeywordDefinition) -> Value {
+    json!({
+        "code": def.code,
+        "codeSystem": def.code_system,
+        "displayName": def.value.item.display_name.value,
+        "statusCode": def.status_code,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_refs_parse_with_and_without_type() {
+        let sponsor: KeywordRef = "pediatric".parse().unwrap();
+        assert_eq!(sponsor.keyword_type, KeywordType::Sponsor);
+        assert_eq!(sponsor.code, "pediatric");
+
+        let study: KeywordRef = "study-id:ABC-101".parse().unwrap();
+        assert_eq!(study.keyword_type, KeywordType::StudyId);
+        assert_eq!(study.to_string(), "study-id:ABC-101");
+        assert_eq!(study.to_keyword().code_system, KeywordType::StudyId.code_system());
+
+        assert!("colour:blue".parse::<KeywordRef>().is_err());
+        assert!("manufacturer:".parse::<KeywordRef>().is_err());
+
+        let json: KeywordRef = serde_json::from_value(json!({ "type": "dosage-form", "code": "tablet" })).unwrap();
+        assert_eq!(json.keyword_type, KeywordType::DosageForm);
+        let json: KeywordRef = serde_json::from_value(json!({ "code": "pediatric" })).unwrap();
+        assert_eq!(json, sponsor);
+    }
+}
@@ -1,56 +1,91 @@
 pub mod documents;
+pub mod applications;
 pub mod submission;
 pub mod export;
+pub mod audit;
+pub mod signatures;
+pub mod storage;
+pub mod blobs;
+pub mod ingest;
+pub mod package;
+pub mod verify;
+pub mod import_v3;
+pub mod contexts;
+pub mod keywords;
+pub mod validation;
+pub mod error;
+pub mod jobs;
+pub mod listing;
+pub mod search;
+pub mod planning;
+pub mod templates;
+pub mod studies;
+pub mod priorities;
+pub mod manifest;
+#[cfg(test)]
+mod testing;
 
 use sqlx::PgPool;
-use aws_sdk_s3::Client as S3Client;
-use aws_sdk_s3::error::SdkError;
+use std::sync::Arc;
 
 // Re-export common types
-pub use documents::AddDocumentParams;
+pub use documents::{AddDocumentParams, AttachedDocument, DocumentReuse, PlannedDocument};
 pub use submission::InitSubmissionParams;
-pub use export::ExportProgress;
+pub use applications::{ApplicationPatch, NewApplicationParams};
+pub use export::{ExportFailure, ExportIncomplete, ExportOptions, ExportProgress};
+pub use package::ArchiveFormat;
+pub use signatures::{SignParams, SignatureStatus};
+pub use storage::{DocumentStore, StorageBackend};
+pub use blobs::{GcReport, StoredBlob};
+pub use ingest::{IngestOutcome, IngestParams};
+pub use verify::{PackageReport, verify_package};
+pub use import_v3::{ImportedSequence, V3ImportOutcome};
+pub use submission::SubmissionPatch;
+pub use contexts::ContextPatch;
+pub use keywords::KeywordRef;
+pub use error::ServiceError;
+pub use search::ReindexReport;
+pub use planning::{NewPlaceholderParams, PlaceholderPatch, ReadinessReport};
+pub use studies::{NewStudyParams, StudyDocumentParams, StudyDocumentRole, StudyPatch};
+pub use priorities::{MoveContextParams, MoveDirection, ReorderContextsParams};
+pub use manifest::{ManifestEntry, read_manifest};
 
 #[derive(Clone)]
 pub struct EctdService {
     pub pool: PgPool,
-    pub s3: S3Client,
-    pub bucket: String,
+    /// Where document bytes live (S3, local directory, memory).
+    pub store: Arc<dyn DocumentStore>,
+    /// Who is performing mutating calls; recorded in the audit trail.
+    pub actor: String,
+    /// Optional justification attached to audit records (e.g. "Response to IR #3").
+    pub reason: Option<String>,
+    /// When set, exports refuse units without a valid "approve" signature.
+    pub require_approval: bool,
 }
 
 impl EctdService {
-    pub fn new(pool: PgPool, s3: S3Client, bucket: String) -> Self {
+    pub fn new(pool: PgPool, store: Arc<dyn DocumentStore>) -> Self {
         Self {
             pool,
-            s3,
-            bucket,
+            store,
+            actor: "system".to_string(),
+            reason: None,
+            require_approval: false,
         }
     }
 
-    /// Idempotent check: Creates the bucket if it doesn't exist.
-    /// Returns Ok if ready, Err if critical failure.
-    pub async fn ensure_bucket(&self) -> Result<(), anyhow::Error> {
-        // 1. Check existence (HeadBucket)
-        let head = self.s3.head_bucket()
-            .bucket(&self.bucket)
-            .send()
-            .await;
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = actor.into();
+        self
+    }
 
-        match head {
-            Ok(_) => Ok(()), // Exists
-            Err(SdkError::ServiceError(err)) if err.err().is_not_found() => {
-                // 2. Create if missing (404)
-                println!("⚠️  Bucket '{}' not found. Creating...", self.bucket);
-                self.s3.create_bucket()
-                    .bucket(&self.bucket)
-                    .send()
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Failed to create bucket: {}", e))?;
+    pub fn with_reason(mut self, reason: Option<String>) -> Self {
+        self.reason = reason;
+        self
+    }
 
-                println!("✅ Bucket created.");
-                Ok(())
-            },
-            Err(e) => Err(anyhow::anyhow!("Failed to check bucket state: {}", e)),
-        }
+    pub fn with_approval_required(mut self, required: bool) -> Self {
+        self.require_approval = required;
+        self
     }
 }
@@ -0,0 +1,42 @@
+use crate::{EctdService, ServiceError};
+use anyhow::Result;
+use uuid::Uuid;
+use ectd_db::listing::{
+    ApplicationSummary, DocumentSummary, ListingError, ListingRepository, Page, PageRequest,
+    SubmissionFilter, SubmissionSort,
+};
+use ectd_db::repository::SubmissionSummary;
+
+impl EctdService {
+    pub async fn list_applications(&self, filter: &SubmissionFilter, page: &PageRequest) -> Result<Page<ApplicationSummary>> {
+        let repo = ListingRepository::new(self.pool.clone());
+        repo.list_applications(filter, page).await.map_err(listing_error)
+    }
+
+    pub async fn list_submissions(
+        &self,
+        filter: &SubmissionFilter,
+        sort: SubmissionSort,
+        page: &PageRequest,
+    ) -> Result<Page<SubmissionSummary>> {
+        let repo = ListingRepository::new(self.pool.clone());
+        repo.list_submissions(filter, sort, page).await.map_err(listing_error)
+    }
+
+    pub async fn list_documents(
+        &self,
+        filter: &SubmissionFilter,
+        unit_id: Option<Uuid>,
+        page: &PageRequest,
+    ) -> Result<Page<DocumentSummary>> {
+        let repo = ListingRepository::new(self.pool.clone());
+        repo.list_documents(filter, unit_id, page).await.map_err(listing_error)
+    }
+}
+
+pub(crate) fn listing_error(e: ListingError) -> anyhow::Error {
+    match e {
+        ListingError::InvalidCursor => ServiceError::Invalid(e.to_string()).into(),
+        ListingError::Database(e) => anyhow::Error::new(e).context("Failed to list"),
+    }
+}
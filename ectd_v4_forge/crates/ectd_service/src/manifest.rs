@@ -0,0 +1,229 @@
+use crate::{AddDocumentParams, DocumentReuse, KeywordRef, ServiceError};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// One document of a manifest. Relative file paths are relative to the
+/// manifest's folder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestEntry {
+    pub file: PathBuf,
+    pub code: String,
+    pub title: String,
+    /// None: the placeholder's, else the next for the code
+    pub priority: Option<u32>,
+    pub keywords: Vec<KeywordRef>,
+    pub placeholder: Option<Uuid>,
+    pub folder: Option<String>,
+}
+
+impl ManifestEntry {
+    pub fn into_params(self, submission_id: Uuid, reuse: DocumentReuse) -> AddDocumentParams {
+        AddDocumentParams {
+            submission_id,
+            file_path: self.file,
+            context_code: self.code,
+            title: self.title,
+            priority: self.priority,
+            placeholder_id: self.placeholder,
+            keywords: self.keywords,
+            folder: self.folder,
+            reuse,
+        }
+    }
+}
+
+/// A CSV row; `keywords` holds "type:code" references separated by ';'.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CsvRow {
+    file: PathBuf,
+    code: String,
+    title: String,
+    #[serde(default)]
+    priority: Option<u32>,
+    #[serde(default)]
+    keywords: Option<String>,
+    #[serde(default)]
+    placeholder: Option<Uuid>,
+    #[serde(default)]
+    folder: Option<String>,
+}
+
+/// A YAML list item, and a CSV row once its keywords are split; `keywords`
+/// are "type:code" references.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Item {
+    file: PathBuf,
+    code: String,
+    title: String,
+    #[serde(default)]
+    priority: Option<u32>,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    placeholder: Option<Uuid>,
+    #[serde(default)]
+    folder: Option<String>,
+}
+
+/// Reads a document manifest: CSV (`.csv`, with a header row) or YAML
+/// (`.yaml`/`.yml`, a list), with the columns or keys `file`, `code`,
+/// `title` and optionally `priority`, `keywords`, `placeholder` and `folder`.
+pub async fn read_manifest(path: &Path) -> Result<Vec<ManifestEntry>> {
+    let text = tokio::fs::read_to_string(path).await
+        .context(format!("Failed to read manifest {:?}", path))?;
+    let base = path.parent().unwrap_or(Path::new(""));
+    let extension = path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "csv" => parse_csv(&text, base),
+        "yaml" | "yml" => parse_yaml(&text, base),
+        _ => Err(ServiceError::Invalid(format!(
+            "manifest {:?} must be a .csv, .yaml or .yml file", path
+        )).into()),
+    }
+}
+
+fn parse_csv(text: &str, base: &Path) -> Result<Vec<ManifestEntry>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let headers = reader.headers()
+        .map_err(|e| invalid_entry(&csv_line(e.position()), e))?
+        .clone();
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| invalid_entry(&csv_line(e.position()), e))?;
+        // Where the record starts; a quoted line break puts it further down
+        // than its index
+        let line = csv_line(record.position());
+        let row: CsvRow = record.deserialize(Some(&headers)).map_err(|e| invalid_entry(&line, e))?;
+        let keywords = row.keywords.as_deref().unwrap_or_default()
+            .split(';')
+            .filter(|k| !k.trim().is_empty())
+            .map(str::to_string)
+            .collect();
+        let item = Item {
+            file: row.file,
+            code: row.code,
+            title: row.title,
+            priority: row.priority,
+            keywords,
+            placeholder: row.placeholder,
+            folder: row.folder,
+        };
+        entries.push(entry(&line, base, item)?);
+    }
+    finish(entries)
+}
+
+fn parse_yaml(text: &str, base: &Path) -> Result<Vec<ManifestEntry>> {
+    let items: Vec<Item> = serde_yaml::from_str(text)
+        .map_err(|e| ServiceError::Invalid(format!("manifest is not a list of documents: {}", e)))?;
+    let mut entries = Vec::with_capacity(items.len());
+    for (i, item) in items.into_iter().enumerate() {
+        entries.push(entry(&format!("entry {}", i + 1), base, item)?);
+    }
+    finish(entries)
+}
+
+/// Checks an item; `at` names its line (CSV) or position (YAML).
+fn entry(at: &str, base: &Path, item: Item) -> Result<ManifestEntry> {
+    if item.file.as_os_str().is_empty() || item.code.trim().is_empty() || item.title.trim().is_empty() {
+        return Err(invalid_entry(at, "file, code and title are required"));
+    }
+    if item.priority == Some(0) {
+        return Err(invalid_entry(at, "priority must be 1 or greater"));
+    }
+    let keywords = item.keywords.iter()
+        .map(|k| k.parse::<KeywordRef>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid_entry(at, e))?;
+    Ok(ManifestEntry {
+        file: base.join(item.file),
+        code: item.code.trim().to_string(),
+        title: item.title.trim().to_string(),
+        priority: item.priority,
+        keywords,
+        placeholder: item.placeholder,
+        folder: item.folder.filter(|f| !f.trim().is_empty()),
+    })
+}
+
+fn finish(entries: Vec<ManifestEntry>) -> Result<Vec<ManifestEntry>> {
+    if entries.is_empty() {
+        return Err(ServiceError::Invalid("manifest lists no documents".to_string()).into());
+    }
+    Ok(entries)
+}
+
+fn csv_line(position: Option<&csv::Position>) -> String {
+    match position {
+        Some(p) => format!("line {}", p.line()),
+        None => "entry".to_string(),
+    }
+}
+
+fn invalid_entry(at: &str, problem: impl std::fmt::Display) -> anyhow::Error {
+    ServiceError::Invalid(format!("manifest {}: {}", at, problem)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ectd_core::models::keyword_definition::KeywordType;
+
+    #[test]
+    fn test_csv_and_yaml_manifests_read_alike() {
+        let csv = "\
+file,code,title,priority,keywords
+cover.pdf,cover-letter,Cover Letter,,
+studies/csr.pdf,clinical-study-report,Study Report,3,study-id:ABC-101;acme
+";
+        let yaml = "
+- file: cover.pdf
+  code: cover-letter
+  title: Cover Letter
+- file: studies/csr.pdf
+  code: clinical-study-report
+  title: Study Report
+  priority: 3
+  keywords: [\"study-id:ABC-101\", acme]
+";
+        let base = Path::new("/data/seq-0002");
+        let from_csv = parse_csv(csv, base).unwrap();
+        assert_eq!(from_csv, parse_yaml(yaml, base).unwrap());
+
+        assert_eq!(from_csv[0].file, Path::new("/data/seq-0002/cover.pdf"));
+        assert_eq!(from_csv[0].priority, None);
+        assert!(from_csv[0].keywords.is_empty());
+        assert_eq!(from_csv[1].priority, Some(3));
+        assert_eq!(from_csv[1].keywords[0].keyword_type, KeywordType::StudyId);
+        assert_eq!(from_csv[1].keywords[1].keyword_type, KeywordType::Sponsor);
+    }
+
+    #[test]
+    fn test_manifest_errors_name_the_entry() {
+        let base = Path::new("");
+        let err = parse_csv("file,code,title\ncover.pdf,,Cover\n", base).unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+
+        let err = parse_csv("file,code,title,colour\ncover.pdf,cover-letter,Cover,red\n", base).unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+
+        // A quoted line break pushes the next record down a line
+        let csv = "file,code,title\na.pdf,cover-letter,\"Cover\nLetter\"\nb.pdf,,Form\n";
+        let err = parse_csv(csv, base).unwrap_err();
+        assert!(err.to_string().contains("line 4"), "{}", err);
+
+        let err = parse_yaml("- file: a.pdf\n  code: x\n  title: A\n- file: b.pdf\n  code: ''\n  title: B\n", base).unwrap_err();
+        assert!(err.to_string().contains("entry 2"), "{}", err);
+
+        assert!(parse_yaml("[]", base).is_err());
+        assert!(parse_yaml("- file: a.pdf\n  code: x\n", base).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use utoipa::ToSchema;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Transmission archive formats accepted by FDA ESG / EMA CESP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::Tar => "tar",
+        }
+    }
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "zip" => Ok(ArchiveFormat::Zip),
+            "tar" => Ok(ArchiveFormat::Tar),
+            other => Err(format!("Unknown archive format '{}' (expected zip or tar)", other)),
+        }
+    }
+}
+
+/// Writes `entries` (paths relative to `root`, '/'-separated) into a reproducible archive:
+/// entries sorted bytewise, fixed timestamps (1980-01-01 for ZIP, epoch for TAR),
+/// fixed permissions and no owner information. The same inputs always produce
+/// byte-identical output, whatever the files' mtimes or the filesystem order.
+pub fn write_archive(format: ArchiveFormat, root: &Path, entries: &[String], dest: &Path) -> Result<()> {
+    let mut entries: Vec<&String> = entries.iter().collect();
+    entries.sort();
+    entries.dedup();
+
+    let out = BufWriter::new(
+        File::create(dest).context(format!("Failed to create archive: {:?}", dest))?,
+    );
+
+    match format {
+        ArchiveFormat::Zip => write_zip(out, root, &entries),
+        ArchiveFormat::Tar => write_tar(out, root, &entries),
+    }
+}
+
+fn write_zip(out: BufWriter<File>, root: &Path, entries: &[&String]) -> Result<()> {
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .compression_level(Some(6))
+        .last_modified_time(zip::DateTime::default())
+        .unix_permissions(0o644);
+
+    let mut zip = ZipWriter::new(out);
+    for name in entries {
+        zip.start_file(name.as_str(), options)?;
+        let mut file = File::open(root.join(name.as_str()))
+            .context(format!("Failed to open {} for archiving", name))?;
+        std::io::copy(&mut file, &mut zip)?;
+    }
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+fn write_tar(out: BufWriter<File>, root: &Path, entries: &[&String]) -> Result<()> {
+    let mut tar = tar::Builder::new(out);
+    for name in entries {
+        let mut file = File::open(root.join(name.as_str()))
+            .context(format!("Failed to open {} for archiving", name))?;
+
+        let mut header = tar::Header::new_ustar();
+        header.set_size(file.metadata()?.len());
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_entry_type(tar::EntryType::Regular);
+
+        tar.append_data(&mut header, name.as_str(), &mut file)?;
+    }
+    tar.into_inner()?.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+    use uuid::Uuid;
+
+    fn scratch_tree() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ectd-package-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("m1/us")).unwrap();
+        std::fs::write(dir.join("m1/us/cover.pdf"), b"%PDF-1.7 cover").unwrap();
+        std::fs::write(dir.join("submissionunit.xml"), b"<submissionUnit/>").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_archives_are_byte_identical() {
+        for format in [ArchiveFormat::Zip, ArchiveFormat::Tar] {
+            let root = scratch_tree();
+            let entries = vec!["submissionunit.xml".to_string(), "m1/us/cover.pdf".to_string()];
+
+            let first = root.join(format!("a.{}", format));
+            write_archive(format, &root, &entries, &first).unwrap();
+
+            // Touch a file and reverse the input order: output must not change.
+            let later = SystemTime::now() + Duration::from_secs(3600);
+            File::options().write(true).open(root.join("m1/us/cover.pdf")).unwrap()
+                .set_modified(later).unwrap();
+            let reversed: Vec<String> = entries.iter().rev().cloned().collect();
+
+            let second = root.join(format!("b.{}", format));
+            write_archive(format, &root, &reversed, &second).unwrap();
+
+            assert_eq!(std::fs::read(&first).unwrap(), std::fs::read(&second).unwrap(), "{} differs", format);
+            std::fs::remove_dir_all(root).ok();
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use crate::{EctdService, ServiceError};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use time::Date;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use ectd_db::planning::{iso_date, NewPlaceholder, Placeholder, PlaceholderUpdate, PlanningRepository};
+use ectd_db::repository::SubmissionRepository;
+
+/// A context of use expected in the unit before its document exists.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NewPlaceholderParams {
+    /// The context of use code the document will be attached under
+    pub code: String,
+    /// Expected document title
+    pub title: Option<String>,
+    /// Who delivers the document
+    pub owner: Option<String>,
+    /// YYYY-MM-DD
+    #[serde(default, with = "iso_date::option")]
+    #[schema(value_type = Option<String>, format = Date)]
+    pub due_date: Option<Date>,
+    pub note: Option<String>,
+    /// Optional placeholders don't hold up the export; defaults to true
+    #[serde(default = "required_by_default")]
+    pub required: bool,
+    /// Priority of the context of use, unless the attach gives one
+    pub priority: Option<u32>,
+    /// Package folder for the document (e.g. "m1/us/annual-report"),
+    /// instead of the default for its code
+    pub folder: Option<String>,
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+/// Changes to an open placeholder; absent fields are left untouched.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PlaceholderPatch {
+    pub code: Option<String>,
+    pub title: Option<String>,
+    pub owner: Option<String>,
+    #[serde(default, with = "iso_date::option")]
+    #[schema(value_type = Option<String>, format = Date)]
+    pub due_date: Option<Date>,
+    pub note: Option<String>,
+    pub required: Option<bool>,
+    pub priority: Option<u32>,
+    pub folder: Option<String>,
+}
+
+/// Whether all planned content of a unit is in place.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessReport {
+    pub submission_unit_id: Uuid,
+    /// True when no required placeholder is open; export is blocked otherwise
+    pub ready: bool,
+    pub planned: usize,
+    pub fulfilled: usize,
+    /// Open required placeholders
+    pub missing: usize,
+    pub overdue: usize,
+    /// Placeholders still waiting for a document, required ones first
+    pub open: Vec<Placeholder>,
+}
+
+impl EctdService {
+    pub async fn add_placeholder(&self, submission_id: Uuid, params: NewPlaceholderParams) -> Result<Placeholder> {
+        self.ensure_submission(submission_id).await?;
+        let placeholder = new_placeholder(params)?;
+
+        let repo = PlanningRepository::new(self.pool.clone());
+        let mut tx = self.pool.begin().await?;
+        let placeholder = repo.create(&mut tx, submission_id, &placeholder).await
+            .context("Failed to save placeholder")?;
+        self.record_audit(
+            &mut tx,
+            "placeholder.create",
+            "context_placeholder",
+            Some(placeholder.id),
+            None,
+            Some(placeholder_state(&placeholder)),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(placeholder)
+    }
+
+    pub async fn list_placeholders(&self, submission_id: Uuid) -> Result<Vec<Placeholder>> {
+        self.ensure_submission(submission_id).await?;
+        PlanningRepository::new(self.pool.clone()).list(submission_id).await
+            .context("Failed to fetch placeholders")
+    }
+
+    /// Changes an open placeholder; fulfilled ones are part of the record.
+    pub async fn update_placeholder(&self, submission_id: Uuid, id: Uuid, patch: PlaceholderPatch) -> Result<Placeholder> {
+        if patch.code.as_deref().is_some_and(|c| c.trim().is_empty()) {
+            return Err(ServiceError::Invalid("code must not be empty".to_string()).into());
+        }
+        if patch.priority == Some(0) {
+            return Err(ServiceError::Invalid("priority must be 1 or greater".to_string()).into());
+        }
+        if let Some(folder) = &patch.folder {
+            check_folder(folder)?;
+        }
+
+        let before = self.find_open_placeholder(submission_id, id).await?;
+
+        let repo = PlanningRepository::new(self.pool.clone());
+        let update = PlaceholderUpdate {
+            code: patch.code.map(|c| c.trim().to_string()),
+            title: patch.title,
+            owner: patch.owner,
+            due_date: patch.due_date,
+            note: patch.note,
+            required: patch.required,
+            priority: patch.priority.map(|p| p as i32),
+            folder: patch.folder.map(|f| f.trim_matches('/').to_string()),
+        };
+        let mut tx = self.pool.begin().await?;
+        let after = repo.update(&mut tx, submission_id, id, &update).await
+            .context("Failed to update placeholder")?
+            .ok_or_else(|| ServiceError::NotFound(format!("Placeholder {}", id)))?;
+        self.record_audit(
+            &mut tx,
+            "placeholder.update",
+            "context_placeholder",
+            Some(id),
+            Some(placeholder_state(&before)),
+            Some(placeholder_state(&after)),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(after)
+    }
+
+    /// Drops an open placeholder from the plan.
+    pub async fn remove_placeholder(&self, submission_id: Uuid, id: Uuid) -> Result<()> {
+        let before = self.find_open_placeholder(submission_id, id).await?;
+
+        let repo = PlanningRepository::new(self.pool.clone());
+        let mut tx = self.pool.begin().await?;
+        repo.delete(&mut tx, submission_id, id).await
+            .context("Failed to delete placeholder")?;
+        self.record_audit(
+            &mut tx,
+            "placeholder.delete",
+            "context_placeholder",
+            Some(id),
+            Some(placeholder_state(&before)),
+            None,
+        ).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn readiness(&self, submission_id: Uuid) -> Result<ReadinessReport> {
+        let placeholders = self.list_placeholders(submission_id).await?;
+        let planned = placeholders.len();
+        let open: Vec<Placeholder> = placeholders.into_iter().filter(Placeholder::is_open).collect();
+        let missing = open.iter().filter(|p| p.required).count();
+
+        Ok(ReadinessReport {
+            submission_unit_id: submission_id,
+            ready: missing == 0,
+            planned,
+            fulfilled: planned - open.len(),
+            missing,
+            overdue: open.iter().filter(|p| p.overdue).count(),
+            open,
+        })
+    }
+
+    /// Fails with a Conflict naming the open required placeholders, if there are any.
+    pub async fn ensure_ready(&self, submission_id: Uuid) -> Result<()> {
+        let report = self.readiness(submission_id).await?;
+        if report.ready {
+            return Ok(());
+        }
+        let open = report.open.iter()
+            .filter(|p| p.required)
+            .map(|p| match &p.owner {
+                Some(owner) => format!("{} ({})", p.code, owner),
+                None => p.code.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(ServiceError::Conflict(format!(
+            "Submission unit {} is not ready: {} of {} planned document(s) missing: {}",
+            submission_id, report.missing, report.planned, open
+        )).into())
+    }
+
+    /// The open placeholder `id` of the unit, checked before a document is attached for it.
+    pub(crate) async fn find_open_placeholder(&self, submission_id: Uuid, id: Uuid) -> Result<Placeholder> {
+        let placeholder = self.find_placeholder(submission_id, id).await?;
+        if let Some(context_id) = placeholder.context_of_use_id {
+            return Err(ServiceError::Conflict(format!(
+                "Placeholder {} is already fulfilled by context of use {}", id, context_id
+            )).into());
+        }
+        Ok(placeholder)
+    }
+
+    async fn find_placeholder(&self, submission_id: Uuid, id: Uuid) -> Result<Placeholder> {
+        PlanningRepository::new(self.pool.clone()).get(submission_id, id).await
+            .context("Failed to fetch placeholder")?
+            .ok_or_else(|| ServiceError::NotFound(format!("Placeholder {}", id)).into())
+    }
+
+    pub(crate) async fn ensure_submission(&self, submission_id: Uuid) -> Result<()> {
+        let repo = SubmissionRepository::new(self.pool.clone());
+        if !repo.submission_exists(submission_id).await? {
+            return Err(ServiceError::submission_not_found(submission_id).into());
+        }
+        Ok(())
+    }
+}
+
+/// Checks and normalizes a placeholder for the repository.
+pub(crate) fn new_placeholder(params: NewPlaceholderParams) -> Result<NewPlaceholder> {
+    let code = params.code.trim().to_string();
+    if code.is_empty() {
+        return Err(ServiceError::Invalid("code must not be empty".to_string()).into());
+    }
+    if params.priority == Some(0) {
+        return Err(ServiceError::Invalid("priority must be 1 or greater".to_string()).into());
+    }
+    if let Some(folder) = &params.folder {
+        check_folder(folder)?;
+    }
+    Ok(NewPlaceholder {
+        code,
+        title: params.title,
+        owner: params.owner,
+        due_date: params.due_date,
+        note: params.note,
+        required: params.required,
+        priority: params.priority.map(|p| p as i32),
+        folder: params.folder.map(|f| f.trim_matches('/').to_string()),
+    })
+}
+
+/// A folder becomes part of every document path below it, so it must stay
+/// inside the package.
+pub(crate) fn check_folder(folder: &str) -> Result<()> {
+    let folder = folder.trim_matches('/');
+    let safe = !folder.is_empty()
+        && folder.split('/').all(|part| {
+            !part.is_empty() && part != "." && part != ".."
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    if !safe {
+        return Err(ServiceError::Invalid(format!(
+            "folder '{}' must be a relative path of letters, digits, '-', '_' and '.'", folder
+        )).into());
+    }
+    Ok(())
+}
+
+fn placeholder_state(p: &Placeholder) -> Value {
+    json!({
+        "submissionUnitId": p.submission_unit_id,
+        "code": p.code,
+        "title": p.title,
+        "owner": p.owner,
+        "dueDate": p.due_date.map(|d| d.to_string()),
+        "note": p.note,
+        "required": p.required,
+        "priority": p.priority,
+        "folder": p.folder,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due_dates_are_plain_dates() {
+        let params: NewPlaceholderParams = serde_json::from_value(json!({
+            "code": "clinical-overview",
+            "owner": "medical writing",
+            "dueDate": "2026-11-30",
+        })).unwrap();
+        assert_eq!(params.due_date.map(|d| d.to_string()), Some("2026-11-30".to_string()));
+
+        let patch: PlaceholderPatch = serde_json::from_value(json!({ "owner": "clinical" })).unwrap();
+        assert!(patch.due_date.is_none());
+
+        assert!(params.required);
+
+        let bad = serde_json::from_value::<NewPlaceholderParams>(json!({ "code": "x", "dueDate": "30/11/2026" }));
+        assert!(bad.is_err());
+    }
+
+    #[test]
+    fn test_folders_stay_inside_the_package() {
+        assert!(check_folder("m1/us/annual-report").is_ok());
+        assert!(check_folder("/m3/32-body-data/").is_ok());
+        assert!(check_folder("m1/../../etc").is_err());
+        assert!(check_folder("m1//us").is_err());
+        assert!(check_folder("m1/us cover").is_err());
+        assert!(check_folder("").is_err());
+    }
+}
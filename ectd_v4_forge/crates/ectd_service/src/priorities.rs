@@ -0,0 +1,257 @@
+use crate::{EctdService, ServiceError};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use ectd_db::priority::{PriorityRepository, ViewContext};
+use ectd_db::repository::SubmissionRepository;
+
+/// Which way `move_context` shifts a context among the unit's contexts of its code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum MoveDirection {
+    /// Towards priority 1
+    Up,
+    Down,
+}
+
+impl MoveDirection {
+    pub const ALL: [MoveDirection; 2] = [MoveDirection::Up, MoveDirection::Down];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            MoveDirection::Up => "up",
+            MoveDirection::Down => "down",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.code() == code)
+    }
+}
+
+impl std::str::FromStr for MoveDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_code(s.trim()).ok_or_else(|| format!(
+            "unknown direction '{}' (one of: {})", s, Self::ALL.map(|d| d.code()).join(", ")
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MoveContextParams {
+    pub direction: MoveDirection,
+}
+
+/// The unit's contexts of one code, in their new order.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ReorderContextsParams {
+    pub code: String,
+    /// Every active context of the code in the unit, each once
+    pub context_ids: Vec<Uuid>,
+}
+
+impl EctdService {
+    /// The contexts in force for the unit: its own and those of earlier
+    /// sequences not replaced or suspended, by code and priority.
+    pub async fn current_view(&self, submission_id: Uuid) -> Result<Vec<ViewContext>> {
+        if !SubmissionRepository::new(self.pool.clone()).submission_exists(submission_id).await? {
+            return Err(ServiceError::submission_not_found(submission_id).into());
+        }
+        PriorityRepository::new(self.pool.clone())
+            .current_view(submission_id).await
+            .context("Failed to fetch the current view")
+    }
+
+    /// The highest priority per context code in the unit's current view;
+    /// new contexts of a code are numbered after it.
+    pub(crate) async fn last_priorities(&self, submission_id: Uuid) -> Result<HashMap<String, u32>> {
+        let mut last: HashMap<String, u32> = HashMap::new();
+        for c in self.current_view(submission_id).await? {
+            let priority = last.entry(c.code).or_default();
+            *priority = (*priority).max(c.priority as u32);
+        }
+        Ok(last)
+    }
+
+    /// Renumbers the unit's active contexts of a code in the given order.
+    /// They take the lowest priorities the earlier sequences' contexts of
+    /// the code leave free, which also closes gaps and duplicates among them.
+    /// Returns the code's contexts in the current view.
+    pub async fn reorder_contexts(&self, submission_id: Uuid, params: ReorderContextsParams) -> Result<Vec<ViewContext>> {
+        let code = params.code.trim().to_string();
+        if code.is_empty() {
+            return Err(ServiceError::Invalid("code must not be empty".to_string()).into());
+        }
+        let view = self.current_view(submission_id).await?;
+        let (own, inherited): (Vec<_>, Vec<_>) = view.into_iter()
+            .filter(|c| c.code == code)
+            .partition(|c| c.submission_unit_id == submission_id);
+        if own.is_empty() {
+            return Err(ServiceError::Invalid(format!(
+                "Unit {} has no active context of use '{}'", submission_id, code
+            )).into());
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(twice) = params.context_ids.iter().find(|id| !seen.insert(**id)) {
+            return Err(ServiceError::Invalid(format!("Context of use {} is listed twice", twice)).into());
+        }
+        if let Some(unknown) = params.context_ids.iter().find(|id| !own.iter().any(|c| c.context_of_use_id == **id)) {
+            return Err(ServiceError::Invalid(format!(
+                "Context of use {} is not an active '{}' context of unit {}", unknown, code, submission_id
+            )).into());
+        }
+        let missing: Vec<String> = own.iter()
+            .filter(|c| !seen.contains(&c.context_of_use_id))
+            .map(|c| c.context_of_use_id.to_string())
+            .collect();
+        if !missing.is_empty() {
+            return Err(ServiceError::Invalid(format!(
+                "The order of '{}' must list every one of its contexts; missing {}", code, missing.join(", ")
+            )).into());
+        }
+
+        let taken: Vec<u32> = inherited.iter().map(|c| c.priority as u32).collect();
+        let priorities: Vec<(Uuid, i32)> = params.context_ids.iter()
+            .zip(free_priorities(&taken, own.len()))
+            .map(|(id, p)| (*id, p as i32))
+            .collect();
+
+        let mut tx = self.pool.begin().await?;
+        PriorityRepository::new(self.pool.clone())
+            .renumber(&mut tx, submission_id, &priorities).await
+            .context("Failed to renumber contexts of use")?;
+
+        let after: Vec<(Uuid, i32)> = {
+            let mut sorted = priorities.clone();
+            sorted.sort_by_key(|(_, p)| *p);
+            sorted
+        };
+        self.record_audit(
+            &mut tx,
+            "context_of_use.reorder",
+            "submission_unit",
+            Some(submission_id),
+            Some(order_state(&code, own.iter().map(|c| (c.context_of_use_id, c.priority)))),
+            Some(order_state(&code, after.into_iter())),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(self.current_view(submission_id).await?
+            .into_iter()
+            .filter(|c| c.code == code)
+            .collect())
+    }
+
+    /// Swaps a context with its neighbour among the unit's contexts of its
+    /// code, then renumbers them as `reorder_contexts` does.
+    pub async fn move_context(&self, submission_id: Uuid, context_id: Uuid, direction: MoveDirection) -> Result<Vec<ViewContext>> {
+        let view = self.current_view(submission_id).await?;
+        let Some(code) = view.iter()
+            .find(|c| c.context_of_use_id == context_id && c.submission_unit_id == submission_id)
+            .map(|c| c.code.clone())
+        else {
+            let cou = self.find_context(submission_id, context_id).await?;
+            return Err(ServiceError::Invalid(format!(
+                "Context of use {} is {}, so it has no place in the order", context_id, cou.status_code
+            )).into());
+        };
+
+        let mut order: Vec<Uuid> = view.iter()
+            .filter(|c| c.code == code && c.submission_unit_id == submission_id)
+            .map(|c| c.context_of_use_id)
+            .collect();
+        let at = order.iter().position(|id| *id == context_id).ok_or_else(|| ServiceError::Invalid(format!(
+            "Context of use {} is not an active '{}' context of unit {}", context_id, code, submission_id
+        )))?;
+        let to = match direction {
+            MoveDirection::Up if at > 0 => at - 1,
+            MoveDirection::Down if at + 1 < order.len() => at + 1,
+            _ => {
+                return Err(ServiceError::Invalid(format!(
+                    "Context of use {} is already the {} '{}' context of the unit",
+                    context_id,
+                    if direction == MoveDirection::Up { "first" } else { "last" },
+                    code
+                )).into());
+            }
+        };
+        order.swap(at, to);
+
+        self.reorder_contexts(submission_id, ReorderContextsParams { code, context_ids: order }).await
+    }
+}
+
+/// The `count` lowest priorities from 1 up that are not `taken`.
+fn free_priorities(taken: &[u32], count: usize) -> Vec<u32> {
+    (1..).filter(|p| !taken.contains(p)).take(count).collect()
+}
+
+fn order_state(code: &str, contexts: impl Iterator<Item = (Uuid, i32)>) -> serde_json::Value {
+    json!({
+        "code": code,
+        "contexts": contexts
+            .map(|(id, priority)| json!({ "contextOfUseId": id, "priority": priority }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::DocumentReuse;
+
+    #[test]
+    fn test_free_priorities_fill_gaps_left_by_earlier_sequences() {
+        assert_eq!(free_priorities(&[], 3), [1, 2, 3]);
+        assert_eq!(free_priorities(&[1, 3], 2), [2, 4]);
+        assert_eq!(free_priorities(&[2, 2, 5], 4), [1, 3, 4, 6]);
+    }
+
+    #[test]
+    fn test_move_direction_codes_round_trip() {
+        for direction in MoveDirection::ALL {
+            assert_eq!(direction.code().parse::<MoveDirection>(), Ok(direction));
+        }
+        assert!("sideways".parse::<MoveDirection>().is_err());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_move_keeps_inherited_contexts_in_place(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let first = testing::new_unit(&service, "100001").await;
+        for name in ["a.txt", "b.txt"] {
+            testing::attach(&service, first, &testing::file(name, name), "cover-letter", DocumentReuse::Copy).await.unwrap();
+        }
+        let second = testing::new_unit(&service, "100001").await;
+        let mut own = vec![];
+        for name in ["c.txt", "d.txt"] {
+            let file = testing::file(name, name);
+            own.push(testing::attach(&service, second, &file, "cover-letter", DocumentReuse::Copy).await.unwrap().context_of_use_id);
+        }
+        let inherited: Vec<(Uuid, i32)> = service.current_view(second).await.unwrap().iter()
+            .filter(|c| c.submission_unit_id == first)
+            .map(|c| (c.context_of_use_id, c.priority))
+            .collect();
+        assert_eq!(inherited.iter().map(|(_, p)| *p).collect::<Vec<_>>(), [1, 2]);
+
+        let moved = service.move_context(second, own[1], MoveDirection::Up).await.unwrap();
+
+        let order: Vec<(Uuid, i32)> = moved.iter().map(|c| (c.context_of_use_id, c.priority)).collect();
+        assert_eq!(order, [inherited[0], inherited[1], (own[1], 3), (own[0], 4)]);
+
+        let err = service.move_context(second, own[1], MoveDirection::Up).await.unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(ServiceError::Invalid(_))), "{err:#}");
+        let err = service.move_context(second, inherited[1].0, MoveDirection::Up).await.unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(ServiceError::NotFound(_))), "{err:#}");
+    }
+}
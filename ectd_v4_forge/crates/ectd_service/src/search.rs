@@ -0,0 +1,140 @@
+use crate::{EctdService, ServiceError};
+use crate::listing::listing_error;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+use uuid::Uuid;
+use ectd_core::media_type;
+use ectd_core::text::extract_pdf_text;
+use ectd_db::listing::{Page, PageRequest, SubmissionFilter};
+use ectd_db::search::{SearchHit, SearchRepository};
+
+/// What `reindex_text` extracted.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReindexReport {
+    /// Distinct contents whose text was extracted and stored
+    pub indexed: usize,
+    /// `href: reason` for files that could not be read
+    pub failed: Vec<String>,
+}
+
+impl EctdService {
+    /// Full-text search over document titles, the keywords each document's
+    /// contexts of use are tagged with, and PDF text.
+    /// `query` uses web search syntax: `stability "batch 42" -placebo`.
+    pub async fn search_documents(
+        &self,
+        query: &str,
+        filter: &SubmissionFilter,
+        page: &PageRequest,
+    ) -> Result<Page<SearchHit>> {
+        if query.trim().is_empty() {
+            return Err(ServiceError::Invalid("search query must not be empty".to_string()).into());
+        }
+        let repo = SearchRepository::new(self.pool.clone());
+        repo.search(query, filter, page).await.map_err(listing_error)
+    }
+
+    /// Extracts the text of a local file for the search index, unless this
+    /// content was indexed before. Best-effort: a file we cannot read must not
+    /// stop the attach or ingest; `reindex_text` retries it later.
+    pub(crate) async fn index_text(&self, path: &Path, sha256: &str, content_type: &str) {
+        if content_type != media_type::PDF {
+            return;
+        }
+        let indexed = SearchRepository::new(self.pool.clone()).has_text(sha256).await;
+        if matches!(indexed, Ok(true)) {
+            return;
+        }
+        if let Err(e) = self.extract_text(path, sha256).await {
+            eprintln!("⚠️  Not searchable: {:?}: {:#} (run `ectd_cli search --reindex`)", path, e);
+        }
+    }
+
+    /// Extracts text for stored PDFs that have none yet: documents added
+    /// before full-text search, or whose extraction failed at the time.
+    pub async fn reindex_text(&self) -> Result<ReindexReport> {
+        let repo = SearchRepository::new(self.pool.clone());
+        let pending = repo.unindexed(media_type::PDF).await
+            .context("Failed to list unindexed documents")?;
+
+        let scratch = std::env::temp_dir().join(format!("ectd-reindex-{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&scratch).await
+            .context(format!("Failed to create scratch dir: {:?}", scratch))?;
+
+        let mut report = ReindexReport::default();
+        for doc in pending {
+            let dest = scratch.join(&doc.sha256);
+            let outcome = async {
+                self.store.get_to_file(&doc.storage_key, &dest).await
+                    .context("Failed to fetch from storage")?;
+                self.extract_text(&dest, &doc.sha256).await
+            }.await;
+            let _ = tokio::fs::remove_file(&dest).await;
+
+            match outcome {
+                Ok(()) => report.indexed += 1,
+                Err(e) => report.failed.push(format!("{}: {:#}", doc.href, e)),
+            }
+        }
+
+        let _ = tokio::fs::remove_dir_all(&scratch).await;
+        Ok(report)
+    }
+
+    async fn extract_text(&self, path: &Path, sha256: &str) -> Result<()> {
+        let path = path.to_path_buf();
+        // lopdf parsing is CPU-bound; keep it off the async workers
+        let text = tokio::task::spawn_blocking(move || extract_pdf_text(&path)).await
+            .context("Text extraction aborted")?
+            .context("Failed to read PDF text")?;
+
+        SearchRepository::new(self.pool.clone()).save_text(sha256, &text).await
+            .context("Failed to store extracted text")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::{AddDocumentParams, DocumentReuse, KeywordRef};
+    use ectd_core::models::keyword_definition::KeywordType;
+
+    async fn found(service: &EctdService, query: &str) -> Vec<Uuid> {
+        service.search_documents(query, &SubmissionFilter::default(), &PageRequest::default()).await
+            .unwrap()
+            .items.into_iter().map(|h| h.id).collect()
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_documents_are_found_by_their_own_keywords_of_earlier_sequences(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let first = testing::new_unit(&service, "100001").await;
+        service.put_keyword(first, KeywordType::Sponsor, "peds", "Paediatric population").await.unwrap();
+        let second = testing::new_unit(&service, "100001").await;
+
+        let tagged = service.attach_document(AddDocumentParams {
+            submission_id: second,
+            file_path: testing::file("plan.txt", "plan"),
+            context_code: "pediatric-plan".to_string(),
+            title: "Study plan".to_string(),
+            priority: None,
+            placeholder_id: None,
+            keywords: vec![KeywordRef { keyword_type: KeywordType::Sponsor, code: "peds".to_string() }],
+            folder: None,
+            reuse: DocumentReuse::Copy,
+        }).await.unwrap();
+        // Same unit, not tagged: the keyword must not find it
+        let plain = testing::file("letter.txt", "letter");
+        testing::attach(&service, second, &plain, "cover-letter", DocumentReuse::Copy).await.unwrap();
+
+        assert_eq!(found(&service, "paediatric").await, [tagged.document_id]);
+
+        // Renaming it where it was defined reaches the later sequence
+        service.put_keyword(first, KeywordType::Sponsor, "peds", "Adolescent population").await.unwrap();
+        assert_eq!(found(&service, "adolescent").await, [tagged.document_id]);
+        assert!(found(&service, "paediatric").await.is_empty());
+    }
+}
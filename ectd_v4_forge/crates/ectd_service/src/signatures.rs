@@ -0,0 +1,150 @@
+use crate::{EctdService, ServiceError};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::json;
+use uuid::Uuid;
+use ectd_core::models::submission_unit::SubmissionUnit;
+use ectd_db::repository::SubmissionRepository;
+use ectd_db::signature::{SignatureMeaning, SignatureRecord, SignatureRepository};
+
+#[derive(Debug)]
+pub struct SignParams {
+    pub submission_id: Uuid,
+    pub meaning: SignatureMeaning,
+    pub comment: Option<String>,
+}
+
+/// A stored signature plus whether it still matches the unit's current content.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureStatus {
+    #[serde(flatten)]
+    pub signature: SignatureRecord,
+    pub valid: bool,
+}
+
+impl EctdService {
+    /// Signs the current state of a submission unit as the service's actor.
+    pub async fn sign_submission(&self, params: SignParams) -> Result<SignatureRecord> {
+        let repo = SubmissionRepository::new(self.pool.clone());
+        if !repo.submission_exists(params.submission_id).await? {
+            return Err(ServiceError::submission_not_found(params.submission_id).into());
+        }
+        let unit = repo.get_submission(params.submission_id).await
+            .context("Failed to fetch submission from DB")?;
+        let digest = unit.content_digest()?;
+
+        let signatures = SignatureRepository::new(self.pool.clone());
+        let mut tx = self.pool.begin().await?;
+        let record = signatures
+            .add_signature(
+                &mut tx,
+                params.submission_id,
+                &self.actor,
+                params.meaning,
+                &digest,
+                params.comment.as_deref(),
+            )
+            .await
+            .context("Failed to store signature")?;
+
+        self.record_audit(
+            &mut tx,
+            "submission.sign",
+            "submission_unit",
+            Some(params.submission_id),
+            None,
+            Some(json!({
+                "signatureId": record.id,
+                "meaning": record.meaning,
+                "contentDigest": record.content_digest,
+                "comment": record.comment,
+            })),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(record)
+    }
+
+    pub async fn signature_status(&self, submission_id: Uuid) -> Result<Vec<SignatureStatus>> {
+        let repo = SubmissionRepository::new(self.pool.clone());
+        if !repo.submission_exists(submission_id).await? {
+            return Err(ServiceError::submission_not_found(submission_id).into());
+        }
+        let unit = repo.get_submission(submission_id).await
+            .context("Failed to fetch submission from DB")?;
+        self.signature_status_for(submission_id, &unit).await
+    }
+
+    pub(crate) async fn signature_status_for(
+        &self,
+        submission_id: Uuid,
+        unit: &SubmissionUnit,
+    ) -> Result<Vec<SignatureStatus>> {
+        let digest = unit.content_digest()?;
+
+        let signatures = SignatureRepository::new(self.pool.clone());
+        let records = signatures.list_signatures(submission_id).await
+            .context("Failed to load signatures")?;
+
+        Ok(records
+            .into_iter()
+            .map(|signature| SignatureStatus {
+                valid: signature.content_digest == digest,
+                signature,
+            })
+            .collect())
+    }
+
+    /// True if at least one "approve" signature still matches the current content.
+    pub async fn is_approved(&self, submission_id: Uuid) -> Result<bool> {
+        let statuses = self.signature_status(submission_id).await?;
+        Ok(has_valid_approval(&statuses))
+    }
+}
+
+pub(crate) fn has_valid_approval(statuses: &[SignatureStatus]) -> bool {
+    statuses
+        .iter()
+        .any(|s| s.valid && s.signature.meaning == SignatureMeaning::Approve.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use crate::DocumentReuse;
+
+    fn approve(submission_id: Uuid) -> SignParams {
+        SignParams { submission_id, meaning: SignatureMeaning::Approve, comment: None }
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_signature_is_invalidated_when_content_changes(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let unit = testing::new_unit(&service, "100001").await;
+        service.sign_submission(approve(unit)).await.unwrap();
+        assert!(service.is_approved(unit).await.unwrap());
+
+        let cover = testing::file("cover.txt", "cover letter");
+        testing::attach(&service, unit, &cover, "cover-letter", DocumentReuse::Ask).await.unwrap();
+
+        let statuses = service.signature_status(unit).await.unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert!(!statuses[0].valid);
+        assert!(!service.is_approved(unit).await.unwrap());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_unknown_unit_is_not_found(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let unknown = Uuid::new_v4();
+
+        for err in [
+            service.sign_submission(approve(unknown)).await.unwrap_err(),
+            service.signature_status(unknown).await.unwrap_err(),
+        ] {
+            assert!(matches!(err.downcast_ref(), Some(ServiceError::NotFound(_))), "{err:#}");
+        }
+    }
+}
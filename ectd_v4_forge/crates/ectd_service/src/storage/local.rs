@@ -0,0 +1,95 @@
+use super::{DocumentStore, StoredObject};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Stores each object as a flat file under `root`. Suited to small teams and CI.
+pub struct LocalStore {
+    root: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn object_path(&self, key: &str) -> Result<PathBuf> {
+        // Keys are UUIDs / hashes; anything path-like would escape the root.
+        if key.is_empty() || key.contains(['/', '\\']) || key.starts_with('.') {
+            anyhow::bail!("Invalid storage key: '{}'", key);
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl DocumentStore for LocalStore {
+    async fn ensure_ready(&self) -> Result<()> {
+        tokio::fs::create_dir_all(&self.root).await
+            .context(format!("Failed to create storage directory: {:?}", self.root))?;
+        Ok(())
+    }
+
+    async fn put_file(&self, key: &str, path: &Path, _content_type: &str) -> Result<()> {
+        let dest = self.object_path(key)?;
+
+        // Copy to a temp name first so a crash never leaves a half-written object.
+        let tmp = self.root.join(format!(".{}.{}.tmp", key, Uuid::new_v4()));
+        tokio::fs::copy(path, &tmp).await
+            .context(format!("Failed to copy {:?} into local store", path))?;
+        tokio::fs::rename(&tmp, &dest).await?;
+
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(tokio::fs::try_exists(self.object_path(key)?).await?)
+    }
+
+    async fn get_to_file(&self, key: &str, dest: &Path) -> Result<u64> {
+        let src = self.object_path(key)?;
+        let bytes = tokio::fs::copy(&src, dest).await
+            .context(format!("Object not found in local store: {}", key))?;
+        Ok(bytes)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.object_path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<StoredObject>> {
+        let mut objects = Vec::new();
+        let mut entries = match tokio::fs::read_dir(&self.root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(objects),
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let key = entry.file_name().to_string_lossy().to_string();
+            if key.starts_with('.') {
+                continue; // in-flight temp file
+            }
+            let meta = entry.metadata().await?;
+            if !meta.is_file() {
+                continue;
+            }
+            objects.push(StoredObject {
+                key,
+                size_bytes: meta.len(),
+                modified: meta.modified().ok(),
+            });
+        }
+
+        Ok(objects)
+    }
+
+    fn describe(&self) -> String {
+        format!("file://{}", self.root.display())
+    }
+}
@@ -0,0 +1,67 @@
+use super::{DocumentStore, StoredObject};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Keeps objects in process memory. Nothing survives a restart.
+#[derive(Default)]
+pub struct MemoryStore {
+    objects: Mutex<HashMap<String, (Vec<u8>, SystemTime)>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl DocumentStore for MemoryStore {
+    async fn ensure_ready(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn put_file(&self, key: &str, path: &Path, _content_type: &str) -> Result<()> {
+        let bytes = tokio::fs::read(path).await
+            .context(format!("Failed to read file: {:?}", path))?;
+        self.objects.lock().unwrap().insert(key.to_string(), (bytes, SystemTime::now()));
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.objects.lock().unwrap().contains_key(key))
+    }
+
+    async fn get_to_file(&self, key: &str, dest: &Path) -> Result<u64> {
+        let bytes = self.objects.lock().unwrap()
+            .get(key)
+            .map(|(bytes, _)| bytes.clone())
+            .ok_or_else(|| anyhow::anyhow!("Object not found in memory store: {}", key))?;
+        tokio::fs::write(dest, &bytes).await?;
+        Ok(bytes.len() as u64)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.objects.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<StoredObject>> {
+        let objects = self.objects.lock().unwrap();
+        Ok(objects
+            .iter()
+            .map(|(key, (bytes, modified))| StoredObject {
+                key: key.clone(),
+                size_bytes: bytes.len() as u64,
+                modified: Some(*modified),
+            })
+            .collect())
+    }
+
+    fn describe(&self) -> String {
+        "memory://".to_string()
+    }
+}
@@ -0,0 +1,167 @@
+pub mod s3;
+pub mod local;
+pub mod memory;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+pub use local::LocalStore;
+pub use memory::MemoryStore;
+pub use s3::S3Store;
+
+/// One entry from `DocumentStore::list`.
+#[derive(Debug, Clone)]
+pub struct StoredObject {
+    pub key: String,
+    pub size_bytes: u64,
+    /// None if the backend can't tell; treated as "old enough" by garbage collection.
+    pub modified: Option<SystemTime>,
+}
+
+/// Where document bytes live. Metadata always lives in Postgres;
+/// the store only maps an opaque key to file content.
+#[async_trait]
+pub trait DocumentStore: Send + Sync {
+    /// Idempotent: prepares the backend (creates the bucket or directory).
+    async fn ensure_ready(&self) -> Result<()>;
+
+    /// Streams a local file into the store under `key`.
+    async fn put_file(&self, key: &str, path: &Path, content_type: &str) -> Result<()>;
+
+    /// Whether an object is stored under `key`.
+    async fn exists(&self, key: &str) -> Result<bool>;
+
+    /// Streams the object stored under `key` into `dest`, returning the bytes written.
+    async fn get_to_file(&self, key: &str, dest: &Path) -> Result<u64>;
+
+    /// Removes the object. Deleting a missing key is not an error.
+    async fn delete(&self, key: &str) -> Result<()>;
+
+    /// Every object in the store (used by garbage collection).
+    async fn list(&self) -> Result<Vec<StoredObject>>;
+
+    /// Human-readable location, e.g. "s3://ectd-documents" or "file:///var/ectd".
+    fn describe(&self) -> String;
+}
+
+/// Storage selection, typically read from the environment.
+#[derive(Clone, Debug)]
+pub enum StorageBackend {
+    /// MinIO / AWS S3 (default)
+    S3 {
+        endpoint: String,
+        bucket: String,
+        region: String,
+    },
+    /// A plain directory; no external services required.
+    Local { root: PathBuf },
+    /// Process memory; for tests and throwaway runs.
+    Memory,
+}
+
+impl StorageBackend {
+    /// STORAGE_BACKEND = "s3" (default) | "local" | "memory"
+    /// S3:    S3_ENDPOINT, S3_BUCKET, AWS_REGION
+    /// Local: STORAGE_PATH (default "./data/documents")
+    pub fn from_env() -> Result<Self> {
+        let kind = env::var("STORAGE_BACKEND").unwrap_or_else(|_| "s3".to_string());
+
+        match kind.to_lowercase().as_str() {
+            "s3" | "minio" => Ok(StorageBackend::S3 {
+                endpoint: env::var("S3_ENDPOINT")
+                    .unwrap_or_else(|_| "http://localhost:9000".to_string()),
+                bucket: env::var("S3_BUCKET")
+                    .unwrap_or_else(|_| "ectd-documents".to_string()),
+                region: env::var("AWS_REGION")
+                    .unwrap_or_else(|_| "us-east-1".to_string()),
+            }),
+            "local" | "fs" => Ok(StorageBackend::Local {
+                root: env::var("STORAGE_PATH")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| PathBuf::from("./data/documents")),
+            }),
+            "memory" => Ok(StorageBackend::Memory),
+            other => anyhow::bail!("Unknown STORAGE_BACKEND '{}' (expected s3, local or memory)", other),
+        }
+    }
+
+    pub async fn build(&self) -> Arc<dyn DocumentStore> {
+        match self {
+            StorageBackend::S3 { endpoint, bucket, region } => {
+                Arc::new(S3Store::connect(endpoint, bucket, region).await)
+            }
+            StorageBackend::Local { root } => Arc::new(LocalStore::new(root.clone())),
+            StorageBackend::Memory => Arc::new(MemoryStore::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    async fn round_trip(store: &dyn DocumentStore, scratch: &Path) {
+        let src = scratch.join("input.pdf");
+        let dest = scratch.join("output.pdf");
+        tokio::fs::write(&src, b"%PDF-1.7 test").await.unwrap();
+
+        store.ensure_ready().await.unwrap();
+        assert!(!store.exists("doc-1").await.unwrap());
+        store.put_file("doc-1", &src, "application/pdf").await.unwrap();
+        assert!(store.exists("doc-1").await.unwrap());
+
+        let bytes = store.get_to_file("doc-1", &dest).await.unwrap();
+        assert_eq!(bytes, 13);
+        assert_eq!(tokio::fs::read(&dest).await.unwrap(), b"%PDF-1.7 test");
+
+        assert!(store.get_to_file("missing", &dest).await.is_err());
+
+        let listed = store.list().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].key, "doc-1");
+        assert_eq!(listed[0].size_bytes, 13);
+
+        store.delete("doc-1").await.unwrap();
+        store.delete("doc-1").await.unwrap(); // idempotent
+        assert!(!store.exists("doc-1").await.unwrap());
+        assert!(store.list().await.unwrap().is_empty());
+    }
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ectd-store-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_round_trip() {
+        let scratch = scratch_dir();
+        round_trip(&MemoryStore::new(), &scratch).await;
+        std::fs::remove_dir_all(scratch).ok();
+    }
+
+    #[tokio::test]
+    async fn test_local_store_round_trip() {
+        let scratch = scratch_dir();
+        let store = LocalStore::new(scratch.join("objects"));
+        round_trip(&store, &scratch).await;
+        std::fs::remove_dir_all(scratch).ok();
+    }
+
+    #[tokio::test]
+    async fn test_local_store_rejects_path_keys() {
+        let scratch = scratch_dir();
+        let store = LocalStore::new(scratch.join("objects"));
+        store.ensure_ready().await.unwrap();
+
+        let dest = scratch.join("out");
+        assert!(store.get_to_file("../input.pdf", &dest).await.is_err());
+        assert!(store.get_to_file("a/b", &dest).await.is_err());
+        std::fs::remove_dir_all(scratch).ok();
+    }
+}
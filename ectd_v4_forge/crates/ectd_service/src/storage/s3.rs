@@ -0,0 +1,150 @@
+use super::{DocumentStore, StoredObject};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use aws_config::meta::region::RegionProviderChain;
+use aws_sdk_s3::config::Region;
+use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::Client;
+use std::path::Path;
+use std::time::SystemTime;
+use tokio::io::AsyncWriteExt;
+
+/// MinIO / AWS S3 backend.
+pub struct S3Store {
+    client: Client,
+    bucket: String,
+}
+
+impl S3Store {
+    pub fn new(client: Client, bucket: String) -> Self {
+        Self { client, bucket }
+    }
+
+    /// Builds a client from the standard AWS environment (AWS_ACCESS_KEY_ID, ...).
+    /// Path-style addressing is forced for MinIO (localhost compatibility).
+    pub async fn connect(endpoint: &str, bucket: &str, region: &str) -> Self {
+        let region_provider = RegionProviderChain::default_provider().or_else(Region::new(region.to_string()));
+        let aws_config = aws_config::from_env().region(region_provider).load().await;
+        let s3_config = aws_sdk_s3::config::Builder::from(&aws_config)
+            .force_path_style(true)
+            .endpoint_url(endpoint)
+            .build();
+
+        Self::new(Client::from_conf(s3_config), bucket.to_string())
+    }
+}
+
+#[async_trait]
+impl DocumentStore for S3Store {
+    async fn ensure_ready(&self) -> Result<()> {
+        // 1. Check existence (HeadBucket)
+        let head = self.client.head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await;
+
+        match head {
+            Ok(_) => Ok(()), // Exists
+            Err(SdkError::ServiceError(err)) if err.err().is_not_found() => {
+                // 2. Create if missing (404)
+                println!("⚠️  Bucket '{}' not found. Creating...", self.bucket);
+                self.client.create_bucket()
+                    .bucket(&self.bucket)
+                    .send()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to create bucket: {}", e))?;
+
+                println!("✅ Bucket created.");
+                Ok(())
+            },
+            Err(e) => Err(anyhow::anyhow!("Failed to check bucket state: {}", e)),
+        }
+    }
+
+    async fn put_file(&self, key: &str, path: &Path, content_type: &str) -> Result<()> {
+        let body = ByteStream::from_path(path).await
+            .context(format!("Failed to open file for streaming: {:?}", path))?;
+
+        self.client.put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(body)
+            .content_type(content_type)
+            .send()
+            .await
+            .context("S3 Upload Failed")?;
+
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        let head = self.client.head_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await;
+
+        match head {
+            Ok(_) => Ok(true),
+            Err(SdkError::ServiceError(err)) if err.err().is_not_found() => Ok(false),
+            Err(e) => Err(anyhow::anyhow!("Failed to check object {}: {}", key, e)),
+        }
+    }
+
+    async fn get_to_file(&self, key: &str, dest: &Path) -> Result<u64> {
+        let mut stream = self.client.get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .context(format!("S3 Download Failed: {}", key))?
+            .body;
+
+        let mut file = tokio::fs::File::create(dest).await?;
+        let mut bytes_written = 0;
+        while let Some(bytes) = stream.try_next().await? {
+            file.write_all(&bytes).await?;
+            bytes_written += bytes.len() as u64;
+        }
+        file.flush().await?;
+
+        Ok(bytes_written)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.client.delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .context(format!("S3 Delete Failed: {}", key))?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<StoredObject>> {
+        let mut objects = Vec::new();
+        let mut pages = self.client.list_objects_v2()
+            .bucket(&self.bucket)
+            .into_paginator()
+            .send();
+
+        while let Some(page) = pages.next().await {
+            let page = page.context("S3 List Failed")?;
+            for obj in page.contents() {
+                let Some(key) = obj.key() else { continue };
+                objects.push(StoredObject {
+                    key: key.to_string(),
+                    size_bytes: obj.size().unwrap_or(0).max(0) as u64,
+                    modified: obj.last_modified().and_then(|t| SystemTime::try_from(*t).ok()),
+                });
+            }
+        }
+
+        Ok(objects)
+    }
+
+    fn describe(&self) -> String {
+        format!("s3://{}", self.bucket)
+    }
+}
@@ -0,0 +1,335 @@
+use crate::{AddDocumentParams, AttachedDocument, DocumentReuse, EctdService, KeywordRef, ServiceError};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use ectd_core::models::keyword_definition::KeywordType;
+use ectd_core::resolve_folder_path;
+use ectd_db::study::{NewStudy, Study, StudyDocument, StudyRepository, StudyUpdate};
+
+/// Report code used when none is given.
+pub const DEFAULT_REPORT_CODE: &str = "clinical-study-report";
+
+/// A new study of an application.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NewStudyParams {
+    /// Becomes the study-id keyword and the study's folder name, e.g. "ABC-101"
+    pub study_id: String,
+    pub title: String,
+    /// e.g. "pk", "efficacy"; stored in lowercase
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Context of use code of its report, protocol and appendices;
+    /// defaults to "clinical-study-report"
+    pub report_code: Option<String>,
+}
+
+/// Changes to a study; absent fields are left untouched.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct StudyPatch {
+    pub title: Option<String>,
+    /// Replaces the whole list
+    pub categories: Option<Vec<String>>,
+    pub report_code: Option<String>,
+}
+
+/// What a document is to its study. Documents are attached in this order,
+/// which also orders their priorities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum StudyDocumentRole {
+    Protocol,
+    Report,
+    Appendix,
+    Crf,
+    Dataset,
+    Define,
+}
+
+impl StudyDocumentRole {
+    pub const ALL: [StudyDocumentRole; 6] = [
+        StudyDocumentRole::Protocol,
+        StudyDocumentRole::Report,
+        StudyDocumentRole::Appendix,
+        StudyDocumentRole::Crf,
+        StudyDocumentRole::Dataset,
+        StudyDocumentRole::Define,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            StudyDocumentRole::Protocol => "protocol",
+            StudyDocumentRole::Report => "report",
+            StudyDocumentRole::Appendix => "appendix",
+            StudyDocumentRole::Crf => "crf",
+            StudyDocumentRole::Dataset => "dataset",
+            StudyDocumentRole::Define => "define",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.code() == code)
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            StudyDocumentRole::Protocol => "Protocol",
+            StudyDocumentRole::Report => "Study Report",
+            StudyDocumentRole::Appendix => "Appendix",
+            StudyDocumentRole::Crf => "Case Report Form",
+            StudyDocumentRole::Dataset => "Dataset",
+            StudyDocumentRole::Define => "Data Definition",
+        }
+    }
+
+    /// The context of use a document of this role gets in `study`.
+    pub fn context_code<'a>(&self, study: &'a Study) -> &'a str {
+        match self {
+            StudyDocumentRole::Protocol | StudyDocumentRole::Report | StudyDocumentRole::Appendix => &study.report_code,
+            StudyDocumentRole::Crf => "case-report-forms",
+            StudyDocumentRole::Dataset => "clinical-dataset",
+            StudyDocumentRole::Define => "data-definition",
+        }
+    }
+}
+
+/// One document of a study.
+#[derive(Debug, Clone)]
+pub struct StudyDocumentParams {
+    pub role: StudyDocumentRole,
+    pub file_path: PathBuf,
+    /// None: "<study id> <role>: <file name>"
+    pub title: Option<String>,
+    /// Further keywords; the study-id keyword is always added
+    pub keywords: Vec<KeywordRef>,
+    /// When the file was already submitted, e.g. a report of an earlier sequence
+    pub reuse: DocumentReuse,
+}
+
+impl EctdService {
+    pub async fn create_study(&self, application_number: &str, params: NewStudyParams) -> Result<Study> {
+        let study = NewStudy {
+            study_id: params.study_id.trim().to_string(),
+            title: params.title.trim().to_string(),
+            categories: normalize_categories(params.categories),
+            report_code: params.report_code.as_deref().unwrap_or(DEFAULT_REPORT_CODE).trim().to_string(),
+        };
+        check_study_id(&study.study_id)?;
+        for (field, value) in [("title", &study.title), ("reportCode", &study.report_code)] {
+            if value.is_empty() {
+                return Err(ServiceError::Invalid(format!("{} must not be empty", field)).into());
+            }
+        }
+        self.get_application(application_number).await?;
+
+        let repo = StudyRepository::new(self.pool.clone());
+        let mut tx = self.pool.begin().await?;
+        let Some(created) = repo.create(&mut tx, application_number, &study).await.context("Failed to create study")? else {
+            return Err(ServiceError::Conflict(format!(
+                "Study {} already exists in application {}", study.study_id, application_number
+            )).into());
+        };
+
+        self.record_audit(
+            &mut tx,
+            "study.create",
+            "study",
+            Some(created.id),
+            None,
+            Some(study_state(application_number, &created)),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    pub async fn list_studies(&self, application_number: &str) -> Result<Vec<Study>> {
+        self.get_application(application_number).await?;
+        StudyRepository::new(self.pool.clone()).list(application_number).await
+            .context("Failed to fetch studies")
+    }
+
+    pub async fn get_study(&self, application_number: &str, study_id: &str) -> Result<Study> {
+        StudyRepository::new(self.pool.clone()).get(application_number, study_id).await
+            .context("Failed to fetch study")?
+            .ok_or_else(|| study_not_found(application_number, study_id))
+    }
+
+    /// Changes the study's record. Documents already attached keep their
+    /// context of use; a new report code applies to later attachments.
+    pub async fn update_study(&self, application_number: &str, study_id: &str, patch: StudyPatch) -> Result<Study> {
+        for (field, value) in [("title", &patch.title), ("reportCode", &patch.report_code)] {
+            if value.as_deref().is_some_and(|v| v.trim().is_empty()) {
+                return Err(ServiceError::Invalid(format!("{} must not be empty", field)).into());
+            }
+        }
+        let before = self.get_study(application_number, study_id).await?;
+
+        let update = StudyUpdate {
+            title: patch.title.map(|t| t.trim().to_string()),
+            categories: patch.categories.map(normalize_categories),
+            report_code: patch.report_code.map(|c| c.trim().to_string()),
+        };
+        let mut tx = self.pool.begin().await?;
+        let after = StudyRepository::new(self.pool.clone())
+            .update(&mut tx, application_number, study_id, &update).await
+            .context("Failed to update study")?
+            .ok_or_else(|| study_not_found(application_number, study_id))?;
+        self.record_audit(
+            &mut tx,
+            "study.update",
+            "study",
+            Some(after.id),
+            Some(study_state(application_number, &before)),
+            Some(study_state(application_number, &after)),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(after)
+    }
+
+    /// The study's documents in every sequence of the application.
+    pub async fn study_documents(&self, application_number: &str, study_id: &str) -> Result<Vec<StudyDocument>> {
+        let study = self.get_study(application_number, study_id).await?;
+        StudyRepository::new(self.pool.clone())
+            .documents(application_number, KeywordType::StudyId.code_system(), &study.study_id).await
+            .context("Failed to fetch study documents")
+    }
+
+    /// Attaches documents of a study to a unit in one go. Each becomes a
+    /// context of use for its role, tagged with the study-id keyword (defined
+    /// in this unit with the study title if the application lacks it), in
+    /// the study's folder under the one for its code. Documents are ordered
+    /// by role and numbered after the code's highest priority in the
+    /// current view.
+    pub async fn attach_study_documents(
+        &self,
+        submission_id: Uuid,
+        study_id: &str,
+        mut documents: Vec<StudyDocumentParams>,
+    ) -> Result<Vec<AttachedDocument>> {
+        if documents.is_empty() {
+            return Err(ServiceError::Invalid("no study documents given".to_string()).into());
+        }
+        let application_number = self.submission_application(submission_id).await?;
+        let study = self.get_study(&application_number, study_id).await?;
+
+        let study_keyword = KeywordRef { keyword_type: KeywordType::StudyId, code: study.study_id.clone() };
+        let vocabulary = self.keyword_vocabulary(submission_id).await?;
+        match vocabulary.iter().find(|e| e.code_system == KeywordType::StudyId.code_system() && e.code == study.study_id) {
+            Some(e) if !e.is_active() => {
+                return Err(ServiceError::Invalid(format!(
+                    "Keyword '{}' was retired in sequence {}", study_keyword, e.sequence_number
+                )).into());
+            }
+            Some(_) => {}
+            None => {
+                self.put_keyword(submission_id, KeywordType::StudyId, &study.study_id, &study.title).await?;
+            }
+        }
+
+        documents.sort_by_key(|d| d.role);
+        let mut batch = Vec::with_capacity(documents.len());
+        for doc in documents {
+            let code = doc.role.context_code(&study);
+
+            let title = doc.title.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| format!(
+                "{} {}: {}",
+                study.study_id,
+                doc.role.label(),
+                doc.file_path.file_name().unwrap_or_default().to_string_lossy(),
+            ));
+            let mut keywords = vec![study_keyword.clone()];
+            keywords.extend(doc.keywords);
+
+            batch.push(AddDocumentParams {
+                submission_id,
+                file_path: doc.file_path,
+                context_code: code.to_string(),
+                title,
+                priority: None,
+                placeholder_id: None,
+                keywords,
+                folder: Some(format!("{}/{}", resolve_folder_path(code), study.study_id.to_lowercase())),
+                reuse: doc.reuse,
+            });
+        }
+
+        self.attach_documents(submission_id, batch).await
+    }
+}
+
+/// Study ids name a keyword and a folder: letters, digits, '-', '_' and '.'.
+fn check_study_id(study_id: &str) -> Result<()> {
+    let safe = !study_id.is_empty()
+        && study_id.len() <= 64
+        && !study_id.starts_with('.')
+        && study_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !safe {
+        return Err(ServiceError::Invalid(format!(
+            "study id '{}' must be 1-64 letters, digits, '-', '_' and '.'", study_id
+        )).into());
+    }
+    Ok(())
+}
+
+fn normalize_categories(categories: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for category in categories {
+        let category = category.trim().to_lowercase();
+        if !category.is_empty() && !normalized.contains(&category) {
+            normalized.push(category);
+        }
+    }
+    normalized
+}
+
+fn study_not_found(application_number: &str, study_id: &str) -> anyhow::Error {
+    ServiceError::NotFound(format!("Study {} of application {}", study_id, application_number)).into()
+}
+
+fn study_state(application_number: &str, study: &Study) -> Value {
+    json!({
+        "applicationNumber": application_number,
+        "studyId": study.study_id,
+        "title": study.title,
+        "categories": study.categories,
+        "reportCode": study.report_code,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::OffsetDateTime;
+
+    #[test]
+    fn test_study_roles_map_to_contexts_in_order() {
+        let study = Study {
+            id: Uuid::new_v4(),
+            study_id: "ABC-101".to_string(),
+            title: "Phase 3 efficacy study".to_string(),
+            categories: vec![],
+            report_code: "clinical-study-report".to_string(),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+        };
+
+        let mut roles = vec![StudyDocumentRole::Dataset, StudyDocumentRole::Appendix, StudyDocumentRole::Protocol];
+        roles.sort();
+        assert_eq!(roles, [StudyDocumentRole::Protocol, StudyDocumentRole::Appendix, StudyDocumentRole::Dataset]);
+
+        assert_eq!(StudyDocumentRole::Report.context_code(&study), "clinical-study-report");
+        assert_eq!(StudyDocumentRole::Crf.context_code(&study), "case-report-forms");
+        assert_eq!(StudyDocumentRole::from_code("define"), Some(StudyDocumentRole::Define));
+        assert_eq!(StudyDocumentRole::from_code("synopsis"), None);
+
+        assert!(check_study_id("ABC-101").is_ok());
+        assert!(check_study_id("../x").is_err());
+        assert_eq!(normalize_categories(vec![" PK ".into(), "pk".into(), "".into()]), ["pk"]);
+    }
+}
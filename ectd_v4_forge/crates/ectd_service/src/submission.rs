@@ -1,67 +1,388 @@
-use crate::EctdService;
+use crate::{EctdService, ServiceError};
 use anyhow::{Context, Result};
+use serde::Deserialize;
+use utoipa::ToSchema;
+use serde_json::{json, Value};
 use uuid::Uuid;
 use ectd_core::models::submission_unit::{
     SubmissionUnit, Submission, Application, ApplicationNumber, Applicant, SponsoringOrganization, SequenceNumber
 };
-use ectd_db::repository::SubmissionRepository;
+use ectd_core::models::keyword_definition::{
+    DisplayName, KeywordDefinition, KeywordDefinitionItem, KeywordDefinitionValue,
+};
+use ectd_db::application::{ApplicationRecord, ApplicationRepository};
+use ectd_db::planning::PlanningRepository;
+use ectd_db::repository::{SubmissionRepository, SubmissionUpdate};
+use crate::applications::NewApplicationParams;
+use crate::templates::template_placeholders;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct InitSubmissionParams {
     pub app_number: String,
-    pub app_type: String, // Added field
-    pub applicant_name: String,
-    pub sequence_number: u32,
-    pub submission_code: String,
+    /// Needed only when the application doesn't exist yet; otherwise it
+    /// must match the application (or be left out)
+    pub app_type: Option<String>,
+    /// Same rules as `app_type`
+    pub applicant_name: Option<String>,
+    /// None: the application's next sequence number
+    pub sequence_number: Option<u32>,
+    /// None: "seq-NNNN"
+    pub submission_code: Option<String>,
+    /// Template whose keywords the unit starts with and whose contexts of
+    /// use are planned as placeholders
+    pub template: Option<String>,
+}
+
+/// Changes to a submission unit header; absent fields are left untouched.
+/// The application number and sequence number identify the unit and can't be changed;
+/// application type and applicant are changed on the application.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SubmissionPatch {
+    pub unit_code: Option<String>,
+    pub status_code: Option<String>,
+    pub submission_code: Option<String>,
 }
 
 impl EctdService {
-    pub async fn create_submission(&self, params: InitSubmissionParams) -> Result<Uuid> {
+    /// Creates a unit in the application `app_number`, creating the application
+    /// too when it is new. Without a sequence number the unit becomes the
+    /// application's next sequence. The unit code is the template's, else
+    /// "original-application".
+    pub async fn create_submission(&self, mut params: InitSubmissionParams) -> Result<Uuid> {
+        let template = match &params.template {
+            Some(name) => Some(self.get_template(name).await?),
+            None => None,
+        };
+        if params.app_type.is_none() {
+            params.app_type = template.as_ref().and_then(|t| t.application_type.clone());
+        }
+
+        let app = self.resolve_application(&params).await?;
+        if let Some(t) = &template
+            && let Some(app_type) = &t.application_type
+            && !app_type.eq_ignore_ascii_case(&app.application_type)
+        {
+            return Err(ServiceError::Invalid(format!(
+                "Template '{}' is for {} applications; {} is {}",
+                t.name, app_type, app.application_number, app.application_type
+            )).into());
+        }
+        let unit_code = template.as_ref().map(|t| t.unit_code.clone())
+            .unwrap_or_else(|| "original-application".to_string());
+        let keyword_definitions = template.as_ref()
+            .map(|t| t.keywords.iter().map(|k| KeywordDefinition {
+                code: k.code.clone(),
+                code_system: k.keyword_type.code_system().to_string(),
+                value: KeywordDefinitionValue {
+                    item: KeywordDefinitionItem {
+                        code: k.code.clone(),
+                        display_name: DisplayName { value: k.display_name.clone() },
+                    },
+                },
+                status_code: None,
+            }).collect::<Vec<_>>())
+            .filter(|defs| !defs.is_empty());
+
         let submission_uuid = Uuid::new_v4();
-        let app_uuid = Uuid::new_v4();
         let unit_id = Uuid::new_v4();
+        let submission_code = params.submission_code
+            .or_else(|| params.sequence_number.map(|n| format!("seq-{:04}", n)))
+            .unwrap_or_default();
 
-        let unit = SubmissionUnit {
+        let mut unit = SubmissionUnit {
             xmlns: "urn:hl7-org:v3".to_string(),
             xmlns_xsi: Some("http://www.w3.org/2001/XMLSchema-instance".to_string()),
             schema_location: Some("urn:hl7-org:v3 ../../util/dtd/v3_0/schema/rps_schema.xsd".to_string()),
             id: unit_id.to_string(),
-            code: "submission-unit".to_string(),
+            code: unit_code,
             code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
             status_code: "active".to_string(),
 
             submission: Submission {
                 id: submission_uuid.to_string(),
-                code: params.submission_code,
+                code: submission_code,
                 code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
-                sequence_number: SequenceNumber { value: params.sequence_number },
+                sequence_number: SequenceNumber { value: params.sequence_number.unwrap_or_default() },
             },
 
             application: Application {
-                id: app_uuid.to_string(),
-                code: params.app_type, // Use parameter
+                id: app.id.to_string(),
+                code: app.application_type,
                 code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
                 application_number: ApplicationNumber {
-                    code: params.app_number,
+                    code: app.application_number,
                     code_system: "urn:oid:2.16.840.1.113883.3.989.2.2.1".to_string(),
                 },
             },
 
             applicant: Applicant {
                 sponsoring_organization: SponsoringOrganization {
-                    name: params.applicant_name,
+                    name: app.applicant_name,
                 },
             },
 
             context_of_use: vec![],
             documents: vec![],
-            keyword_definitions: None,
+            keyword_definitions,
         };
 
         let repo = SubmissionRepository::new(self.pool.clone());
-        repo.create_submission(&unit).await
-            .context("Failed to persist submission unit")?;
+        let mut tx = self.pool.begin().await?;
+        let created = match params.sequence_number {
+            Some(_) => repo.create_submission(&mut tx, &unit).await,
+            None => repo.create_next_submission(&mut tx, &mut unit).await,
+        };
+        match created {
+            Err(sqlx::Error::Database(e)) if e.constraint() == Some("unique_sequence_per_application") => {
+                return Err(ServiceError::Conflict(format!(
+                    "Application {} already has sequence {:04}",
+                    unit.application.application_number.code, unit.submission.sequence_number.value
+                )).into());
+            }
+            other => other.context("Failed to persist submission unit")?,
+        };
+
+        let placeholders = template.as_ref().map(template_placeholders).unwrap_or_default();
+        if !placeholders.is_empty() {
+            PlanningRepository::new(self.pool.clone()).create_all(&mut tx, unit_id, &placeholders).await
+                .context("Failed to plan the template's contexts of use")?;
+        }
+
+        self.record_audit(
+            &mut tx,
+            "submission.create",
+            "submission_unit",
+            Some(unit_id),
+            None,
+            Some(json!({
+                "unitCode": unit.code,
+                "applicationType": unit.application.code,
+                "applicationNumber": unit.application.application_number.code,
+                "applicant": unit.applicant.sponsoring_organization.name,
+                "sequenceNumber": unit.submission.sequence_number.value,
+                "submissionCode": unit.submission.code,
+                "template": params.template,
+                "placeholders": placeholders.len(),
+            })),
+        ).await?;
+        tx.commit().await?;
 
         Ok(unit_id)
     }
+
+    /// The application a new unit goes into. A new application is created from
+    /// the params; an existing one must not be contradicted by them, since
+    /// every unit of an application names the same type and applicant.
+    async fn resolve_application(&self, params: &InitSubmissionParams) -> Result<ApplicationRecord> {
+        let number = params.app_number.trim();
+        if number.is_empty() {
+            return Err(ServiceError::Invalid("application number must not be empty".to_string()).into());
+        }
+
+        let repo = ApplicationRepository::new(self.pool.clone());
+        let existing = repo.get(number).await
+            .context("Failed to fetch application")?;
+        let app = match existing {
+            Some(app) => app,
+            None => {
+                let (Some(app_type), Some(applicant)) = (&params.app_type, &params.applicant_name) else {
+                    return Err(ServiceError::Invalid(format!(
+                        "Application {} doesn't exist yet; give its type and applicant to create it", number
+                    )).into());
+                };
+                let created = self.create_application(NewApplicationParams {
+                    application_number: number.to_string(),
+                    application_type: app_type.clone(),
+                    region: None,
+                    applicant_name: applicant.clone(),
+                    product_name: None,
+                    related_applications: vec![],
+                }).await;
+                match created {
+                    Ok(app) => return Ok(app),
+                    // A concurrent init created it first; use theirs if it agrees
+                    Err(e) if matches!(e.downcast_ref(), Some(ServiceError::Conflict(_))) => {
+                        match repo.get(number).await.context("Failed to fetch application")? {
+                            Some(app) => app,
+                            None => return Err(e),
+                        }
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+
+        if let Some(app_type) = &params.app_type
+            && !app_type.trim().eq_ignore_ascii_case(&app.application_type)
+        {
+            return Err(ServiceError::Invalid(format!(
+                "Application {} is of type {}, not {}; change the application instead",
+                number, app.application_type, app_type
+            )).into());
+        }
+        if let Some(applicant) = &params.applicant_name
+            && applicant.trim() != app.applicant_name
+        {
+            return Err(ServiceError::Invalid(format!(
+                "Application {} belongs to '{}', not '{}'; change the application instead",
+                number, app.applicant_name, applicant
+            )).into());
+        }
+        Ok(app)
+    }
+
+    pub async fn get_submission(&self, id: Uuid) -> Result<SubmissionUnit> {
+        let repo = SubmissionRepository::new(self.pool.clone());
+        match repo.get_submission(id).await {
+            Ok(unit) => Ok(unit),
+            Err(sqlx::Error::RowNotFound) => Err(ServiceError::submission_not_found(id).into()),
+            Err(e) => Err(e).context("Failed to fetch submission from DB"),
+        }
+    }
+
+    /// Application number of a unit, e.g. for access checks.
+    pub async fn submission_application(&self, id: Uuid) -> Result<String> {
+        let repo = SubmissionRepository::new(self.pool.clone());
+        repo.application_number(id).await
+            .context("Failed to look up submission unit")?
+            .ok_or_else(|| ServiceError::submission_not_found(id).into())
+    }
+
+    pub async fn update_submission(&self, id: Uuid, patch: SubmissionPatch) -> Result<SubmissionUnit> {
+        if let Some(status) = &patch.status_code {
+            check_status(status)?;
+        }
+        for (field, value) in [
+            ("unitCode", &patch.unit_code),
+            ("submissionCode", &patch.submission_code),
+        ] {
+            if value.as_deref().is_some_and(|v| v.trim().is_empty()) {
+                return Err(ServiceError::Invalid(format!("{} must not be empty", field)).into());
+            }
+        }
+
+        let before = self.get_submission(id).await?;
+
+        let repo = SubmissionRepository::new(self.pool.clone());
+        let update = SubmissionUpdate {
+            code: patch.unit_code,
+            status_code: patch.status_code,
+            submission_code: patch.submission_code,
+        };
+        let before_state = header_state(&before);
+        let mut after = before;
+        if let Some(code) = &update.code {
+            after.code = code.clone();
+        }
+        if let Some(status) = &update.status_code {
+            after.status_code = status.clone();
+        }
+        if let Some(submission_code) = &update.submission_code {
+            after.submission.code = submission_code.clone();
+        }
+
+        let mut tx = self.pool.begin().await?;
+        if !repo.update_submission(&mut tx, id, &update).await.context("Failed to update submission unit")? {
+            return Err(ServiceError::submission_not_found(id).into());
+        }
+        self.record_audit(
+            &mut tx,
+            "submission.update",
+            "submission_unit",
+            Some(id),
+            Some(before_state),
+            Some(header_state(&after)),
+        ).await?;
+        tx.commit().await?;
+
+        Ok(after)
+    }
+}
+
+/// The v4.0 status codes for units and contexts of use.
+pub(crate) fn check_status(status: &str) -> Result<()> {
+    match status {
+        "active" | "suspended" => Ok(()),
+        other => Err(ServiceError::Invalid(format!(
+            "Unknown status '{}' (expected active or suspended)", other
+        )).into()),
+    }
+}
+
+fn header_state(unit: &SubmissionUnit) -> Value {
+    json!({
+        "unitCode": unit.code,
+        "statusCode": unit.status_code,
+        "submissionCode": unit.submission.code,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    async fn sequence_of(service: &EctdService, id: Uuid) -> (u32, String) {
+        let unit = service.get_submission(id).await.unwrap();
+        (unit.submission.sequence_number.value, unit.submission.code)
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_first_unit_is_sequence_0001(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+        let first = testing::new_unit(&service, "100001").await;
+        let second = testing::new_unit(&service, "100001").await;
+        let other = testing::new_unit(&service, "100002").await;
+
+        assert_eq!(sequence_of(&service, first).await, (1, "seq-0001".to_string()));
+        assert_eq!(sequence_of(&service, second).await, (2, "seq-0002".to_string()));
+        assert_eq!(sequence_of(&service, other).await, (1, "seq-0001".to_string()));
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_concurrent_inits_get_distinct_sequences(pool: sqlx::PgPool) {
+        let service = testing::service(pool).await;
+
+        let ids = futures::future::join_all((0..8).map(|_| testing::new_unit(&service, "100001"))).await;
+
+        let mut sequences = Vec::new();
+        for id in ids {
+            sequences.push(sequence_of(&service, id).await.0);
+        }
+        sequences.sort();
+        assert_eq!(sequences, (1..=8).collect::<Vec<u32>>());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_template_keywords_take_the_code_system_of_their_type(pool: sqlx::PgPool) {
+        use ectd_core::models::keyword_definition::KeywordType;
+        let service = testing::service(pool).await;
+        let template = serde_json::from_value(json!({
+            "name": "nda-original",
+            "unitCode": "original-application",
+            "keywords": [
+                { "code": "peds", "displayName": "Paediatric population" },
+                { "type": "study-id", "code": "ABC-101", "displayName": "Study ABC-101" },
+            ],
+        })).unwrap();
+        service.save_template(template).await.unwrap();
+
+        let id = service.create_submission(InitSubmissionParams {
+            app_number: "100001".to_string(),
+            app_type: Some("nda".to_string()),
+            applicant_name: Some("Acme Pharma".to_string()),
+            template: Some("nda-original".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+
+        let mut defined: Vec<_> = service.list_keywords(id).await.unwrap().into_iter()
+            .map(|k| (k.code, KeywordType::from_code_system(&k.code_system)))
+            .collect();
+        defined.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(defined, [
+            ("ABC-101".to_string(), Some(KeywordType::StudyId)),
+            ("peds".to_string(), Some(KeywordType::Sponsor)),
+        ]);
+    }
 }
@@ -0,0 +1,123 @@
+use crate::planning::check_folder;
+use crate::{EctdService, ServiceError};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use ectd_db::planning::NewPlaceholder;
+use ectd_db::template::{SubmissionTemplate, TemplateRepository};
+
+impl EctdService {
+    pub async fn list_templates(&self) -> Result<Vec<SubmissionTemplate>> {
+        TemplateRepository::new(self.pool.clone()).list().await
+            .context("Failed to fetch templates")
+    }
+
+    pub async fn get_template(&self, name: &str) -> Result<SubmissionTemplate> {
+        TemplateRepository::new(self.pool.clone()).get(name).await
+            .context("Failed to fetch template")?
+            .ok_or_else(|| ServiceError::NotFound(format!("Template '{}'", name)).into())
+    }
+
+    /// Creates or replaces a template; returns it as stored and whether it is new.
+    /// Units already created from it keep what they were created with.
+    pub async fn save_template(&self, template: SubmissionTemplate) -> Result<(SubmissionTemplate, bool)> {
+        let template = normalize(template)?;
+
+        let repo = TemplateRepository::new(self.pool.clone());
+        let before = repo.get(&template.name).await.context("Failed to fetch template")?;
+        let mut tx = self.pool.begin().await?;
+        let created = repo.save(&mut tx, &template).await.context("Failed to save template")?;
+        self.record_audit(
+            &mut tx,
+            "template.save",
+            "submission_template",
+            None,
+            before.as_ref().map(template_state),
+            Some(template_state(&template)),
+        ).await?;
+        tx.commit().await?;
+
+        Ok((template, created))
+    }
+
+    pub async fn delete_template(&self, name: &str) -> Result<()> {
+        let before = self.get_template(name).await?;
+
+        let repo = TemplateRepository::new(self.pool.clone());
+        let mut tx = self.pool.begin().await?;
+        repo.delete(&mut tx, name).await.context("Failed to delete template")?;
+        self.record_audit(
+            &mut tx,
+            "template.delete",
+            "submission_template",
+            None,
+            Some(template_state(&before)),
+            None,
+        ).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// The template's contexts of use as placeholders of a new unit.
+pub(crate) fn template_placeholders(template: &SubmissionTemplate) -> Vec<NewPlaceholder> {
+    template.contexts.iter()
+        .map(|c| NewPlaceholder {
+            code: c.code.clone(),
+            title: c.title.clone(),
+            owner: None,
+            due_date: None,
+            note: None,
+            required: c.required,
+            priority: c.priority.map(|p| p as i32),
+            folder: c.folder.clone(),
+        })
+        .collect()
+}
+
+fn normalize(mut t: SubmissionTemplate) -> Result<SubmissionTemplate> {
+    let invalid = |message: String| -> anyhow::Error { ServiceError::Invalid(message).into() };
+
+    t.name = t.name.trim().to_string();
+    if t.name.is_empty() || !t.name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        return Err(invalid(format!("template name '{}' must be lowercase letters, digits and dashes", t.name)));
+    }
+    t.unit_code = t.unit_code.trim().to_string();
+    if t.unit_code.is_empty() {
+        return Err(invalid("unitCode must not be empty".to_string()));
+    }
+    t.application_type = t.application_type
+        .map(|a| a.trim().to_lowercase())
+        .filter(|a| !a.is_empty());
+
+    for c in &mut t.contexts {
+        c.code = c.code.trim().to_string();
+        if c.code.is_empty() {
+            return Err(invalid("context codes must not be empty".to_string()));
+        }
+        if c.priority == Some(0) {
+            return Err(invalid(format!("priority of '{}' must be 1 or greater", c.code)));
+        }
+        if let Some(folder) = &c.folder {
+            check_folder(folder)?;
+            c.folder = Some(folder.trim_matches('/').to_string());
+        }
+    }
+
+    for (i, k) in t.keywords.iter().enumerate() {
+        if k.code.trim().is_empty() || k.display_name.trim().is_empty() {
+            return Err(invalid("keyword code and displayName must not be empty".to_string()));
+        }
+        if t.keywords[..i].iter().any(|other| other.keyword_type == k.keyword_type && other.code == k.code) {
+            return Err(invalid(format!("keyword '{}:{}' is defined twice", k.keyword_type.code(), k.code)));
+        }
+    }
+
+    Ok(t)
+}
+
+fn template_state(t: &SubmissionTemplate) -> Value {
+    json!({
+        "name": t.name,
+        "definition": t,
+    })
+}
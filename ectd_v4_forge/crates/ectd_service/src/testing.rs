@@ -0,0 +1,59 @@
+//! Fixtures for the tests that run against a database. They use
+//! `#[sqlx::test(migrations = false)]`, which creates a scratch database per
+//! test from DATABASE_URL; `service` brings it to the current schema.
+
+use crate::storage::MemoryStore;
+use crate::{AddDocumentParams, AttachedDocument, DocumentReuse, EctdService, InitSubmissionParams};
+use anyhow::Result;
+use sqlx::PgPool;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// A service over a migrated scratch database and an in-memory store.
+pub(crate) async fn service(pool: PgPool) -> EctdService {
+    ectd_db::schema::migrate(&pool).await.expect("migrations apply");
+    EctdService::new(pool, Arc::new(MemoryStore::new())).with_actor("tester")
+}
+
+/// The application's next sequence; the application is created on first use.
+pub(crate) async fn new_unit(service: &EctdService, app_number: &str) -> Uuid {
+    service.create_submission(InitSubmissionParams {
+        app_number: app_number.to_string(),
+        app_type: Some("nda".to_string()),
+        applicant_name: Some("Acme Pharma".to_string()),
+        sequence_number: None,
+        submission_code: None,
+        template: None,
+    }).await.expect("unit is created")
+}
+
+/// A file with `content` in a directory of its own.
+pub(crate) fn file(name: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ectd-test-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+/// Attaches `path` under a new context of use `code`.
+pub(crate) async fn attach(
+    service: &EctdService,
+    unit_id: Uuid,
+    path: &Path,
+    code: &str,
+    reuse: DocumentReuse,
+) -> Result<AttachedDocument> {
+    service.attach_document(AddDocumentParams {
+        submission_id: unit_id,
+        file_path: path.to_path_buf(),
+        context_code: code.to_string(),
+        title: format!("{} {}", code, path.file_name().unwrap_or_default().to_string_lossy()),
+        priority: None,
+        placeholder_id: None,
+        keywords: vec![],
+        folder: None,
+        reuse,
+    }).await
+}
@@ -0,0 +1,79 @@
+use crate::EctdService;
+use anyhow::{Context, Result};
+use uuid::Uuid;
+
+use ectd_core::get_standard_validator;
+use ectd_core::validation::{ValidationError, rules::{RuleDocumentReference, RuleKeywordResolution, RulePriorityOrder}};
+use ectd_db::blob::BlobRepository;
+use ectd_db::keyword::KeywordRepository;
+use ectd_db::priority::PriorityRepository;
+use ectd_db::repository::SubmissionRepository;
+
+impl EctdService {
+    /// Runs the standard validation profile against a stored unit, plus the
+    /// keyword, priority and document reference checks, which need the
+    /// definitions, contexts and documents of the earlier sequences.
+    ///
+    /// The document rules open files by reference, so the documents are
+    /// fetched into a scratch folder first; findings still name the
+    /// package paths, not the scratch copies.
+    pub async fn validate_submission(&self, id: Uuid) -> Result<Vec<ValidationError>> {
+        let mut unit = self.get_submission(id).await?;
+        let storage_keys = BlobRepository::new(self.pool.clone()).storage_keys(id).await
+            .context("Failed to resolve storage keys")?;
+        let inherited = KeywordRepository::new(self.pool.clone()).inherited(id).await
+            .context("Failed to fetch keywords")?
+            .into_iter()
+            .filter(|k| k.is_active())
+            .map(|k| (k.code_system, k.code))
+            .collect();
+        let inherited_priorities = PriorityRepository::new(self.pool.clone()).inherited(id).await
+            .context("Failed to fetch the current view")?
+            .into_iter()
+            .map(|c| (c.code, c.priority as u32))
+            .collect();
+        let inherited_documents = SubmissionRepository::new(self.pool.clone()).inherited_document_ids(id).await
+            .context("Failed to fetch earlier documents")?
+            .into_iter()
+            .map(|d| d.to_string())
+            .collect();
+
+        let scratch = std::env::temp_dir().join(format!("ectd-validate-{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&scratch).await
+            .context(format!("Failed to create scratch dir: {:?}", scratch))?;
+
+        let mut renamed = Vec::new();
+        let fetched: Result<()> = async {
+            for doc in unit.documents.iter_mut() {
+                let key = Uuid::parse_str(&doc.id).ok()
+                    .and_then(|u| storage_keys.get(&u).cloned())
+                    .unwrap_or_else(|| doc.id.clone());
+                let dest = scratch.join(&doc.id);
+                self.store.get_to_file(&key, &dest).await
+                    .context(format!("Failed to fetch {} from storage", doc.text.reference.value))?;
+
+                let local = dest.to_string_lossy().to_string();
+                renamed.push((local.clone(), doc.text.reference.value.clone()));
+                doc.text.reference.value = local;
+            }
+            Ok(())
+        }.await;
+
+        let findings = fetched.map(|_| {
+            get_standard_validator()
+                .add_rule(RuleKeywordResolution { inherited })
+                .add_rule(RulePriorityOrder { inherited: inherited_priorities })
+                .add_rule(RuleDocumentReference { inherited: inherited_documents })
+                .run(&unit)
+        });
+        let _ = tokio::fs::remove_dir_all(&scratch).await;
+
+        let mut findings = findings?;
+        for finding in findings.iter_mut() {
+            for (local, href) in &renamed {
+                finding.message = finding.message.replace(local.as_str(), href);
+            }
+        }
+        Ok(findings)
+    }
+}
@@ -0,0 +1,362 @@
+use anyhow::{Context, Result};
+use quick_xml::de::from_str;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use ectd_core::models::submission_unit::SubmissionUnit;
+use ectd_core::validation::{ValidationError, ValidationRule, rules::RuleDocumentReference};
+use crate::ingest::SUBMISSION_UNIT_FILE;
+use ectd_core::{get_standard_validator, media_type};
+
+const MANIFEST_FILE: &str = "sha256.txt";
+
+/// Outcome of re-reading an exported package from disk.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageReport {
+    /// The sequence folder that holds `submissionunit.xml`.
+    pub root: PathBuf,
+    pub files_checked: usize,
+    /// Package integrity problems followed by validation-profile findings.
+    pub findings: Vec<ValidationError>,
+}
+
+impl PackageReport {
+    /// Go means nothing blocks transmission; warnings are allowed.
+    pub fn is_go(&self) -> bool {
+        !self.findings.iter().any(|f| f.severity.contains("High Error"))
+    }
+
+    fn fail(&mut self, code: &str, message: String, target: Option<&str>) {
+        self.findings.push(ValidationError {
+            code: code.to_string(),
+            severity: "High Error".to_string(),
+            message,
+            target_id: target.map(str::to_string),
+        });
+    }
+}
+
+/// Verifies an exported package without touching the database:
+///
+/// 1. every `sha256.txt` entry exists and matches,
+/// 2. nothing is on disk that the manifest doesn't list,
+/// 3. `submissionunit.xml` parses,
+/// 4. every referenced document exists, matches its `integrityCheck`
+///    and its declared media type,
+/// 5. the standard validation profile passes, and every document reference
+///    resolves to a document of the package or of an earlier sequence
+///    exported alongside it (`<application>/<sequence>/`).
+///
+/// `dir` may be the sequence folder itself or a transmission root
+/// (`<application>/<sequence>/`).
+pub fn verify_package(dir: &Path) -> Result<PackageReport> {
+    let root = locate_sequence_root(dir)?;
+    let mut report = PackageReport { root: root.clone(), files_checked: 0, findings: Vec::new() };
+
+    // 1. Manifest
+    let manifest = match fs::read_to_string(root.join(MANIFEST_FILE)) {
+        Ok(text) => parse_manifest(&text, &mut report),
+        Err(e) => {
+            report.fail("PKG-001", format!("Cannot read {}: {}", MANIFEST_FILE, e), Some(MANIFEST_FILE));
+            BTreeMap::new()
+        }
+    };
+
+    // Hash each file once; the manifest and the XML both refer to them
+    let mut hashes: BTreeMap<String, String> = BTreeMap::new();
+    for (rel, expected) in &manifest {
+        let Some(path) = resolve_inside(&root, rel) else {
+            report.fail("PKG-001", format!("Manifest entry escapes the package: {}", rel), Some(rel));
+            continue;
+        };
+        match hash_file(&path) {
+            Ok(actual) => {
+                report.files_checked += 1;
+                if !actual.eq_ignore_ascii_case(expected) {
+                    report.fail("PKG-002", format!("Checksum mismatch for {} (manifest {}, actual {})", rel, expected, actual), Some(rel));
+                }
+                hashes.insert(rel.clone(), actual);
+            }
+            Err(e) => report.fail("PKG-002", format!("Manifest lists {} but it cannot be read: {}", rel, e), Some(rel)),
+        }
+    }
+
+    // 2. Extra files
+    for rel in list_files(&root)? {
+        if !manifest.contains_key(&rel) && rel != MANIFEST_FILE {
+            report.fail("PKG-007", format!("File is not part of the package manifest: {}", rel), Some(&rel));
+        }
+    }
+
+    // 3. Structure
+    let xml = fs::read_to_string(root.join(SUBMISSION_UNIT_FILE))
+        .context(format!("Failed to read {:?}", root.join(SUBMISSION_UNIT_FILE)))?;
+    let mut unit: SubmissionUnit = match from_str(&xml) {
+        Ok(u) => u,
+        Err(e) => {
+            report.fail("PKG-003", format!("{} is not well-formed eCTD v4.0 XML: {}", SUBMISSION_UNIT_FILE, e), Some(SUBMISSION_UNIT_FILE));
+            return Ok(report);
+        }
+    };
+
+    // 4. Referenced documents
+    for doc in unit.documents.iter_mut() {
+        let rel = doc.text.reference.value.clone();
+        let Some(path) = resolve_inside(&root, &rel) else {
+            report.fail("PKG-004", format!("Document reference escapes the package: {}", rel), Some(&doc.id));
+            continue;
+        };
+        if !path.is_file() {
+            report.fail("PKG-004", format!("Referenced file is missing: {}", rel), Some(&doc.id));
+            continue;
+        }
+
+        let actual = match hashes.get(&rel) {
+            Some(h) => h.clone(),
+            None => {
+                report.fail("PKG-008", format!("Referenced file is not listed in {}: {}", MANIFEST_FILE, rel), Some(&doc.id));
+                match hash_file(&path) {
+                    Ok(h) => h,
+                    Err(e) => {
+                        report.fail("PKG-004", format!("Cannot read {}: {}", rel, e), Some(&doc.id));
+                        continue;
+                    }
+                }
+            }
+        };
+        if !actual.eq_ignore_ascii_case(&doc.text.checksum) {
+            report.fail("PKG-005", format!("integrityCheck mismatch for {} (XML {}, actual {})", rel, doc.text.checksum, actual), Some(&doc.id));
+        }
+
+        let declared = media_type::normalize(&doc.text.media_type);
+        if let Some(detected) = sniff(&path)?
+            && !declared.is_empty() && declared != detected
+        {
+            report.fail("PKG-006", format!("{} is declared as '{}' but its content is '{}'", rel, doc.text.media_type, detected), Some(&doc.id));
+        }
+
+        // The validation rules open files by reference, so point them at the package
+        doc.text.reference.value = path.to_string_lossy().to_string();
+    }
+
+    // 5. Validation profile
+    report.findings.extend(get_standard_validator().run(&unit));
+
+    let (inherited, history) = earlier_documents(&root, &unit);
+    let mut references = RuleDocumentReference { inherited }.check(&unit);
+    if !history {
+        // The document may well be in an earlier sequence that just isn't here
+        for finding in references.iter_mut() {
+            finding.severity = "Warning".to_string();
+            finding.message.push_str(" (earlier sequences are not alongside this package to check)");
+        }
+    }
+    report.findings.extend(references);
+
+    Ok(report)
+}
+
+/// Document ids of the earlier sequences of the unit's application found next
+/// to `root` (sequence folders of a transmission root), and whether the
+/// history is there at all: sequence 1 has none, a later one needs at least
+/// one earlier folder.
+fn earlier_documents(root: &Path, unit: &SubmissionUnit) -> (Vec<String>, bool) {
+    let sequence = unit.submission.sequence_number.value;
+    let mut ids = Vec::new();
+    let mut found = false;
+
+    let siblings = root.parent().and_then(|parent| fs::read_dir(parent).ok());
+    for entry in siblings.into_iter().flatten().flatten() {
+        let earlier = entry.file_name().to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+            .is_some_and(|n| n < sequence);
+        if !earlier {
+            continue;
+        }
+        let Ok(xml) = fs::read_to_string(entry.path().join(SUBMISSION_UNIT_FILE)) else { continue };
+        let Ok(other) = from_str::<SubmissionUnit>(&xml) else { continue };
+        if other.application.application_number.code != unit.application.application_number.code {
+            continue;
+        }
+        found = true;
+        ids.extend(other.documents.into_iter().map(|d| d.id));
+    }
+
+    (ids, found || sequence <= 1)
+}
+
+/// Finds the folder holding `submissionunit.xml`: `dir` itself, or the
+/// single `<application>/<sequence>/` folder below a transmission root.
+fn locate_sequence_root(dir: &Path) -> Result<PathBuf> {
+    if dir.join(SUBMISSION_UNIT_FILE).is_file() {
+        return Ok(dir.to_path_buf());
+    }
+
+    let mut candidates = Vec::new();
+    for app in fs::read_dir(dir).context(format!("Failed to read package dir: {:?}", dir))? {
+        let app = app?.path();
+        if !app.is_dir() {
+            continue;
+        }
+        for seq in fs::read_dir(&app)? {
+            let seq = seq?.path();
+            if seq.join(SUBMISSION_UNIT_FILE).is_file() {
+                candidates.push(seq);
+            }
+        }
+    }
+
+    match candidates.len() {
+        0 => anyhow::bail!("No {} found in {:?}", SUBMISSION_UNIT_FILE, dir),
+        1 => Ok(candidates.remove(0)),
+        n => anyhow::bail!("{} sequences found in {:?}; point --dir at one of them", n, dir),
+    }
+}
+
+/// `<hex>  <path>` per line, as written by export (and `sha256sum`).
+fn parse_manifest(text: &str, report: &mut PackageReport) -> BTreeMap<String, String> {
+    let mut entries = BTreeMap::new();
+    for (n, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.split_once("  ") {
+            Some((hash, rel)) if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+                entries.insert(rel.to_string(), hash.to_lowercase());
+            }
+            _ => report.fail("PKG-001", format!("Malformed {} line {}: {}", MANIFEST_FILE, n + 1, line), Some(MANIFEST_FILE)),
+        }
+    }
+    entries
+}
+
+/// Joins a '/'-separated package path onto `root`, refusing anything that
+/// could point outside of it.
+fn resolve_inside(root: &Path, rel: &str) -> Option<PathBuf> {
+    let rel = Path::new(rel);
+    if rel.components().all(|c| matches!(c, Component::Normal(_))) {
+        Some(root.join(rel))
+    } else {
+        None
+    }
+}
+
+/// Every regular file below `root`, as sorted '/'-separated relative paths.
+fn list_files(root: &Path) -> Result<BTreeSet<String>> {
+    let mut files = BTreeSet::new();
+    let mut pending = vec![PathBuf::new()];
+
+    while let Some(rel_dir) = pending.pop() {
+        for entry in fs::read_dir(root.join(&rel_dir))? {
+            let entry = entry?;
+            let rel = rel_dir.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                pending.push(rel);
+            } else {
+                files.insert(rel.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn sniff(path: &Path) -> Result<Option<&'static str>> {
+    let mut head = Vec::with_capacity(media_type::SNIFF_LEN);
+    File::open(path)?.take(media_type::SNIFF_LEN as u64).read_to_end(&mut head)?;
+    Ok(media_type::detect(&head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ectd-verify-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_manifest_mismatch_and_extra_files_are_no_go() {
+        let dir = scratch("manifest");
+        fs::write(dir.join("a.txt"), b"hello").unwrap();
+        fs::write(dir.join("stray.txt"), b"left behind").unwrap();
+        fs::write(dir.join(MANIFEST_FILE), format!("{}  a.txt\n", "0".repeat(64))).unwrap();
+        fs::write(dir.join(SUBMISSION_UNIT_FILE), "not xml").unwrap();
+        let report = verify_package(&dir).unwrap();
+        let codes: Vec<&str> = report.findings.iter().map(|f| f.code.as_str()).collect();
+
+        assert!(!report.is_go());
+        assert!(codes.contains(&"PKG-002"), "{:?}", codes);
+        assert!(codes.contains(&"PKG-007"), "{:?}", codes);
+        assert!(codes.contains(&"PKG-003"), "{:?}", codes);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_references_resolve_against_earlier_sequences_alongside(pool: sqlx::PgPool) {
+        use crate::{testing, DocumentReuse, ExportOptions};
+        use futures::StreamExt;
+
+        let service = testing::service(pool).await;
+        let letter = testing::file("letter.txt", "letter of authorization");
+        let first = testing::new_unit(&service, "100001").await;
+        testing::attach(&service, first, &letter, "cover-letter", DocumentReuse::Ask).await.unwrap();
+        let second = testing::new_unit(&service, "100001").await;
+        testing::attach(&service, second, &letter, "cover-letter", DocumentReuse::Reference).await.unwrap();
+
+        let root = scratch("references");
+        for unit in [first, second] {
+            let options = ExportOptions { transmission_layout: true, ..Default::default() };
+            let mut export = service.export_package_stream(unit, root.clone(), options);
+            while let Some(progress) = export.next().await {
+                progress.unwrap();
+            }
+        }
+        let severities = |dir: &Path| -> Vec<String> {
+            let report = verify_package(dir).unwrap();
+            report.findings.into_iter().filter(|f| f.code == "DOC-001").map(|f| f.severity).collect()
+        };
+
+        let sequence = root.join("100001").join("0002");
+        assert!(severities(&sequence).is_empty());
+
+        // Without sequence 0001 the reference can't be checked, which only warrants a warning
+        fs::remove_dir_all(root.join("100001").join("0001")).unwrap();
+        assert_eq!(severities(&sequence), ["Warning"]);
+
+        // A context pointing at no document at all is an error
+        let xml_path = sequence.join(SUBMISSION_UNIT_FILE);
+        let xml = fs::read_to_string(&xml_path).unwrap();
+        let mut unit: SubmissionUnit = from_str(&xml).unwrap();
+        unit.submission.sequence_number.value = 1;
+        fs::write(&xml_path, unit.to_xml().unwrap()).unwrap();
+        assert_eq!(severities(&sequence), ["High Error"]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_inside_rejects_escapes() {
+        let root = Path::new("/pkg");
+        assert_eq!(resolve_inside(root, "m1/us/a.pdf"), Some(PathBuf::from("/pkg/m1/us/a.pdf")));
+        assert_eq!(resolve_inside(root, "../etc/passwd"), None);
+        assert_eq!(resolve_inside(root, "/etc/passwd"), None);
+        assert_eq!(resolve_inside(root, "m1/./a.pdf"), Some(PathBuf::from("/pkg/m1/a.pdf")));
+    }
+}